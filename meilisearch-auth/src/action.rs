@@ -24,6 +24,10 @@ pub enum Action {
     IndexesDelete = actions::INDEXES_DELETE,
     #[serde(rename = "tasks.get")]
     TasksGet = actions::TASKS_GET,
+    #[serde(rename = "tasks.cancel")]
+    TasksCancel = actions::TASKS_CANCEL,
+    #[serde(rename = "tasks.delete")]
+    TasksDelete = actions::TASKS_DELETE,
     #[serde(rename = "settings.get")]
     SettingsGet = actions::SETTINGS_GET,
     #[serde(rename = "settings.update")]
@@ -44,6 +48,12 @@ pub enum Action {
     KeysUpdate = actions::KEYS_UPDATE,
     #[serde(rename = "keys.delete")]
     KeysDelete = actions::KEYS_DELETE,
+    #[serde(rename = "webhooks.get")]
+    WebhooksGet = actions::WEBHOOKS_GET,
+    #[serde(rename = "webhooks.create")]
+    WebhooksCreate = actions::WEBHOOKS_CREATE,
+    #[serde(rename = "webhooks.delete")]
+    WebhooksDelete = actions::WEBHOOKS_DELETE,
 }
 
 impl Action {
@@ -60,6 +70,8 @@ impl Action {
             INDEXES_UPDATE => Some(Self::IndexesUpdate),
             INDEXES_DELETE => Some(Self::IndexesDelete),
             TASKS_GET => Some(Self::TasksGet),
+            TASKS_CANCEL => Some(Self::TasksCancel),
+            TASKS_DELETE => Some(Self::TasksDelete),
             SETTINGS_GET => Some(Self::SettingsGet),
             SETTINGS_UPDATE => Some(Self::SettingsUpdate),
             STATS_GET => Some(Self::StatsGet),
@@ -70,6 +82,9 @@ impl Action {
             KEYS_GET => Some(Self::KeysGet),
             KEYS_UPDATE => Some(Self::KeysUpdate),
             KEYS_DELETE => Some(Self::KeysDelete),
+            WEBHOOKS_GET => Some(Self::WebhooksGet),
+            WEBHOOKS_CREATE => Some(Self::WebhooksCreate),
+            WEBHOOKS_DELETE => Some(Self::WebhooksDelete),
             _otherwise => None,
         }
     }
@@ -87,6 +102,8 @@ impl Action {
             Self::IndexesUpdate => INDEXES_UPDATE,
             Self::IndexesDelete => INDEXES_DELETE,
             Self::TasksGet => TASKS_GET,
+            Self::TasksCancel => TASKS_CANCEL,
+            Self::TasksDelete => TASKS_DELETE,
             Self::SettingsGet => SETTINGS_GET,
             Self::SettingsUpdate => SETTINGS_UPDATE,
             Self::StatsGet => STATS_GET,
@@ -97,6 +114,9 @@ impl Action {
             Self::KeysGet => KEYS_GET,
             Self::KeysUpdate => KEYS_UPDATE,
             Self::KeysDelete => KEYS_DELETE,
+            Self::WebhooksGet => WEBHOOKS_GET,
+            Self::WebhooksCreate => WEBHOOKS_CREATE,
+            Self::WebhooksDelete => WEBHOOKS_DELETE,
         }
     }
 }
@@ -122,4 +142,9 @@ pub mod actions {
     pub const KEYS_GET: u8 = 17;
     pub const KEYS_UPDATE: u8 = 18;
     pub const KEYS_DELETE: u8 = 19;
+    pub const TASKS_CANCEL: u8 = 20;
+    pub const TASKS_DELETE: u8 = 21;
+    pub const WEBHOOKS_GET: u8 = 22;
+    pub const WEBHOOKS_CREATE: u8 = 23;
+    pub const WEBHOOKS_DELETE: u8 = 24;
 }
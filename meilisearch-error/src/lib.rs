@@ -133,6 +133,8 @@ pub enum Code {
 
     Filter,
     Sort,
+    InvalidFacetSearchFacetName,
+    InvalidDocumentEditionFunction,
 
     BadParameter,
     BadRequest,
@@ -147,6 +149,10 @@ pub enum Code {
     NoSpaceLeftOnDevice,
     DumpNotFound,
     TaskNotFound,
+    MissingTaskFilters,
+    BatchNotFound,
+    WebhookNotFound,
+    InvalidWebhookUrl,
     PayloadTooLarge,
     RetrieveDocument,
     SearchDocuments,
@@ -217,6 +223,14 @@ impl Code {
             Filter => ErrCode::invalid("invalid_filter", StatusCode::BAD_REQUEST),
             // error related to sorts
             Sort => ErrCode::invalid("invalid_sort", StatusCode::BAD_REQUEST),
+            // error related to the facet search
+            InvalidFacetSearchFacetName => {
+                ErrCode::invalid("invalid_facet_search_facet_name", StatusCode::BAD_REQUEST)
+            }
+            // error related to the document edition functions
+            InvalidDocumentEditionFunction => {
+                ErrCode::invalid("invalid_document_edition_function", StatusCode::BAD_REQUEST)
+            }
 
             BadParameter => ErrCode::invalid("bad_parameter", StatusCode::BAD_REQUEST),
             BadRequest => ErrCode::invalid("bad_request", StatusCode::BAD_REQUEST),
@@ -232,6 +246,10 @@ impl Code {
                 ErrCode::authentication("missing_authorization_header", StatusCode::UNAUTHORIZED)
             }
             TaskNotFound => ErrCode::invalid("task_not_found", StatusCode::NOT_FOUND),
+            MissingTaskFilters => ErrCode::invalid("missing_task_filters", StatusCode::BAD_REQUEST),
+            BatchNotFound => ErrCode::invalid("batch_not_found", StatusCode::NOT_FOUND),
+            WebhookNotFound => ErrCode::invalid("webhook_not_found", StatusCode::NOT_FOUND),
+            InvalidWebhookUrl => ErrCode::invalid("invalid_webhook_url", StatusCode::BAD_REQUEST),
             DumpNotFound => ErrCode::invalid("dump_not_found", StatusCode::NOT_FOUND),
             NoSpaceLeftOnDevice => {
                 ErrCode::internal("no_space_left_on_device", StatusCode::INTERNAL_SERVER_ERROR)
@@ -10,7 +10,7 @@ use http::header::CONTENT_TYPE;
 use meilisearch_auth::SearchRules;
 use meilisearch_lib::index::{
     SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
 use meilisearch_lib::index_controller::Stats;
 use meilisearch_lib::MeiliSearch;
@@ -345,6 +345,7 @@ pub struct SearchAggregator {
     // requests
     total_received: usize,
     total_succeeded: usize,
+    total_degraded: usize,
     time_spent: BinaryHeap<usize>,
 
     // sort
@@ -365,10 +366,12 @@ pub struct SearchAggregator {
     // q
     // The maximum number of terms in a q request
     max_terms_number: usize,
+    matching_strategy: HashMap<String, usize>,
 
     // pagination
     max_limit: usize,
     max_offset: usize,
+    finite_pagination: usize,
 
     // formatting
     highlight_pre_tag: bool,
@@ -376,6 +379,10 @@ pub struct SearchAggregator {
     crop_marker: bool,
     show_matches_position: bool,
     crop_length: bool,
+
+    // scoring
+    show_ranking_score: bool,
+    show_ranking_score_details: bool,
 }
 
 impl SearchAggregator {
@@ -422,9 +429,16 @@ impl SearchAggregator {
         if let Some(ref q) = query.q {
             ret.max_terms_number = q.split_whitespace().count();
         }
+        ret.matching_strategy
+            .insert(format!("{:?}", query.matching_strategy), 1);
 
-        ret.max_limit = query.limit;
-        ret.max_offset = query.offset.unwrap_or_default();
+        if query.is_finite_pagination() {
+            ret.max_limit = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            ret.finite_pagination = 1;
+        } else {
+            ret.max_limit = query.limit;
+            ret.max_offset = query.offset.unwrap_or_default();
+        }
 
         ret.highlight_pre_tag = query.highlight_pre_tag != DEFAULT_HIGHLIGHT_PRE_TAG();
         ret.highlight_post_tag = query.highlight_post_tag != DEFAULT_HIGHLIGHT_POST_TAG();
@@ -432,11 +446,17 @@ impl SearchAggregator {
         ret.crop_length = query.crop_length != DEFAULT_CROP_LENGTH();
         ret.show_matches_position = query.show_matches_position;
 
+        ret.show_ranking_score = query.show_ranking_score;
+        ret.show_ranking_score_details = query.show_ranking_score_details;
+
         ret
     }
 
     pub fn succeed(&mut self, result: &SearchResult) {
         self.total_succeeded = self.total_succeeded.saturating_add(1);
+        if result.degraded {
+            self.total_degraded = self.total_degraded.saturating_add(1);
+        }
         self.time_spent.push(result.processing_time_ms as usize);
     }
 
@@ -453,6 +473,7 @@ impl SearchAggregator {
         // request
         self.total_received = self.total_received.saturating_add(other.total_received);
         self.total_succeeded = self.total_succeeded.saturating_add(other.total_succeeded);
+        self.total_degraded = self.total_degraded.saturating_add(other.total_degraded);
         self.time_spent.append(&mut other.time_spent);
         // sort
         self.sort_with_geo_point |= other.sort_with_geo_point;
@@ -476,15 +497,25 @@ impl SearchAggregator {
         }
         // q
         self.max_terms_number = self.max_terms_number.max(other.max_terms_number);
+        for (key, value) in other.matching_strategy.into_iter() {
+            let matching_strategy = self.matching_strategy.entry(key).or_insert(0);
+            *matching_strategy = matching_strategy.saturating_add(value);
+        }
         // pagination
         self.max_limit = self.max_limit.max(other.max_limit);
         self.max_offset = self.max_offset.max(other.max_offset);
+        self.finite_pagination = self
+            .finite_pagination
+            .saturating_add(other.finite_pagination);
 
         self.highlight_pre_tag |= other.highlight_pre_tag;
         self.highlight_post_tag |= other.highlight_post_tag;
         self.crop_marker |= other.crop_marker;
         self.show_matches_position |= other.show_matches_position;
         self.crop_length |= other.crop_length;
+
+        self.show_ranking_score |= other.show_ranking_score;
+        self.show_ranking_score_details |= other.show_ranking_score_details;
     }
 
     pub fn into_event(self, user: &User, event_name: &str) -> Option<Track> {
@@ -503,6 +534,7 @@ impl SearchAggregator {
                 "requests": {
                     "99th_response_time":  time_spent.map(|t| format!("{:.2}", t)),
                     "total_succeeded": self.total_succeeded,
+                    "total_degraded": self.total_degraded,
                     "total_failed": self.total_received.saturating_sub(self.total_succeeded), // just to be sure we never panics
                     "total_received": self.total_received,
                 },
@@ -517,10 +549,12 @@ impl SearchAggregator {
                 },
                 "q": {
                    "max_terms_number": self.max_terms_number,
+                   "most_used_matching_strategy": self.matching_strategy.iter().max_by_key(|(_, v)| *v).map(|(k, _)| json!(k)).unwrap_or_else(|| json!(null)),
                 },
                 "pagination": {
                    "max_limit": self.max_limit,
                    "max_offset": self.max_offset,
+                   "finite_pagination": self.finite_pagination > self.total_received / 2,
                 },
                 "formatting": {
                     "highlight_pre_tag": self.highlight_pre_tag,
@@ -529,6 +563,10 @@ impl SearchAggregator {
                     "show_matches_position": self.show_matches_position,
                     "crop_length": self.crop_length,
                 },
+                "scoring": {
+                    "show_ranking_score": self.show_ranking_score,
+                    "show_ranking_score_details": self.show_ranking_score_details,
+                },
             });
 
             Some(Track {
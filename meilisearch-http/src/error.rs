@@ -12,6 +12,15 @@ pub enum MeilisearchHttpError {
         .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", ")
     )]
     InvalidContentType(String, Vec<String>),
+    #[error("Task {0} requires at least one of the following filters: {}",
+            .1.iter().map(|s| format!("`{}`", s)).collect::<Vec<_>>().join(", "))]
+    MissingTaskFilters(&'static str, Vec<String>),
+    #[error("The federated search weight of the query at position {0} must be a positive number, found `{1}`.")]
+    InvalidFederationWeight(usize, f64),
+    #[error("The provided API key is not allowed to access the `{0}` index.")]
+    UnauthorizedIndex(String),
+    #[error("The `{0}` task date filter must be earlier than the `{1}` one.")]
+    InvalidTaskDateRange(&'static str, &'static str),
 }
 
 impl ErrorCode for MeilisearchHttpError {
@@ -19,6 +28,10 @@ impl ErrorCode for MeilisearchHttpError {
         match self {
             MeilisearchHttpError::MissingContentType(_) => Code::MissingContentType,
             MeilisearchHttpError::InvalidContentType(_, _) => Code::InvalidContentType,
+            MeilisearchHttpError::MissingTaskFilters(_, _) => Code::MissingTaskFilters,
+            MeilisearchHttpError::InvalidFederationWeight(_, _) => Code::BadRequest,
+            MeilisearchHttpError::UnauthorizedIndex(_) => Code::InvalidToken,
+            MeilisearchHttpError::InvalidTaskDateRange(_, _) => Code::BadRequest,
         }
     }
 }
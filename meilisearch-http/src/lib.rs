@@ -24,6 +24,7 @@ use actix_web::{web, HttpRequest};
 use extractors::payload::PayloadConfig;
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
+use task::TaskView;
 
 pub static AUTOBATCHING_ENABLED: AtomicBool = AtomicBool::new(false);
 
@@ -47,7 +48,9 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         // dump
         .set_ignore_missing_dump(opt.ignore_missing_dump)
         .set_ignore_dump_if_db_exists(opt.ignore_dump_if_db_exists)
-        .set_dump_dst(opt.dumps_dir.clone());
+        .set_dump_dst(opt.dumps_dir.clone())
+        // webhooks
+        .set_webhook_payload(|task| serde_json::json!(TaskView::from(task.clone())));
 
     if let Some(ref path) = opt.import_snapshot {
         meilisearch.set_import_snapshot(path.clone());
@@ -61,6 +64,14 @@ pub fn setup_meilisearch(opt: &Opt) -> anyhow::Result<MeiliSearch> {
         meilisearch.set_schedule_snapshot();
     }
 
+    if let Some(days) = opt.task_retention_days {
+        meilisearch.set_task_retention_period(Duration::from_secs(days * 24 * 60 * 60));
+    }
+
+    if let Some(max_finished_tasks) = opt.max_finished_tasks {
+        meilisearch.set_max_finished_tasks(max_finished_tasks);
+    }
+
     meilisearch.build(
         opt.db_path.clone(),
         opt.indexer_options.clone(),
@@ -55,6 +55,16 @@ pub struct Opt {
     #[clap(long, env = "MEILI_MAX_TASK_DB_SIZE", default_value = "100 GiB")]
     pub max_task_db_size: Byte,
 
+    /// Defines the number of days after which the finished tasks are deleted from the task store.
+    /// If not specified, finished tasks are kept until they are deleted with `DELETE /tasks`.
+    #[clap(long, env = "MEILI_TASK_RETENTION_DAYS")]
+    pub task_retention_days: Option<u64>,
+
+    /// The maximum number of finished tasks kept in the task store. Once this number is reached,
+    /// the oldest finished tasks are deleted first.
+    #[clap(long, env = "MEILI_MAX_FINISHED_TASKS")]
+    pub max_finished_tasks: Option<usize>,
+
     /// The maximum size, in bytes, of accepted JSON payloads
     #[clap(long, env = "MEILI_HTTP_PAYLOAD_SIZE_LIMIT", default_value = "100 MB")]
     pub http_payload_size_limit: Byte,
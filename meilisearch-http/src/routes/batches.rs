@@ -0,0 +1,105 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use meilisearch_auth::SearchRules;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::tasks::batch::BatchId;
+use meilisearch_lib::tasks::TaskFilter;
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::task::{BatchListView, BatchView};
+
+const DEFAULT_LIMIT: fn() -> usize = || 20;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_batches))))
+        .service(web::resource("/{batch_uid}").route(web::get().to(SeqHandler(get_batch))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BatchListQuery {
+    #[serde(default = "DEFAULT_LIMIT")]
+    limit: usize,
+    from: Option<BatchId>,
+}
+
+/// Restricts the batches to the ones on the indexes the key is allowed to see.
+fn build_batch_filter(search_rules: &SearchRules) -> Option<TaskFilter> {
+    if search_rules.is_index_authorized("*") {
+        None
+    } else {
+        let mut filter = TaskFilter::default();
+        for (index, _policy) in search_rules.clone() {
+            filter.filter_index(index);
+        }
+        Some(filter)
+    }
+}
+
+async fn get_batches(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<BatchListQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Batches Seen".to_string(),
+        json!({ "per_batch_uid": false }),
+        Some(&req),
+    );
+
+    let BatchListQuery { limit, from } = params.into_inner();
+    let filter = build_batch_filter(&meilisearch.filters().search_rules);
+
+    // We +1 just to know if there is more after this "page" or not.
+    let limit = limit.saturating_add(1);
+
+    let mut results: Vec<_> = meilisearch
+        .list_batches(filter, Some(limit), from)
+        .await?
+        .into_iter()
+        .map(BatchView::from)
+        .collect();
+
+    // If we were able to fetch the number +1 batches we asked
+    // it means that there is more to come.
+    let next = if results.len() == limit {
+        results.pop().map(|b| b.uid)
+    } else {
+        None
+    };
+
+    let from = results.first().map(|b| b.uid);
+
+    let batches = BatchListView {
+        results,
+        limit: limit.saturating_sub(1),
+        from,
+        next,
+    };
+
+    Ok(HttpResponse::Ok().json(batches))
+}
+
+async fn get_batch(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    batch_uid: web::Path<BatchId>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Batches Seen".to_string(),
+        json!({ "per_batch_uid": true }),
+        Some(&req),
+    );
+
+    let batch_uid = batch_uid.into_inner();
+    let filter = build_batch_filter(&meilisearch.filters().search_rules);
+    let tasks = meilisearch.get_batch(batch_uid, filter).await?;
+
+    Ok(HttpResponse::Ok().json(BatchView::from((batch_uid, tasks))))
+}
@@ -7,8 +7,10 @@ use bstr::ByteSlice;
 use futures::{Stream, StreamExt};
 use log::debug;
 use meilisearch_error::ResponseError;
+use meilisearch_lib::index::DocumentExportFormat;
 use meilisearch_lib::index_controller::{DocumentAdditionFormat, Update};
 use meilisearch_lib::milli::update::IndexDocumentsMethod;
+use meilisearch_lib::tasks::task::TaskPriority;
 use meilisearch_lib::MeiliSearch;
 use mime::Mime;
 use once_cell::sync::Lazy;
@@ -22,9 +24,12 @@ use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::payload::Payload;
 use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::fix_sort_query_parameters;
 use crate::routes::{fold_star_or, PaginationView, StarOr};
 use crate::task::SummarizedTaskView;
 
+use super::TaskPriorityQuery;
+
 static ACCEPTED_CONTENT_TYPE: Lazy<Vec<String>> = Lazy::new(|| {
     vec![
         "application/json".to_string(),
@@ -62,6 +67,14 @@ fn extract_mime_type(req: &HttpRequest) -> Result<Option<Mime>, MeilisearchHttpE
     }
 }
 
+/// The filter query parameter is either a filter array in JSON, or a filter string.
+fn parse_filter_query_parameter(filter: String) -> Value {
+    match serde_json::from_str(&filter) {
+        Ok(filter) => filter,
+        Err(_) => Value::String(filter),
+    }
+}
+
 #[derive(Deserialize)]
 pub struct DocumentParam {
     index_uid: String,
@@ -74,10 +87,15 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .route(web::get().to(SeqHandler(get_all_documents)))
             .route(web::post().to(SeqHandler(add_documents)))
             .route(web::put().to(SeqHandler(update_documents)))
+            .route(web::patch().to(SeqHandler(merge_patch_documents)))
             .route(web::delete().to(SeqHandler(clear_all_documents))),
     )
-    // this route needs to be before the /documents/{document_id} to match properly
+    // these routes need to be before the /documents/{document_id} to match properly
+    .service(web::resource("/fetch").route(web::post().to(SeqHandler(fetch_documents))))
+    .service(web::resource("/export").route(web::get().to(SeqHandler(export_documents))))
     .service(web::resource("/delete-batch").route(web::post().to(SeqHandler(delete_documents))))
+    .service(web::resource("/delete").route(web::post().to(SeqHandler(delete_documents_by_filter))))
+    .service(web::resource("/edit").route(web::post().to(SeqHandler(edit_documents))))
     .service(
         web::resource("/{document_id}")
             .route(web::get().to(SeqHandler(get_document)))
@@ -111,13 +129,17 @@ pub async fn get_document(
 pub async fn delete_document(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<DocumentParam>,
+    params: web::Query<TaskPriorityQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let DocumentParam {
         document_id,
         index_uid,
     } = path.into_inner();
     let update = Update::DeleteDocuments(vec![document_id]);
-    let task: SummarizedTaskView = meilisearch.register_update(index_uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(index_uid, update, params.priority)
+        .await?
+        .into();
     debug!("returns: {:?}", task);
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -130,6 +152,8 @@ pub struct BrowseQuery {
     #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
     limit: usize,
     fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+    sort: Option<String>,
 }
 
 pub async fn get_all_documents(
@@ -142,11 +166,83 @@ pub async fn get_all_documents(
         limit,
         offset,
         fields,
+        filter,
+        sort,
     } = params.into_inner();
     let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(parse_filter_query_parameter);
+    let sort = sort.map(|sort| fix_sort_query_parameters(&sort));
+
+    documents_page(
+        meilisearch,
+        path.into_inner(),
+        offset,
+        limit,
+        attributes_to_retrieve,
+        filter,
+        sort,
+    )
+    .await
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BrowseQueryPost {
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "crate::routes::PAGINATION_DEFAULT_LIMIT")]
+    limit: usize,
+    fields: Option<Vec<String>>,
+    filter: Option<Value>,
+    sort: Option<Vec<String>>,
+}
+
+pub async fn fetch_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Json<BrowseQueryPost>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let BrowseQueryPost {
+        offset,
+        limit,
+        fields,
+        filter,
+        sort,
+    } = params.into_inner();
+    // A `*` selects all the fields, like in the query parameter of the `GET` route.
+    let attributes_to_retrieve = fields.filter(|fields| !fields.iter().any(|field| field == "*"));
+
+    documents_page(
+        meilisearch,
+        path.into_inner(),
+        offset,
+        limit,
+        attributes_to_retrieve,
+        filter,
+        sort,
+    )
+    .await
+}
 
+async fn documents_page(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    index_uid: String,
+    offset: usize,
+    limit: usize,
+    attributes_to_retrieve: Option<Vec<String>>,
+    filter: Option<Value>,
+    sort: Option<Vec<String>>,
+) -> Result<HttpResponse, ResponseError> {
     let (total, documents) = meilisearch
-        .documents(path.into_inner(), offset, limit, attributes_to_retrieve)
+        .documents(
+            index_uid,
+            offset,
+            limit,
+            attributes_to_retrieve,
+            filter,
+            sort,
+        )
         .await?;
 
     let ret = PaginationView::new(offset, limit, total as usize, documents);
@@ -155,10 +251,46 @@ pub async fn get_all_documents(
     Ok(HttpResponse::Ok().json(ret))
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ExportQuery {
+    #[serde(default)]
+    format: DocumentExportFormat,
+    fields: Option<CS<StarOr<String>>>,
+    filter: Option<String>,
+}
+
+pub async fn export_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_GET }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<ExportQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let ExportQuery {
+        format,
+        fields,
+        filter,
+    } = params.into_inner();
+    let attributes_to_retrieve = fields.and_then(fold_star_or);
+    let filter = filter.map(parse_filter_query_parameter);
+
+    let chunks = meilisearch
+        .export_documents(path.into_inner(), format, attributes_to_retrieve, filter)
+        .await?
+        .map(|chunk| chunk.map_err(ResponseError::from));
+
+    // Without a content length, the export is sent with the chunked transfer encoding.
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .streaming(chunks))
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct UpdateDocumentsQuery {
     pub primary_key: Option<String>,
+    #[serde(default)]
+    pub priority: TaskPriority,
 }
 
 pub async fn add_documents(
@@ -179,15 +311,14 @@ pub async fn add_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.primary_key,
+        params,
         body,
         IndexDocumentsMethod::ReplaceDocuments,
-        allow_index_creation,
+        false,
     )
     .await?;
 
@@ -203,6 +334,7 @@ pub async fn update_documents(
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", params);
+    let params = params.into_inner();
     let index_uid = path.into_inner();
 
     analytics.update_documents(
@@ -211,15 +343,48 @@ pub async fn update_documents(
         &req,
     );
 
-    let allow_index_creation = meilisearch.filters().allow_index_creation;
     let task = document_addition(
         extract_mime_type(&req)?,
         meilisearch,
         index_uid,
-        params.into_inner().primary_key,
+        params,
         body,
         IndexDocumentsMethod::UpdateDocuments,
-        allow_index_creation,
+        false,
+    )
+    .await?;
+
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+/// Updates the documents with the JSON merge patch semantics: the nested objects are merged with
+/// the stored ones, and a `null` removes a field.
+pub async fn merge_patch_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Query<UpdateDocumentsQuery>,
+    body: Payload,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", params);
+    let params = params.into_inner();
+    let index_uid = path.into_inner();
+
+    analytics.update_documents(
+        &params,
+        meilisearch.get_index(index_uid.clone()).await.is_err(),
+        &req,
+    );
+
+    let task = document_addition(
+        extract_mime_type(&req)?,
+        meilisearch,
+        index_uid,
+        params,
+        body,
+        IndexDocumentsMethod::UpdateDocuments,
+        true,
     )
     .await?;
 
@@ -230,10 +395,10 @@ async fn document_addition(
     mime_type: Option<Mime>,
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
     index_uid: String,
-    primary_key: Option<String>,
+    params: UpdateDocumentsQuery,
     body: Payload,
     method: IndexDocumentsMethod,
-    allow_index_creation: bool,
+    merge_patch: bool,
 ) -> Result<SummarizedTaskView, ResponseError> {
     let format = match mime_type
         .as_ref()
@@ -258,13 +423,17 @@ async fn document_addition(
 
     let update = Update::DocumentAddition {
         payload: Box::new(payload_to_stream(body)),
-        primary_key,
+        primary_key: params.primary_key,
         method,
+        merge_patch,
         format,
-        allow_index_creation,
+        allow_index_creation: meilisearch.filters().allow_index_creation,
     };
 
-    let task = meilisearch.register_update(index_uid, update).await?.into();
+    let task = meilisearch
+        .register_update(index_uid, update, params.priority)
+        .await?
+        .into();
 
     debug!("returns: {:?}", task);
     Ok(task)
@@ -274,6 +443,7 @@ pub async fn delete_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
     body: web::Json<Vec<Value>>,
+    params: web::Query<TaskPriorityQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     debug!("called with params: {:?}", body);
     let ids = body
@@ -287,7 +457,58 @@ pub async fn delete_documents(
 
     let update = Update::DeleteDocuments(ids);
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_update(path.into_inner(), update, params.priority)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentDeletionByFilter {
+    filter: Value,
+}
+
+pub async fn delete_documents_by_filter(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentDeletionByFilter>,
+    params: web::Query<TaskPriorityQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentDeletionByFilter { filter } = body.into_inner();
+
+    let update = Update::DeleteDocumentsByFilter(filter);
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
+        .await?
+        .into();
+
+    debug!("returns: {:?}", task);
+    Ok(HttpResponse::Accepted().json(task))
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct DocumentEditionByFunction {
+    filter: Option<Value>,
+    function: String,
+}
+
+pub async fn edit_documents(
+    meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_ADD }>, MeiliSearch>,
+    path: web::Path<String>,
+    body: web::Json<DocumentEditionByFunction>,
+    params: web::Query<TaskPriorityQuery>,
+) -> Result<HttpResponse, ResponseError> {
+    debug!("called with params: {:?}", body);
+    let DocumentEditionByFunction { filter, function } = body.into_inner();
+
+    let update = Update::EditDocuments { filter, function };
+    let task: SummarizedTaskView = meilisearch
+        .register_update(path.into_inner(), update, params.priority)
         .await?
         .into();
 
@@ -298,10 +519,11 @@ pub async fn delete_documents(
 pub async fn clear_all_documents(
     meilisearch: GuardedData<ActionPolicy<{ actions::DOCUMENTS_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<TaskPriorityQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let update = Update::ClearDocuments;
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_update(path.into_inner(), update, params.priority)
         .await?
         .into();
 
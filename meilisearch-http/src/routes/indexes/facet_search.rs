@@ -0,0 +1,54 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::FacetSearchQuery;
+use meilisearch_lib::MeiliSearch;
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(facet_search_with_post))));
+}
+
+pub async fn facet_search_with_post(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    path: web::Path<String>,
+    params: web::Json<FacetSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let mut query = params.into_inner();
+    debug!("facet search called with params: {:?}", query);
+
+    analytics.publish(
+        "Facet Searched POST".to_string(),
+        json!({
+            "total_facet_query_terms": query
+                .facet_query
+                .as_ref()
+                .map(|q| q.split_whitespace().count()),
+            "with_q": query.q.is_some(),
+            "with_filter": query.filter.is_some(),
+        }),
+        Some(&req),
+    );
+
+    let index_uid = path.into_inner();
+    // Tenant token search_rules.
+    if let Some(search_rules) = meilisearch
+        .filters()
+        .search_rules
+        .get_index_search_rules(&index_uid)
+    {
+        add_search_rules(&mut query.filter, search_rules);
+    }
+
+    let search_result = meilisearch.facet_search(index_uid, query).await?;
+
+    debug!("returns: {:?}", search_result);
+    Ok(HttpResponse::Ok().json(search_result))
+}
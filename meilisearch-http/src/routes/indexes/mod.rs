@@ -2,6 +2,7 @@ use actix_web::{web, HttpRequest, HttpResponse};
 use log::debug;
 use meilisearch_error::ResponseError;
 use meilisearch_lib::index_controller::Update;
+use meilisearch_lib::tasks::task::TaskPriority;
 use meilisearch_lib::MeiliSearch;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -15,6 +16,7 @@ use crate::task::SummarizedTaskView;
 use super::Pagination;
 
 pub mod documents;
+pub mod facet_search;
 pub mod search;
 pub mod settings;
 
@@ -35,6 +37,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
             .service(web::resource("/stats").route(web::get().to(SeqHandler(get_index_stats))))
             .service(web::scope("/documents").configure(documents::configure))
             .service(web::scope("/search").configure(search::configure))
+            .service(web::scope("/facet-search").configure(facet_search::configure))
             .service(web::scope("/settings").configure(settings::configure)),
     );
 }
@@ -57,6 +60,14 @@ pub async fn list_indexes(
     Ok(HttpResponse::Ok().json(ret))
 }
 
+/// The query parameters accepted by the routes registering a task.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct TaskPriorityQuery {
+    #[serde(default)]
+    pub priority: TaskPriority,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct IndexCreateRequest {
@@ -67,6 +78,7 @@ pub struct IndexCreateRequest {
 pub async fn create_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_CREATE }>, MeiliSearch>,
     body: web::Json<IndexCreateRequest>,
+    params: web::Query<TaskPriorityQuery>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -81,7 +93,10 @@ pub async fn create_index(
     );
 
     let update = Update::CreateIndex { primary_key };
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(uid, update, params.priority)
+        .await?
+        .into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -120,6 +135,7 @@ pub async fn update_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_UPDATE }>, MeiliSearch>,
     path: web::Path<String>,
     body: web::Json<UpdateIndexRequest>,
+    params: web::Query<TaskPriorityQuery>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -136,7 +152,7 @@ pub async fn update_index(
     };
 
     let task: SummarizedTaskView = meilisearch
-        .register_update(path.into_inner(), update)
+        .register_update(path.into_inner(), update, params.priority)
         .await?
         .into();
 
@@ -147,10 +163,14 @@ pub async fn update_index(
 pub async fn delete_index(
     meilisearch: GuardedData<ActionPolicy<{ actions::INDEXES_DELETE }>, MeiliSearch>,
     path: web::Path<String>,
+    params: web::Query<TaskPriorityQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let uid = path.into_inner();
     let update = Update::DeleteIndex;
-    let task: SummarizedTaskView = meilisearch.register_update(uid, update).await?.into();
+    let task: SummarizedTaskView = meilisearch
+        .register_update(uid, update, params.priority)
+        .await?
+        .into();
 
     Ok(HttpResponse::Accepted().json(task))
 }
@@ -3,8 +3,8 @@ use log::debug;
 use meilisearch_auth::IndexSearchRules;
 use meilisearch_error::ResponseError;
 use meilisearch_lib::index::{
-    SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
-    DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    MatchingStrategy, SearchQuery, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
+    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
 use meilisearch_lib::MeiliSearch;
 use serde::Deserialize;
@@ -30,6 +30,8 @@ pub struct SearchQueryGet {
     q: Option<String>,
     offset: Option<usize>,
     limit: Option<usize>,
+    page: Option<usize>,
+    hits_per_page: Option<usize>,
     attributes_to_retrieve: Option<CS<StarOr<String>>>,
     attributes_to_crop: Option<CS<StarOr<String>>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -46,6 +48,13 @@ pub struct SearchQueryGet {
     highlight_post_tag: String,
     #[serde(default = "DEFAULT_CROP_MARKER")]
     crop_marker: String,
+    #[serde(default = "Default::default")]
+    show_ranking_score: bool,
+    #[serde(default = "Default::default")]
+    show_ranking_score_details: bool,
+    search_cutoff_ms: Option<u64>,
+    #[serde(default)]
+    matching_strategy: MatchingStrategy,
 }
 
 impl From<SearchQueryGet> for SearchQuery {
@@ -62,6 +71,8 @@ impl From<SearchQueryGet> for SearchQuery {
             q: other.q,
             offset: other.offset,
             limit: other.limit.unwrap_or_else(DEFAULT_SEARCH_LIMIT),
+            page: other.page,
+            hits_per_page: other.hits_per_page,
             attributes_to_retrieve: other.attributes_to_retrieve.and_then(fold_star_or),
             attributes_to_crop: other.attributes_to_crop.and_then(fold_star_or),
             crop_length: other.crop_length,
@@ -73,13 +84,17 @@ impl From<SearchQueryGet> for SearchQuery {
             highlight_pre_tag: other.highlight_pre_tag,
             highlight_post_tag: other.highlight_post_tag,
             crop_marker: other.crop_marker,
+            show_ranking_score: other.show_ranking_score,
+            show_ranking_score_details: other.show_ranking_score_details,
+            search_cutoff_ms: other.search_cutoff_ms,
+            matching_strategy: other.matching_strategy,
         }
     }
 }
 
-/// Incorporate search rules in search query
-fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
-    query.filter = match (query.filter.take(), rules.filter) {
+/// Incorporate search rules in the filter of a search query
+pub fn add_search_rules(filter: &mut Option<Value>, rules: IndexSearchRules) {
+    *filter = match (filter.take(), rules.filter) {
         (None, rules_filter) => rules_filter,
         (filter, None) => filter,
         (Some(filter), Some(rules_filter)) => {
@@ -100,7 +115,7 @@ fn add_search_rules(query: &mut SearchQuery, rules: IndexSearchRules) {
 // TODO: TAMO: split on :asc, and :desc, instead of doing some weird things
 
 /// Transform the sort query parameter into something that matches the post expected format.
-fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
+pub fn fix_sort_query_parameters(sort_query: &str) -> Vec<String> {
     let mut sort_parameters = Vec::new();
     let mut merge = false;
     for current_sort in sort_query.trim_matches('"').split(',').map(|s| s.trim()) {
@@ -140,7 +155,7 @@ pub async fn search_with_url_query(
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query.filter, search_rules);
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
@@ -174,7 +189,7 @@ pub async fn search_with_post(
         .search_rules
         .get_index_search_rules(&index_uid)
     {
-        add_search_rules(&mut query, search_rules);
+        add_search_rules(&mut query.filter, search_rules);
     }
 
     let mut aggregate = SearchAggregator::from_query(&query, &req);
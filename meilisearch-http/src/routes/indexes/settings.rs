@@ -2,6 +2,7 @@ use log::debug;
 
 use actix_web::{web, HttpRequest, HttpResponse};
 use meilisearch_error::ResponseError;
+use meilisearch_lib::index::updates::FacetValuesSort;
 use meilisearch_lib::index::{Settings, Unchecked};
 use meilisearch_lib::index_controller::Update;
 use meilisearch_lib::MeiliSearch;
@@ -11,6 +12,8 @@ use crate::analytics::Analytics;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::task::SummarizedTaskView;
 
+use super::TaskPriorityQuery;
+
 #[macro_export]
 macro_rules! make_setting_route {
     ($route:literal, $update_verb:ident, $type:ty, $attr:ident, $camelcase_attr:literal, $analytics_var:ident, $analytics:expr) => {
@@ -25,11 +28,13 @@ macro_rules! make_setting_route {
             use $crate::analytics::Analytics;
             use $crate::extractors::authentication::{policies::*, GuardedData};
             use $crate::extractors::sequential_extractor::SeqHandler;
+            use $crate::routes::indexes::TaskPriorityQuery;
             use $crate::task::SummarizedTaskView;
 
             pub async fn delete(
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: web::Path<String>,
+                params: web::Query<TaskPriorityQuery>,
             ) -> Result<HttpResponse, ResponseError> {
                 let settings = Settings {
                     $attr: Setting::Reset,
@@ -43,7 +48,7 @@ macro_rules! make_setting_route {
                     allow_index_creation,
                 };
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_update(index_uid.into_inner(), update, params.priority)
                     .await?
                     .into();
 
@@ -55,6 +60,7 @@ macro_rules! make_setting_route {
                 meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
                 index_uid: actix_web::web::Path<String>,
                 body: actix_web::web::Json<Option<$type>>,
+                params: web::Query<TaskPriorityQuery>,
                 req: HttpRequest,
                 $analytics_var: web::Data<dyn Analytics>,
             ) -> std::result::Result<HttpResponse, ResponseError> {
@@ -77,7 +83,7 @@ macro_rules! make_setting_route {
                     allow_index_creation,
                 };
                 let task: SummarizedTaskView = meilisearch
-                    .register_update(index_uid.into_inner(), update)
+                    .register_update(index_uid.into_inner(), update, params.priority)
                     .await?
                     .into();
 
@@ -214,6 +220,86 @@ make_setting_route!(
     }
 );
 
+make_setting_route!(
+    "/pagination",
+    patch,
+    meilisearch_lib::index::updates::PaginationSettings,
+    pagination,
+    "pagination",
+    analytics,
+    |setting: &Option<meilisearch_lib::index::updates::PaginationSettings>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Pagination Updated".to_string(),
+            json!({
+                "pagination": {
+                    "max_total_hits": setting
+                        .as_ref()
+                        .and_then(|s| s.max_total_hits.set()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/faceting",
+    patch,
+    meilisearch_lib::index::updates::FacetingSettings,
+    faceting,
+    "faceting",
+    analytics,
+    |setting: &Option<meilisearch_lib::index::updates::FacetingSettings>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "Faceting Updated".to_string(),
+            json!({
+                "faceting": {
+                    "max_values_per_facet": setting
+                        .as_ref()
+                        .and_then(|s| s.max_values_per_facet.set()),
+                    "sort_facet_values_by_star_count": setting
+                        .as_ref()
+                        .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                        .map(|s| s.iter().any(|(facet, sort)| {
+                            facet == "*" && sort == &meilisearch_lib::index::updates::FacetValuesSort::Count
+                        })),
+                    "sort_facet_values_by_total": setting
+                        .as_ref()
+                        .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                        .map(|s| s.len()),
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
+make_setting_route!(
+    "/search-cutoff-ms",
+    put,
+    u64,
+    search_cutoff_ms,
+    "searchCutoffMs",
+    analytics,
+    |setting: &Option<u64>, req: &HttpRequest| {
+        use serde_json::json;
+
+        analytics.publish(
+            "SearchCutoffMs Updated".to_string(),
+            json!({
+                "search_cutoff_ms": {
+                    "search_cutoff_ms": setting,
+                },
+            }),
+            Some(req),
+        );
+    }
+);
+
 make_setting_route!(
     "/searchable-attributes",
     put,
@@ -305,13 +391,17 @@ generate_configure!(
     stop_words,
     synonyms,
     ranking_rules,
-    typo_tolerance
+    typo_tolerance,
+    pagination,
+    faceting,
+    search_cutoff_ms
 );
 
 pub async fn update_all(
     meilisearch: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
     body: web::Json<Settings<Unchecked>>,
+    params: web::Query<TaskPriorityQuery>,
     req: HttpRequest,
     analytics: web::Data<dyn Analytics>,
 ) -> Result<HttpResponse, ResponseError> {
@@ -365,6 +455,33 @@ pub async fn update_all(
                         .map(|s| s.two_typos.set()))
                     .flatten(),
             },
+            "pagination": {
+                "max_total_hits": settings.pagination
+                    .as_ref()
+                    .set()
+                    .and_then(|s| s.max_total_hits.as_ref().set())
+                    .copied(),
+            },
+            "faceting": {
+                "max_values_per_facet": settings.faceting
+                    .as_ref()
+                    .set()
+                    .and_then(|s| s.max_values_per_facet.as_ref().set())
+                    .copied(),
+                "sort_facet_values_by_star_count": settings.faceting
+                    .as_ref()
+                    .set()
+                    .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                    .map(|s| s.iter().any(|(facet, sort)| facet == "*" && sort == &FacetValuesSort::Count)),
+                "sort_facet_values_by_total": settings.faceting
+                    .as_ref()
+                    .set()
+                    .and_then(|s| s.sort_facet_values_by.as_ref().set())
+                    .map(|s| s.len()),
+            },
+            "search_cutoff_ms": {
+                "search_cutoff_ms": settings.search_cutoff_ms.as_ref().set(),
+            },
         }),
         Some(&req),
     );
@@ -376,7 +493,7 @@ pub async fn update_all(
         allow_index_creation,
     };
     let task: SummarizedTaskView = meilisearch
-        .register_update(index_uid.into_inner(), update)
+        .register_update(index_uid.into_inner(), update, params.priority)
         .await?
         .into();
 
@@ -396,6 +513,7 @@ pub async fn get_all(
 pub async fn delete_all(
     data: GuardedData<ActionPolicy<{ actions::SETTINGS_UPDATE }>, MeiliSearch>,
     index_uid: web::Path<String>,
+    params: web::Query<TaskPriorityQuery>,
 ) -> Result<HttpResponse, ResponseError> {
     let settings = Settings::cleared().into_unchecked();
 
@@ -406,7 +524,7 @@ pub async fn delete_all(
         allow_index_creation,
     };
     let task: SummarizedTaskView = data
-        .register_update(index_uid.into_inner(), update)
+        .register_update(index_uid.into_inner(), update, params.priority)
         .await?
         .into();
 
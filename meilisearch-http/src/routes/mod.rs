@@ -13,18 +13,24 @@ use meilisearch_lib::MeiliSearch;
 use crate::extractors::authentication::{policies::*, GuardedData};
 
 mod api_key;
+mod batches;
 mod dump;
 pub mod indexes;
+mod multi_search;
 mod tasks;
+mod webhooks;
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(web::scope("/tasks").configure(tasks::configure))
+        .service(web::scope("/batches").configure(batches::configure))
         .service(web::resource("/health").route(web::get().to(get_health)))
         .service(web::scope("/keys").configure(api_key::configure))
         .service(web::scope("/dumps").configure(dump::configure))
         .service(web::resource("/stats").route(web::get().to(get_stats)))
         .service(web::resource("/version").route(web::get().to(get_version)))
-        .service(web::scope("/indexes").configure(indexes::configure));
+        .service(web::scope("/indexes").configure(indexes::configure))
+        .service(web::scope("/multi-search").configure(multi_search::configure))
+        .service(web::scope("/webhooks").configure(webhooks::configure));
 }
 
 /// A type that tries to match either a star (*) or
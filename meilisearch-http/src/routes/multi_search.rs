@@ -0,0 +1,366 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::future::try_join_all;
+use log::debug;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::index::{
+    HitsInfo, SearchHit, SearchQuery, SearchResult, DEFAULT_SEARCH_LIMIT,
+};
+use meilisearch_lib::MeiliSearch;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+use crate::routes::indexes::search::add_search_rules;
+
+const DEFAULT_FEDERATION_WEIGHT: fn() -> f64 = || 1.0;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(web::resource("").route(web::post().to(SeqHandler(multi_search_with_post))));
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MultiSearchQuery {
+    queries: Vec<SearchQueryWithIndex>,
+    /// When present, the hits of all the queries are merged into a single list of hits.
+    federation: Option<Federation>,
+}
+
+/// A search query, along with the index it must be run on.
+///
+/// The fields of the flattened query are not checked by its own `deny_unknown_fields`, so the
+/// unknown fields must be denied here.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchQueryWithIndex {
+    index_uid: String,
+    federation_options: Option<FederationOptions>,
+    #[serde(flatten)]
+    query: SearchQuery,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Federation {
+    #[serde(default = "DEFAULT_SEARCH_LIMIT")]
+    limit: usize,
+    #[serde(default)]
+    offset: usize,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FederationOptions {
+    /// Multiplies the ranking score of the hits of the query when merging them, must be positive.
+    #[serde(default = "DEFAULT_FEDERATION_WEIGHT")]
+    weight: f64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultWithIndex {
+    index_uid: String,
+    #[serde(flatten)]
+    result: SearchResult,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FederatedSearchResult {
+    hits: Vec<FederatedHit>,
+    estimated_total_hits: u64,
+    limit: usize,
+    offset: usize,
+    processing_time_ms: u128,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FederatedHit {
+    #[serde(flatten)]
+    hit: SearchHit,
+    #[serde(rename = "_federation")]
+    federation: HitFederation,
+}
+
+/// Where a federated hit comes from, and how it was ranked among the other hits.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct HitFederation {
+    index_uid: String,
+    queries_position: usize,
+    weighted_ranking_score: f64,
+}
+
+pub async fn multi_search_with_post(
+    meilisearch: GuardedData<ActionPolicy<{ actions::SEARCH }>, MeiliSearch>,
+    params: web::Json<MultiSearchQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let MultiSearchQuery {
+        queries,
+        federation,
+    } = params.into_inner();
+    debug!(
+        "multi-search called with params: {:?}, federation: {:?}",
+        queries, federation
+    );
+
+    analytics.publish(
+        "Multi Search POST".to_string(),
+        json!({
+            "total_queries": queries.len(),
+            "federated": federation.is_some(),
+        }),
+        Some(&req),
+    );
+
+    // Tenant token search_rules, which also tell the indexes the key can search.
+    let search_rules = &meilisearch.filters().search_rules;
+    let mut index_queries = Vec::with_capacity(queries.len());
+    for (position, query) in queries.into_iter().enumerate() {
+        let SearchQueryWithIndex {
+            index_uid,
+            federation_options,
+            mut query,
+        } = query;
+
+        let rules = search_rules
+            .get_index_search_rules(&index_uid)
+            .ok_or_else(|| MeilisearchHttpError::UnauthorizedIndex(index_uid.clone()))?;
+        add_search_rules(&mut query.filter, rules);
+
+        let weight = federation_options.map_or_else(DEFAULT_FEDERATION_WEIGHT, |o| o.weight);
+        if !weight.is_finite() || weight <= 0.0 {
+            return Err(MeilisearchHttpError::InvalidFederationWeight(position, weight).into());
+        }
+
+        index_queries.push((index_uid, query, weight));
+    }
+
+    let meilisearch: &MeiliSearch = &meilisearch;
+    match federation {
+        Some(federation) => {
+            let result = federated_search(meilisearch, index_queries, federation).await?;
+            debug!("returns: {:?}", result);
+            Ok(HttpResponse::Ok().json(result))
+        }
+        None => {
+            // The queries are run concurrently, the first error fails the whole request.
+            let searches =
+                index_queries
+                    .into_iter()
+                    .map(|(index_uid, query, _weight)| async move {
+                        let result = meilisearch.search(index_uid.clone(), query).await?;
+                        Ok::<_, ResponseError>(SearchResultWithIndex { index_uid, result })
+                    });
+            let results = try_join_all(searches).await?;
+
+            debug!("returns: {:?}", results);
+            Ok(HttpResponse::Ok().json(json!({ "results": results })))
+        }
+    }
+}
+
+/// Runs all the queries and merges their hits by weighted ranking score.
+async fn federated_search(
+    meilisearch: &MeiliSearch,
+    queries: Vec<(String, SearchQuery, f64)>,
+    federation: Federation,
+) -> Result<FederatedSearchResult, ResponseError> {
+    let before_search = Instant::now();
+    let Federation { limit, offset } = federation;
+
+    // Any query could provide all the hits of the requested page,
+    // so each of them must return the hits up to the end of this page.
+    let per_query_limit = offset.saturating_add(limit);
+    let searches = queries
+        .into_iter()
+        .map(|(index_uid, mut query, weight)| async move {
+            query.offset = None;
+            query.limit = per_query_limit;
+            query.page = None;
+            query.hits_per_page = None;
+            // The ranking scores are needed to merge the hits, but are only shown if asked.
+            let show_ranking_score = query.show_ranking_score;
+            query.show_ranking_score = true;
+            let result = meilisearch.search(index_uid.clone(), query).await?;
+            Ok::<_, ResponseError>((index_uid, weight, show_ranking_score, result))
+        });
+    let results = try_join_all(searches).await?;
+
+    // The queries on the same index can match the same documents, so only the largest of their
+    // estimations is counted.
+    let mut estimated_total_hits_by_index = HashMap::new();
+    let mut hits_by_query = Vec::with_capacity(results.len());
+    for (queries_position, (index_uid, weight, show_ranking_score, result)) in
+        results.into_iter().enumerate()
+    {
+        let estimated_total_hits = match result.hits_info {
+            HitsInfo::OffsetLimit {
+                estimated_total_hits,
+                ..
+            } => estimated_total_hits,
+            HitsInfo::Pagination { total_hits, .. } => total_hits as u64,
+        };
+        let index_total = estimated_total_hits_by_index
+            .entry(index_uid.clone())
+            .or_insert(0);
+        *index_total = estimated_total_hits.max(*index_total);
+
+        let hits: VecDeque<_> = result
+            .hits
+            .into_iter()
+            .map(|mut hit| {
+                let ranking_score = hit.ranking_score.unwrap_or_default();
+                if !show_ranking_score {
+                    hit.ranking_score = None;
+                }
+                FederatedHit {
+                    federation: HitFederation {
+                        index_uid: index_uid.clone(),
+                        queries_position,
+                        weighted_ranking_score: weight * ranking_score,
+                    },
+                    hit,
+                }
+            })
+            .collect();
+        hits_by_query.push(hits);
+    }
+    let estimated_total_hits = estimated_total_hits_by_index.values().sum();
+
+    let hits = merge_ranked_hits(hits_by_query, per_query_limit);
+    let hits = hits.into_iter().skip(offset).take(limit).collect();
+
+    Ok(FederatedSearchResult {
+        hits,
+        estimated_total_hits,
+        limit,
+        offset,
+        processing_time_ms: before_search.elapsed().as_millis(),
+    })
+}
+
+/// Merges the ranked hits of the queries into a single list of at most `limit` hits.
+///
+/// The hits of each query are kept in the order the engine ranked them in their index, and the
+/// weighted ranking scores are only used to interleave the hits of the different queries: this is
+/// a k-way merge that always takes the first remaining hit with the best weighted score. On equal
+/// scores, the hits of the first queries come first.
+fn merge_ranked_hits(
+    mut hits_by_query: Vec<VecDeque<FederatedHit>>,
+    limit: usize,
+) -> Vec<FederatedHit> {
+    let mut hits = Vec::new();
+    while hits.len() < limit {
+        let best = hits_by_query
+            .iter()
+            .enumerate()
+            .filter_map(|(position, hits)| {
+                let hit = hits.front()?;
+                Some((position, hit.federation.weighted_ranking_score))
+            })
+            .reduce(|best, current| if current.1 > best.1 { current } else { best });
+        match best.and_then(|(position, _)| hits_by_query[position].pop_front()) {
+            Some(hit) => hits.push(hit),
+            None => break,
+        }
+    }
+
+    hits
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::{Map, Value};
+
+    use super::*;
+
+    fn ranked_hits(
+        index_uid: &str,
+        queries_position: usize,
+        scores: &[f64],
+    ) -> VecDeque<FederatedHit> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(rank, score)| {
+                let mut document = Map::new();
+                document.insert("rank".to_string(), Value::from(rank));
+                FederatedHit {
+                    hit: SearchHit {
+                        document,
+                        formatted: Map::new(),
+                        matches_position: None,
+                        ranking_score: Some(*score),
+                        ranking_score_details: None,
+                    },
+                    federation: HitFederation {
+                        index_uid: index_uid.to_string(),
+                        queries_position,
+                        weighted_ranking_score: *score,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merge_preserves_the_order_of_each_index() {
+        // The scores of an index aren't necessarily decreasing along the engine order.
+        let hits_by_query = vec![
+            ranked_hits("movies", 0, &[0.9, 0.2, 0.8, 0.4]),
+            ranked_hits("books", 1, &[0.5, 0.6, 0.1]),
+        ];
+
+        let hits = merge_ranked_hits(hits_by_query, 6);
+        let merged: Vec<_> = hits
+            .iter()
+            .map(|hit| {
+                (
+                    hit.federation.index_uid.as_str(),
+                    hit.hit.document["rank"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            merged,
+            [
+                ("movies", Value::from(0)),
+                ("books", Value::from(0)),
+                ("books", Value::from(1)),
+                ("movies", Value::from(1)),
+                ("movies", Value::from(2)),
+                ("movies", Value::from(3)),
+            ]
+        );
+
+        for index_uid in ["movies", "books"] {
+            let ranks: Vec<_> = hits
+                .iter()
+                .filter(|hit| hit.federation.index_uid == index_uid)
+                .map(|hit| hit.hit.document["rank"].as_u64().unwrap())
+                .collect();
+            assert!(ranks.windows(2).all(|w| w[0] < w[1]), "{:?}", ranks);
+        }
+    }
+
+    #[test]
+    fn merge_ties_favor_the_first_queries() {
+        let hits_by_query = vec![
+            ranked_hits("movies", 0, &[0.5]),
+            ranked_hits("books", 1, &[0.5]),
+        ];
+
+        let hits = merge_ranked_hits(hits_by_query, 1);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].federation.index_uid, "movies");
+    }
+}
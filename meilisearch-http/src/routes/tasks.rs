@@ -1,83 +1,247 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use actix_web::http::header::CACHE_CONTROL;
+use actix_web::web::Bytes;
 use actix_web::{web, HttpRequest, HttpResponse};
+use futures::StreamExt;
+use meilisearch_auth::SearchRules;
 use meilisearch_error::ResponseError;
-use meilisearch_lib::tasks::task::{TaskContent, TaskEvent, TaskId};
+use meilisearch_lib::tasks::task::{Task, TaskId};
 use meilisearch_lib::tasks::TaskFilter;
 use meilisearch_lib::{IndexUid, MeiliSearch};
 use serde::Deserialize;
 use serde_cs::vec::CS;
 use serde_json::json;
+use time::OffsetDateTime;
+use tokio::sync::mpsc;
 
 use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
 use crate::extractors::authentication::{policies::*, GuardedData};
 use crate::extractors::sequential_extractor::SeqHandler;
-use crate::task::{TaskListView, TaskStatus, TaskType, TaskView};
+use crate::task::{
+    TaskCancelationView, TaskDeletionView, TaskListView, TaskStatus, TaskType, TaskView,
+};
 
 use super::{fold_star_or, StarOr};
 
 const DEFAULT_LIMIT: fn() -> usize = || 20;
 
+/// The interval at which a comment is sent on an idle task stream, so that the proxies don't close
+/// the connection and the closed connections are detected.
+const STREAM_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The maximum number of tasks replayed when a client resumes a task stream.
+const MAX_REPLAYED_TASKS: usize = 1000;
+
 pub fn configure(cfg: &mut web::ServiceConfig) {
-    cfg.service(web::resource("").route(web::get().to(SeqHandler(get_tasks))))
-        .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
+    cfg.service(
+        web::resource("")
+            .route(web::get().to(SeqHandler(get_tasks)))
+            .route(web::delete().to(SeqHandler(delete_tasks))),
+    )
+    .service(web::resource("/cancel").route(web::post().to(SeqHandler(cancel_tasks))))
+    .service(web::resource("/stream").route(web::get().to(SeqHandler(stream_tasks))))
+    .service(web::resource("/{task_id}").route(web::get().to(SeqHandler(get_task))));
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "camelCase", deny_unknown_fields)]
-pub struct TaskFilterQuery {
-    #[serde(rename = "type")]
-    type_: Option<CS<StarOr<TaskType>>>,
-    status: Option<CS<StarOr<TaskStatus>>>,
-    index_uid: Option<CS<StarOr<IndexUid>>>,
+/// Defines a query of the routes operating on a set of tasks, made of the task filters and of
+/// the extra parameters of the route.
+///
+/// The date filters are inlined in each query, since serde doesn't support the flattened fields
+/// along with `deny_unknown_fields`.
+macro_rules! tasks_filters_query {
+    ($name:ident { $($(#[$attr:meta])* $field:ident: $ty:ty,)* }) => {
+        #[derive(Deserialize, Debug)]
+        #[serde(rename_all = "camelCase", deny_unknown_fields)]
+        pub struct $name {
+            #[serde(rename = "type")]
+            type_: Option<CS<StarOr<TaskType>>>,
+            status: Option<CS<StarOr<TaskStatus>>>,
+            index_uid: Option<CS<StarOr<IndexUid>>>,
+            uids: Option<CS<TaskId>>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            before_enqueued_at: Option<OffsetDateTime>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            after_enqueued_at: Option<OffsetDateTime>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            before_started_at: Option<OffsetDateTime>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            after_started_at: Option<OffsetDateTime>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            before_finished_at: Option<OffsetDateTime>,
+            #[serde(default, with = "time::serde::rfc3339::option")]
+            after_finished_at: Option<OffsetDateTime>,
+            $($(#[$attr])* $field: $ty,)*
+        }
+
+        impl From<$name> for TasksFilters {
+            fn from(query: $name) -> Self {
+                Self {
+                    type_: query.type_,
+                    status: query.status,
+                    index_uid: query.index_uid,
+                    uids: query.uids,
+                    dates: DateFilters {
+                        before_enqueued_at: query.before_enqueued_at,
+                        after_enqueued_at: query.after_enqueued_at,
+                        before_started_at: query.before_started_at,
+                        after_started_at: query.after_started_at,
+                        before_finished_at: query.before_finished_at,
+                        after_finished_at: query.after_finished_at,
+                    },
+                }
+            }
+        }
+    };
+}
+
+tasks_filters_query!(TaskFilterQuery {
     #[serde(default = "DEFAULT_LIMIT")]
     limit: usize,
     from: Option<TaskId>,
+});
+
+tasks_filters_query!(TaskCancelationQuery {});
+
+tasks_filters_query!(TaskDeletionQuery {});
+
+tasks_filters_query!(TaskStreamQuery {});
+
+/// The date filters shared by the task routes. Each bound is exclusive, and is checked against
+/// the timestamps of the task events.
+#[derive(Debug, Default)]
+pub struct DateFilters {
+    before_enqueued_at: Option<OffsetDateTime>,
+    after_enqueued_at: Option<OffsetDateTime>,
+    before_started_at: Option<OffsetDateTime>,
+    after_started_at: Option<OffsetDateTime>,
+    before_finished_at: Option<OffsetDateTime>,
+    after_finished_at: Option<OffsetDateTime>,
 }
 
-#[rustfmt::skip]
-fn task_type_matches_content(type_: &TaskType, content: &TaskContent) -> bool {
-    matches!((type_, content),
-          (TaskType::IndexCreation, TaskContent::IndexCreation { .. })
-        | (TaskType::IndexUpdate, TaskContent::IndexUpdate { .. })
-        | (TaskType::IndexDeletion, TaskContent::IndexDeletion { .. })
-        | (TaskType::DocumentAdditionOrUpdate, TaskContent::DocumentAddition { .. })
-        | (TaskType::DocumentDeletion, TaskContent::DocumentDeletion{ .. })
-        | (TaskType::SettingsUpdate, TaskContent::SettingsUpdate { .. })
-    )
+impl DateFilters {
+    /// The query parameters of the date filters.
+    const PARAMETERS: [&'static str; 6] = [
+        "beforeEnqueuedAt",
+        "afterEnqueuedAt",
+        "beforeStartedAt",
+        "afterStartedAt",
+        "beforeFinishedAt",
+        "afterFinishedAt",
+    ];
+
+    fn is_empty(&self) -> bool {
+        self.before_enqueued_at.is_none()
+            && self.after_enqueued_at.is_none()
+            && self.before_started_at.is_none()
+            && self.after_started_at.is_none()
+            && self.before_finished_at.is_none()
+            && self.after_finished_at.is_none()
+    }
+
+    /// Makes sure that no range is empty, which is most likely a mistake in the query.
+    fn validate(&self) -> Result<(), MeilisearchHttpError> {
+        let ranges = [
+            ("afterEnqueuedAt", self.after_enqueued_at),
+            ("beforeEnqueuedAt", self.before_enqueued_at),
+            ("afterStartedAt", self.after_started_at),
+            ("beforeStartedAt", self.before_started_at),
+            ("afterFinishedAt", self.after_finished_at),
+            ("beforeFinishedAt", self.before_finished_at),
+        ];
+        for range in ranges.chunks(2) {
+            if let [(after_name, Some(after)), (before_name, Some(before))] = range {
+                if after >= before {
+                    return Err(MeilisearchHttpError::InvalidTaskDateRange(
+                        *after_name,
+                        *before_name,
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restricts the task filter to the dates.
+    fn apply(self, filters: &mut TaskFilter) {
+        if let Some(date) = self.before_enqueued_at {
+            filters.filter_enqueued_before(date);
+        }
+        if let Some(date) = self.after_enqueued_at {
+            filters.filter_enqueued_after(date);
+        }
+        if let Some(date) = self.before_started_at {
+            filters.filter_started_before(date);
+        }
+        if let Some(date) = self.after_started_at {
+            filters.filter_started_after(date);
+        }
+        if let Some(date) = self.before_finished_at {
+            filters.filter_finished_before(date);
+        }
+        if let Some(date) = self.after_finished_at {
+            filters.filter_finished_after(date);
+        }
+    }
 }
 
-#[rustfmt::skip]
-fn task_status_matches_events(status: &TaskStatus, events: &[TaskEvent]) -> bool {
-    events.last().map_or(false, |event| {
-        matches!((status, event),
-              (TaskStatus::Enqueued, TaskEvent::Created(_))
-            | (TaskStatus::Processing, TaskEvent::Processing(_) | TaskEvent::Batched { .. })
-            | (TaskStatus::Succeeded, TaskEvent::Succeeded { .. })
-            | (TaskStatus::Failed, TaskEvent::Failed { .. }),
-        )
-    })
+/// The filters shared by the routes operating on a set of tasks.
+#[derive(Default)]
+struct TasksFilters {
+    type_: Option<CS<StarOr<TaskType>>>,
+    status: Option<CS<StarOr<TaskStatus>>>,
+    index_uid: Option<CS<StarOr<IndexUid>>>,
+    uids: Option<CS<TaskId>>,
+    dates: DateFilters,
 }
 
-async fn get_tasks(
-    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
-    params: web::Query<TaskFilterQuery>,
-    req: HttpRequest,
-    analytics: web::Data<dyn Analytics>,
-) -> Result<HttpResponse, ResponseError> {
-    analytics.publish(
-        "Tasks Seen".to_string(),
-        json!({ "per_task_uid": false }),
-        Some(&req),
-    );
+impl TasksFilters {
+    fn is_empty(&self) -> bool {
+        self.type_.is_none()
+            && self.status.is_none()
+            && self.index_uid.is_none()
+            && self.uids.is_none()
+            && self.dates.is_empty()
+    }
 
-    let TaskFilterQuery {
+    /// The query parameters of the filters, for the routes that require at least one of them.
+    fn parameters() -> Vec<String> {
+        ["type", "status", "indexUid", "uids"]
+            .into_iter()
+            .chain(DateFilters::PARAMETERS)
+            .map(String::from)
+            .collect()
+    }
+
+    fn analytics(&self) -> serde_json::Value {
+        json!({
+            "filtered_by_type": self.type_.is_some(),
+            "filtered_by_status": self.status.is_some(),
+            "filtered_by_index_uid": self.index_uid.is_some(),
+            "filtered_by_uid": self.uids.is_some(),
+            "filtered_by_date": !self.dates.is_empty(),
+        })
+    }
+}
+
+/// Builds the `TaskFilter` matching the filters, restricted to the indexes the caller is allowed
+/// to see. Fails if the filters are invalid.
+fn build_task_filter(
+    search_rules: &SearchRules,
+    filters: TasksFilters,
+) -> Result<Option<TaskFilter>, MeilisearchHttpError> {
+    let TasksFilters {
         type_,
         status,
         index_uid,
-        limit,
-        from,
-    } = params.into_inner();
+        uids,
+        dates,
+    } = filters;
 
-    let search_rules = &meilisearch.filters().search_rules;
+    dates.validate()?;
 
     // We first transform a potential indexUid=* into a "not specified indexUid filter"
     // for every one of the filters: type, status, and indexUid.
@@ -113,28 +277,50 @@ async fn get_tasks(
     // Then we complete the task filter with other potential status and types filters.
     let filters = if type_.is_some() || status.is_some() {
         let mut filters = indexes_filters.unwrap_or_default();
-        filters.filter_fn(move |task| {
-            let matches_type = match &type_ {
-                Some(types) => types
-                    .iter()
-                    .any(|t| task_type_matches_content(t, &task.content)),
-                None => true,
-            };
-
-            let matches_status = match &status {
-                Some(statuses) => statuses
-                    .iter()
-                    .any(|t| task_status_matches_events(t, &task.events)),
-                None => true,
-            };
-
-            matches_type && matches_status
-        });
+        for type_ in type_.into_iter().flatten() {
+            filters.filter_kind(type_.into());
+        }
+        for status in status.into_iter().flatten() {
+            filters.filter_status(status.into());
+        }
         Some(filters)
     } else {
         indexes_filters
     };
 
+    // Finally, we complete the task filter with the potential uids and dates filters.
+    if uids.is_none() && dates.is_empty() {
+        return Ok(filters);
+    }
+
+    let mut filters = filters.unwrap_or_default();
+    for uid in uids.into_iter().flatten() {
+        filters.filter_uid(uid);
+    }
+    dates.apply(&mut filters);
+
+    Ok(Some(filters))
+}
+
+async fn get_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<TaskFilterQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    analytics.publish(
+        "Tasks Seen".to_string(),
+        json!({ "per_task_uid": false }),
+        Some(&req),
+    );
+
+    let params = params.into_inner();
+    let (limit, from) = (params.limit, params.from);
+    let filters = TasksFilters::from(params);
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = build_task_filter(search_rules, filters)?;
+
     // We +1 just to know if there is more after this "page" or not.
     let limit = limit.saturating_add(1);
 
@@ -188,10 +374,222 @@ async fn get_task(
         Some(filters)
     };
 
-    let task: TaskView = meilisearch
-        .get_task(task_id.into_inner(), filters)
-        .await?
-        .into();
+    let task_id = task_id.into_inner();
+    let task = meilisearch.get_task(task_id, filters).await?;
+    let progress = meilisearch.get_task_progress(task_id).await;
+    let task = TaskView::from(task).with_progress(progress);
 
     Ok(HttpResponse::Ok().json(task))
 }
+
+async fn cancel_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_CANCEL }>, MeiliSearch>,
+    params: web::Query<TaskCancelationQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let filters = TasksFilters::from(params.into_inner());
+
+    // Canceling every task of the instance must be explicitly asked for with a `*`.
+    if filters.is_empty() {
+        return Err(MeilisearchHttpError::MissingTaskFilters(
+            "cancelation",
+            TasksFilters::parameters(),
+        )
+        .into());
+    }
+
+    analytics.publish(
+        "Tasks Canceled".to_string(),
+        filters.analytics(),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = build_task_filter(search_rules, filters)?.unwrap_or_default();
+
+    let cancelation = meilisearch.cancel_tasks(filters).await?;
+
+    let view = TaskCancelationView {
+        canceled: cancelation.canceled.into_iter().map(|t| t.id).collect(),
+        processing: cancelation.processing,
+        not_cancelable: cancelation.not_cancelable,
+    };
+
+    Ok(HttpResponse::Ok().json(view))
+}
+
+async fn delete_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_DELETE }>, MeiliSearch>,
+    params: web::Query<TaskDeletionQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let filters = TasksFilters::from(params.into_inner());
+
+    // Deleting every task of the instance must be explicitly asked for with a `*`.
+    if filters.is_empty() {
+        return Err(MeilisearchHttpError::MissingTaskFilters(
+            "deletion",
+            TasksFilters::parameters(),
+        )
+        .into());
+    }
+
+    analytics.publish("Tasks Deleted".to_string(), filters.analytics(), Some(&req));
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = build_task_filter(search_rules, filters)?.unwrap_or_default();
+
+    let deleted = meilisearch.delete_tasks(Some(filters)).await?;
+
+    let view = TaskDeletionView {
+        deleted_tasks: deleted.len(),
+    };
+
+    Ok(HttpResponse::Ok().json(view))
+}
+
+/// Streams the state changes of the tasks as server-sent events, identified by the date of the
+/// state change and the task uid.
+///
+/// When the client reconnects with a `Last-Event-ID` header, the current state of the tasks that
+/// changed after this event is sent before the new state changes. A task may then be sent twice.
+async fn stream_tasks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::TASKS_GET }>, MeiliSearch>,
+    params: web::Query<TaskStreamQuery>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let filters = TasksFilters::from(params.into_inner());
+
+    analytics.publish(
+        "Tasks Streamed".to_string(),
+        filters.analytics(),
+        Some(&req),
+    );
+
+    let search_rules = &meilisearch.filters().search_rules;
+    let filters = build_task_filter(search_rules, filters)?;
+    let accepts = move |task: &Task| filters.as_ref().map_or(true, |f| f.accepts(task));
+
+    // We subscribe before fetching the tasks to replay so that no state change is missed.
+    let mut events = meilisearch.subscribe_tasks().await;
+
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(EventId::parse);
+
+    // A client resuming a stream is sent the current state of the tasks that changed after the
+    // last event it saw, in the order of the changes and a window at a time.
+    let (replayed, truncated) = match last_event_id {
+        Some(last_event_id) => {
+            let mut filter = TaskFilter::default();
+            filter.filter_fn(move |task| EventId::of(task) > last_event_id);
+            let mut replayed = meilisearch.list_tasks(Some(filter), None, None).await?;
+            replayed.sort_unstable_by_key(EventId::of);
+
+            let truncated = replayed.len() > MAX_REPLAYED_TASKS;
+            replayed.truncate(MAX_REPLAYED_TASKS);
+            (replayed, truncated)
+        }
+        None => (Vec::new(), false),
+    };
+
+    let (snd, recv) = mpsc::channel(1);
+    tokio::task::spawn_local(async move {
+        let window_end = replayed.last().map(EventId::of);
+        for task in replayed.into_iter().filter(|task| accepts(task)) {
+            if snd.send(task_event(task)).await.is_err() {
+                return;
+            }
+        }
+
+        // When the replay doesn't reach the latest changes, the stream is closed right away, with
+        // the id of the end of the window, so that the client reconnects to get the next window.
+        if truncated {
+            if let Some(window_end) = window_end {
+                let _ = snd
+                    .send(Bytes::from(format!("id: {}\n\n", window_end)))
+                    .await;
+            }
+            return;
+        }
+
+        let mut keep_alive = tokio::time::interval(STREAM_KEEP_ALIVE_INTERVAL);
+        loop {
+            let event = tokio::select! {
+                received = events.recv() => match received {
+                    Ok(task) if accepts(&task) => task_event(task),
+                    Ok(_) => continue,
+                    // The stream lagged behind and missed some state changes, it is closed so
+                    // that the client reconnects and gets the current state of the tasks.
+                    Err(_) => break,
+                },
+                _ = keep_alive.tick() => Bytes::from_static(b":\n\n"),
+            };
+
+            if snd.send(event).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let stream = tokio_stream::wrappers::ReceiverStream::new(recv).map(Ok::<_, Infallible>);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .streaming(stream))
+}
+
+/// The id of a task state change in a task stream. The ids increase with the changes, the uid
+/// breaking the ties between the tasks changed at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct EventId {
+    /// The date of the change, in nanoseconds since the epoch.
+    timestamp: i128,
+    uid: TaskId,
+}
+
+impl EventId {
+    fn of(task: &Task) -> Self {
+        Self {
+            timestamp: task
+                .updated_at()
+                .map_or(0, OffsetDateTime::unix_timestamp_nanos),
+            uid: task.id,
+        }
+    }
+
+    fn parse(id: &str) -> Option<Self> {
+        let (timestamp, uid) = id.trim().rsplit_once('-')?;
+        Some(Self {
+            timestamp: timestamp.parse().ok()?,
+            uid: uid.parse().ok()?,
+        })
+    }
+}
+
+impl std::fmt::Display for EventId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.timestamp, self.uid)
+    }
+}
+
+/// Formats the state of the task as a server-sent event.
+fn task_event(task: Task) -> Bytes {
+    let id = EventId::of(&task);
+    let status = task.status();
+    let view = TaskView::from(task);
+    let data = serde_json::to_string(&view).unwrap_or_default();
+
+    Bytes::from(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        id,
+        status.as_str(),
+        data
+    ))
+}
@@ -0,0 +1,118 @@
+use actix_web::{web, HttpRequest, HttpResponse};
+use log::debug;
+use meilisearch_auth::SearchRules;
+use meilisearch_error::ResponseError;
+use meilisearch_lib::tasks::error::TaskError;
+use meilisearch_lib::tasks::webhooks::Webhook;
+use meilisearch_lib::MeiliSearch;
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::analytics::Analytics;
+use crate::error::MeilisearchHttpError;
+use crate::extractors::authentication::{policies::*, GuardedData};
+use crate::extractors::sequential_extractor::SeqHandler;
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("")
+            .route(web::post().to(SeqHandler(create_webhook)))
+            .route(web::get().to(SeqHandler(list_webhooks))),
+    )
+    .service(
+        web::resource("/{webhook_uid}")
+            .route(web::get().to(SeqHandler(get_webhook)))
+            .route(web::delete().to(SeqHandler(delete_webhook))),
+    );
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct WebhookRequest {
+    url: String,
+    index_uid: Option<String>,
+}
+
+/// Returns whether the key is allowed to see the tasks sent to the webhook.
+fn is_webhook_authorized(search_rules: &SearchRules, webhook: &Webhook) -> bool {
+    search_rules.is_index_authorized(webhook.index_uid.as_deref().unwrap_or("*"))
+}
+
+pub async fn create_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_CREATE }>, MeiliSearch>,
+    body: web::Json<WebhookRequest>,
+    req: HttpRequest,
+    analytics: web::Data<dyn Analytics>,
+) -> Result<HttpResponse, ResponseError> {
+    let WebhookRequest { url, index_uid } = body.into_inner();
+
+    analytics.publish(
+        "Webhook Created".to_string(),
+        json!({ "per_index_uid": index_uid.is_some() }),
+        Some(&req),
+    );
+
+    // A webhook without index receives the tasks of every index.
+    let index = index_uid.as_deref().unwrap_or("*");
+    if !meilisearch
+        .filters()
+        .search_rules
+        .is_index_authorized(index)
+    {
+        return Err(MeilisearchHttpError::UnauthorizedIndex(index.to_string()).into());
+    }
+
+    let webhook = meilisearch.register_webhook(url, index_uid).await?;
+
+    debug!("returns: {:?}", webhook);
+    Ok(HttpResponse::Created().json(webhook))
+}
+
+pub async fn list_webhooks(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, MeiliSearch>,
+) -> Result<HttpResponse, ResponseError> {
+    let search_rules = &meilisearch.filters().search_rules;
+    let webhooks: Vec<_> = meilisearch
+        .list_webhooks()
+        .await?
+        .into_iter()
+        .filter(|webhook| is_webhook_authorized(search_rules, webhook))
+        .collect();
+
+    debug!("returns: {:?}", webhooks);
+    Ok(HttpResponse::Ok().json(json!({ "results": webhooks })))
+}
+
+pub async fn get_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_GET }>, MeiliSearch>,
+    webhook_uid: web::Path<Uuid>,
+) -> Result<HttpResponse, ResponseError> {
+    let webhook = authorized_webhook(&meilisearch, webhook_uid.into_inner()).await?;
+
+    debug!("returns: {:?}", webhook);
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+pub async fn delete_webhook(
+    meilisearch: GuardedData<ActionPolicy<{ actions::WEBHOOKS_DELETE }>, MeiliSearch>,
+    webhook_uid: web::Path<Uuid>,
+) -> Result<HttpResponse, ResponseError> {
+    let webhook = authorized_webhook(&meilisearch, webhook_uid.into_inner()).await?;
+    meilisearch.delete_webhook(webhook.uid).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Fetches a webhook, reporting the webhooks the key isn't allowed to see as missing.
+async fn authorized_webhook<P>(
+    meilisearch: &GuardedData<P, MeiliSearch>,
+    uid: Uuid,
+) -> Result<Webhook, ResponseError> {
+    let webhook = meilisearch.get_webhook(uid).await?;
+    if is_webhook_authorized(&meilisearch.filters().search_rules, &webhook) {
+        Ok(webhook)
+    } else {
+        Err(TaskError::UnexistingWebhook(uid).into())
+    }
+}
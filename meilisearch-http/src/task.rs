@@ -6,9 +6,11 @@ use meilisearch_error::ResponseError;
 use meilisearch_lib::index::{Settings, Unchecked};
 use meilisearch_lib::tasks::batch::BatchId;
 use meilisearch_lib::tasks::task::{
-    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult,
+    self, DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskKind, TaskProgress,
+    TaskResult,
 };
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use time::{Duration, OffsetDateTime};
 
 use crate::AUTOBATCHING_ENABLED;
@@ -21,6 +23,7 @@ pub enum TaskType {
     IndexDeletion,
     DocumentAdditionOrUpdate,
     DocumentDeletion,
+    DocumentEdition,
     SettingsUpdate,
     DumpCreation,
 }
@@ -33,12 +36,28 @@ impl From<TaskContent> for TaskType {
             TaskContent::IndexDeletion { .. } => TaskType::IndexDeletion,
             TaskContent::DocumentAddition { .. } => TaskType::DocumentAdditionOrUpdate,
             TaskContent::DocumentDeletion { .. } => TaskType::DocumentDeletion,
+            TaskContent::DocumentEdition { .. } => TaskType::DocumentEdition,
             TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
             TaskContent::Dump { .. } => TaskType::DumpCreation,
         }
     }
 }
 
+impl From<TaskType> for TaskKind {
+    fn from(other: TaskType) -> Self {
+        match other {
+            TaskType::IndexCreation => TaskKind::IndexCreation,
+            TaskType::IndexUpdate => TaskKind::IndexUpdate,
+            TaskType::IndexDeletion => TaskKind::IndexDeletion,
+            TaskType::DocumentAdditionOrUpdate => TaskKind::DocumentAddition,
+            TaskType::DocumentDeletion => TaskKind::DocumentDeletion,
+            TaskType::DocumentEdition => TaskKind::DocumentEdition,
+            TaskType::SettingsUpdate => TaskKind::SettingsUpdate,
+            TaskType::DumpCreation => TaskKind::Dump,
+        }
+    }
+}
+
 impl FromStr for TaskType {
     type Err = String;
 
@@ -53,6 +72,8 @@ impl FromStr for TaskType {
             Ok(TaskType::DocumentAdditionOrUpdate)
         } else if status.eq_ignore_ascii_case("documentDeletion") {
             Ok(TaskType::DocumentDeletion)
+        } else if status.eq_ignore_ascii_case("documentEdition") {
+            Ok(TaskType::DocumentEdition)
         } else if status.eq_ignore_ascii_case("settingsUpdate") {
             Ok(TaskType::SettingsUpdate)
         } else if status.eq_ignore_ascii_case("dumpCreation") {
@@ -61,7 +82,7 @@ impl FromStr for TaskType {
             Err(format!(
                 "invalid task type `{}`, expecting one of: \
                 indexCreation, indexUpdate, indexDeletion, documentAdditionOrUpdate, \
-                documentDeletion, settingsUpdate, dumpCreation",
+                documentDeletion, documentEdition, settingsUpdate, dumpCreation",
                 status
             ))
         }
@@ -75,6 +96,19 @@ pub enum TaskStatus {
     Processing,
     Succeeded,
     Failed,
+    Canceled,
+}
+
+impl From<TaskStatus> for task::TaskStatus {
+    fn from(other: TaskStatus) -> Self {
+        match other {
+            TaskStatus::Enqueued => task::TaskStatus::Enqueued,
+            TaskStatus::Processing => task::TaskStatus::Processing,
+            TaskStatus::Succeeded => task::TaskStatus::Succeeded,
+            TaskStatus::Failed => task::TaskStatus::Failed,
+            TaskStatus::Canceled => task::TaskStatus::Canceled,
+        }
+    }
 }
 
 impl FromStr for TaskStatus {
@@ -89,10 +123,12 @@ impl FromStr for TaskStatus {
             Ok(TaskStatus::Succeeded)
         } else if status.eq_ignore_ascii_case("failed") {
             Ok(TaskStatus::Failed)
+        } else if status.eq_ignore_ascii_case("canceled") {
+            Ok(TaskStatus::Canceled)
         } else {
             Err(format!(
                 "invalid task status `{}`, expecting one of: \
-                enqueued, processing, succeeded, or failed",
+                enqueued, processing, succeeded, failed, or canceled",
                 status,
             ))
         }
@@ -121,6 +157,17 @@ enum TaskDetails {
         deleted_documents: Option<u64>,
     },
     #[serde(rename_all = "camelCase")]
+    DocumentDeletionByFilter {
+        original_filter: Value,
+        deleted_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    DocumentEdition {
+        original_filter: Option<Value>,
+        function: String,
+        edited_documents: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
     ClearAll { deleted_documents: Option<u64> },
     #[serde(rename_all = "camelCase")]
     Dump { dump_uid: String },
@@ -199,6 +246,18 @@ pub struct TaskView {
     finished_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     batch_uid: Option<Option<BatchId>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    progress: Option<TaskProgress>,
+}
+
+impl TaskView {
+    /// Adds the indexing progress of a processing task to the view.
+    pub fn with_progress(mut self, progress: Option<TaskProgress>) -> Self {
+        if matches!(self.status, TaskStatus::Processing) {
+            self.progress = progress;
+        }
+        self
+    }
 }
 
 impl From<Task> for TaskView {
@@ -231,6 +290,16 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentDeletion {
+                deletion: DocumentDeletion::Filter(filter),
+                ..
+            } => (
+                TaskType::DocumentDeletion,
+                Some(TaskDetails::DocumentDeletionByFilter {
+                    original_filter: filter,
+                    deleted_documents: None,
+                }),
+            ),
             TaskContent::DocumentDeletion {
                 deletion: DocumentDeletion::Clear,
                 ..
@@ -240,6 +309,16 @@ impl From<Task> for TaskView {
                     deleted_documents: None,
                 }),
             ),
+            TaskContent::DocumentEdition {
+                filter, function, ..
+            } => (
+                TaskType::DocumentEdition,
+                Some(TaskDetails::DocumentEdition {
+                    original_filter: filter,
+                    function,
+                    edited_documents: None,
+                }),
+            ),
             TaskContent::IndexDeletion { .. } => (
                 TaskType::IndexDeletion,
                 Some(TaskDetails::ClearAll {
@@ -295,6 +374,28 @@ impl From<Task> for TaskView {
                     ) => {
                         deleted_documents.replace(*docs);
                     }
+                    (
+                        TaskResult::DocumentDeletion {
+                            deleted_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentDeletionByFilter {
+                            ref mut deleted_documents,
+                            ..
+                        }),
+                    ) => {
+                        deleted_documents.replace(*docs);
+                    }
+                    (
+                        TaskResult::DocumentEdition {
+                            edited_documents: docs,
+                        },
+                        Some(TaskDetails::DocumentEdition {
+                            ref mut edited_documents,
+                            ..
+                        }),
+                    ) => {
+                        edited_documents.replace(*docs);
+                    }
                     (
                         TaskResult::ClearAll {
                             deleted_documents: docs,
@@ -317,6 +418,18 @@ impl From<Task> for TaskView {
                     }) => {
                         deleted_documents.replace(0);
                     }
+                    Some(TaskDetails::DocumentDeletionByFilter {
+                        ref mut deleted_documents,
+                        ..
+                    }) => {
+                        deleted_documents.replace(0);
+                    }
+                    Some(TaskDetails::DocumentEdition {
+                        ref mut edited_documents,
+                        ..
+                    }) => {
+                        edited_documents.replace(0);
+                    }
                     Some(TaskDetails::ClearAll {
                         ref mut deleted_documents,
                         ..
@@ -333,6 +446,7 @@ impl From<Task> for TaskView {
                 }
                 (TaskStatus::Failed, Some(error.clone()), Some(*timestamp))
             }
+            TaskEvent::Canceled(timestamp) => (TaskStatus::Canceled, None, Some(*timestamp)),
         };
 
         let enqueued_at = match events.first() {
@@ -369,6 +483,7 @@ impl From<Task> for TaskView {
             started_at,
             finished_at,
             batch_uid,
+            progress: None,
         }
     }
 }
@@ -381,6 +496,93 @@ pub struct TaskListView {
     pub next: Option<TaskId>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchSize {
+    tasks: usize,
+    received_documents: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchView {
+    pub uid: BatchId,
+    index_uid: Option<String>,
+    task_uids: Vec<TaskId>,
+    size: BatchSize,
+    #[serde(serialize_with = "serialize_duration")]
+    duration: Option<Duration>,
+    #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
+    started_at: Option<OffsetDateTime>,
+    #[serde(serialize_with = "time::serde::rfc3339::option::serialize")]
+    finished_at: Option<OffsetDateTime>,
+}
+
+impl From<(BatchId, Vec<Task>)> for BatchView {
+    fn from((uid, tasks): (BatchId, Vec<Task>)) -> Self {
+        // All the tasks of a batch target the same index.
+        let index_uid = tasks.first().and_then(Task::index_uid).map(String::from);
+
+        let received_documents = tasks
+            .iter()
+            .map(|task| match task.content {
+                TaskContent::DocumentAddition {
+                    documents_count, ..
+                } => documents_count,
+                _ => 0,
+            })
+            .sum();
+
+        let started_at = tasks.iter().filter_map(Task::started_at).min();
+        // A batch is finished once all its tasks are.
+        let finished_at = tasks
+            .iter()
+            .map(Task::finished_at)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|dates| dates.into_iter().max());
+
+        let duration = finished_at.zip(started_at).map(|(tf, ts)| (tf - ts));
+
+        Self {
+            uid,
+            index_uid,
+            task_uids: tasks.iter().map(|task| task.id).collect(),
+            size: BatchSize {
+                tasks: tasks.len(),
+                received_documents,
+            },
+            duration,
+            started_at,
+            finished_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchListView {
+    pub results: Vec<BatchView>,
+    pub limit: usize,
+    pub from: Option<BatchId>,
+    pub next: Option<BatchId>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCancelationView {
+    /// The uids of the enqueued tasks that were canceled.
+    pub canceled: Vec<TaskId>,
+    /// The uids of the processing tasks that will be canceled if they weren't indexed yet.
+    pub processing: Vec<TaskId>,
+    /// The uids of the processing tasks that can't be canceled.
+    pub not_cancelable: Vec<TaskId>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskDeletionView {
+    pub deleted_tasks: usize,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SummarizedTaskView {
@@ -10,14 +10,25 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
     Lazy::new(|| {
         hashmap! {
             ("POST",    "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/indexes/products/facet-search") =>                   hashset!{"search", "*"},
             ("GET",     "/indexes/products/search") =>                         hashset!{"search", "*"},
+            ("POST",    "/multi-search") =>                                    hashset!{"search", "*"},
             ("POST",    "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
+            ("PATCH",   "/indexes/products/documents") =>                      hashset!{"documents.add", "*"},
             ("GET",     "/indexes/products/documents") =>                      hashset!{"documents.get", "*"},
+            ("POST",    "/indexes/products/documents/fetch") =>                hashset!{"documents.get", "*"},
+            ("GET",     "/indexes/products/documents/export") =>               hashset!{"documents.get", "*"},
             ("GET",     "/indexes/products/documents/0") =>                    hashset!{"documents.get", "*"},
             ("DELETE",  "/indexes/products/documents/0") =>                    hashset!{"documents.delete", "*"},
+            ("POST",    "/indexes/products/documents/delete") =>               hashset!{"documents.delete", "*"},
+            ("POST",    "/indexes/products/documents/edit") =>                 hashset!{"documents.add", "*"},
             ("GET",     "/tasks") =>                                           hashset!{"tasks.get", "*"},
             ("GET",     "/tasks?indexUid=products") =>                         hashset!{"tasks.get", "*"},
             ("GET",     "/tasks/0") =>                                         hashset!{"tasks.get", "*"},
+            ("POST",    "/tasks/cancel?indexUid=products") =>                  hashset!{"tasks.cancel", "*"},
+            ("DELETE",  "/tasks?indexUid=products") =>                         hashset!{"tasks.delete", "*"},
+            ("GET",     "/batches") =>                                         hashset!{"tasks.get", "*"},
+            ("GET",     "/batches/0") =>                                       hashset!{"tasks.get", "*"},
             ("PATCH",   "/indexes/products/") =>                               hashset!{"indexes.update", "*"},
             ("GET",     "/indexes/products/") =>                               hashset!{"indexes.get", "*"},
             ("DELETE",  "/indexes/products/") =>                               hashset!{"indexes.delete", "*"},
@@ -26,19 +37,25 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("GET",     "/indexes/products/settings") =>                       hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/faceting") =>              hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/pagination") =>            hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.get", "*"},
+            ("GET",     "/indexes/products/settings/search-cutoff-ms") =>      hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.get", "*"},
             ("GET",     "/indexes/products/settings/synonyms") =>              hashset!{"settings.get", "*"},
             ("DELETE",  "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings") =>                       hashset!{"settings.update", "*"},
+            ("PATCH",   "/indexes/products/settings/faceting") =>              hashset!{"settings.update", "*"},
+            ("PATCH",   "/indexes/products/settings/pagination") =>            hashset!{"settings.update", "*"},
             ("PATCH",   "/indexes/products/settings/typo-tolerance") =>        hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/displayed-attributes") =>  hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/distinct-attribute") =>    hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/filterable-attributes") => hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/ranking-rules") =>         hashset!{"settings.update", "*"},
+            ("PUT",     "/indexes/products/settings/search-cutoff-ms") =>      hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/searchable-attributes") => hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/sortable-attributes") =>   hashset!{"settings.update", "*"},
             ("PUT",     "/indexes/products/settings/stop-words") =>            hashset!{"settings.update", "*"},
@@ -52,6 +69,10 @@ pub static AUTHORIZATIONS: Lazy<HashMap<(&'static str, &'static str), HashSet<&'
             ("DELETE",  "/keys/mykey/") =>                                     hashset!{"keys.delete", "*"},
             ("POST",    "/keys") =>                                            hashset!{"keys.create", "*"},
             ("GET",     "/keys") =>                                            hashset!{"keys.get", "*"},
+            ("POST",    "/webhooks") =>                                        hashset!{"webhooks.create", "*"},
+            ("GET",     "/webhooks") =>                                        hashset!{"webhooks.get", "*"},
+            ("GET",     "/webhooks/f0a1e8e6-5a8f-4a4f-9a4e-000000000000") =>   hashset!{"webhooks.get", "*"},
+            ("DELETE",  "/webhooks/f0a1e8e6-5a8f-4a4f-9a4e-000000000000") =>   hashset!{"webhooks.delete", "*"},
         }
     });
 
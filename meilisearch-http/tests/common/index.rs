@@ -88,6 +88,22 @@ impl Index<'_> {
         self.service.put(url, documents).await
     }
 
+    pub async fn merge_patch_documents(
+        &self,
+        documents: Value,
+        primary_key: Option<&str>,
+    ) -> (Value, StatusCode) {
+        let url = match primary_key {
+            Some(key) => format!(
+                "/indexes/{}/documents?primaryKey={}",
+                encode(self.uid.as_ref()),
+                key
+            ),
+            None => format!("/indexes/{}/documents", encode(self.uid.as_ref())),
+        };
+        self.service.patch(url, documents).await
+    }
+
     pub async fn wait_task(&self, update_id: u64) -> Value {
         // try 10 times to get status, or panic to not wait forever
         let url = format!("/tasks/{}", update_id);
@@ -151,9 +167,22 @@ impl Index<'_> {
             url.push_str(&format!("fields={}&", attributes_to_retrieve.join(",")));
         }
 
+        if let Some(filter) = options.filter {
+            url.push_str(&format!("filter={}&", encode(filter)));
+        }
+
+        if let Some(sort) = options.sort {
+            url.push_str(&format!("sort={}&", encode(&sort.join(","))));
+        }
+
         self.service.get(url).await
     }
 
+    pub async fn fetch_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/fetch", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn delete_document(&self, id: u64) -> (Value, StatusCode) {
         let url = format!("/indexes/{}/documents/{}", encode(self.uid.as_ref()), id);
         self.service.delete(url).await
@@ -164,6 +193,16 @@ impl Index<'_> {
         self.service.delete(url).await
     }
 
+    pub async fn delete_by_filter(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/delete", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
+    pub async fn edit_documents(&self, body: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/documents/edit", encode(self.uid.as_ref()));
+        self.service.post(url, body).await
+    }
+
     pub async fn delete_batch(&self, ids: Vec<u64>) -> (Value, StatusCode) {
         let url = format!(
             "/indexes/{}/documents/delete-batch",
@@ -219,6 +258,11 @@ impl Index<'_> {
         self.service.post(url, query).await
     }
 
+    pub async fn facet_search(&self, query: Value) -> (Value, StatusCode) {
+        let url = format!("/indexes/{}/facet-search", encode(self.uid.as_ref()));
+        self.service.post(url, query).await
+    }
+
     pub async fn search_get(&self, query: Value) -> (Value, StatusCode) {
         let params = serde_url_params::to_string(&query).unwrap();
         let url = format!("/indexes/{}/search?{}", encode(self.uid.as_ref()), params);
@@ -253,4 +297,6 @@ pub struct GetAllDocumentsOptions {
     pub limit: Option<usize>,
     pub offset: Option<usize>,
     pub attributes_to_retrieve: Option<Vec<&'static str>>,
+    pub filter: Option<&'static str>,
+    pub sort: Option<Vec<&'static str>>,
 }
@@ -1,4 +1,8 @@
+use std::time::Duration;
+
+use actix_web::body::MessageBody;
 use actix_web::{http::StatusCode, test};
+use futures::future::poll_fn;
 use meilisearch_auth::AuthController;
 use meilisearch_lib::MeiliSearch;
 use serde_json::Value;
@@ -90,6 +94,61 @@ impl Service {
         (response, status_code)
     }
 
+    /// Reads the server-sent events of a streaming route until `count` events were received or
+    /// the stream is closed. The keep-alive comments are ignored.
+    pub async fn get_event_stream(
+        &self,
+        url: impl AsRef<str>,
+        last_event_id: Option<&str>,
+        count: usize,
+    ) -> (Vec<String>, StatusCode) {
+        let app = test::init_service(create_app!(
+            &self.meilisearch,
+            &self.auth,
+            true,
+            &self.options,
+            analytics::MockAnalytics::new(&self.options).0
+        ))
+        .await;
+
+        let mut req = test::TestRequest::get().uri(url.as_ref());
+        if let Some(api_key) = &self.api_key {
+            req = req.insert_header(("Authorization", ["Bearer ", api_key].concat()));
+        }
+        if let Some(last_event_id) = last_event_id {
+            req = req.insert_header(("Last-Event-ID", last_event_id));
+        }
+        let req = req.to_request();
+        let res = test::call_service(&app, req).await;
+        let status_code = res.status();
+
+        let mut body = Box::pin(res.into_body());
+        let mut buffer = String::new();
+        let mut events = Vec::new();
+        while events.len() < count {
+            let chunk = tokio::time::timeout(
+                Duration::from_secs(10),
+                poll_fn(|cx| body.as_mut().poll_next(cx)),
+            )
+            .await
+            .expect("timed out waiting for an event");
+
+            match chunk {
+                Some(Ok(bytes)) => buffer.push_str(std::str::from_utf8(&bytes).unwrap()),
+                _ => break,
+            }
+
+            while let Some(end) = buffer.find("\n\n") {
+                let event: String = buffer.drain(..end + 2).collect();
+                if !event.starts_with(':') {
+                    events.push(event);
+                }
+            }
+        }
+
+        (events, status_code)
+    }
+
     pub async fn put(&self, url: impl AsRef<str>, body: Value) -> (Value, StatusCode) {
         let app = test::init_service(create_app!(
             &self.meilisearch,
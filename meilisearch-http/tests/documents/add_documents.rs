@@ -810,6 +810,64 @@ async fn update_document() {
     );
 }
 
+#[actix_rt::test]
+async fn merge_patch_documents() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        {
+            "doc_id": 1,
+            "title": "foo",
+            "price": { "eur": 10, "usd": 12 },
+            "color": "blue",
+        }
+    ]);
+
+    let (_response, code) = index.add_documents(documents, None).await;
+    assert_eq!(code, 202);
+
+    index.wait_task(0).await;
+
+    // the nested object is merged, null removes a field, and the patches of a batch
+    // to the same document are applied in order
+    let documents = json!([
+        {
+            "doc_id": 1,
+            "price": { "eur": 9 },
+            "color": null,
+        },
+        {
+            "doc_id": 1,
+            "title": "bar",
+        },
+        {
+            "doc_id": 2,
+            "title": "new",
+        }
+    ]);
+
+    let (response, code) = index.merge_patch_documents(documents, None).await;
+    assert_eq!(code, 202, "response: {}", response);
+
+    index.wait_task(1).await;
+
+    let (response, code) = index.get_task(1).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded", "{}", response);
+
+    let (response, code) = index.get_document(1, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "doc_id": 1, "title": "bar", "price": { "eur": 9, "usd": 12 } })
+    );
+
+    let (response, code) = index.get_document(2, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(response, json!({ "doc_id": 2, "title": "new" }));
+}
+
 #[actix_rt::test]
 async fn add_larger_dataset() {
     let server = Server::new().await;
@@ -145,3 +145,83 @@ async fn delete_no_document_batch() {
     assert_eq!(code, 200);
     assert_eq!(response["results"].as_array().unwrap().len(), 3);
 }
+
+#[actix_rt::test]
+async fn delete_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "color": "red" },
+                { "id": 1, "color": "blue" },
+                { "id": 2, "color": "red" },
+                { "id": 3 },
+            ]),
+            Some("id"),
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index
+        .delete_by_filter(json!({ "filter": "color = red" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentDeletion");
+    assert_eq!(response["details"]["originalFilter"], "color = red");
+    assert_eq!(response["details"]["deletedDocuments"], 2);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    let ids: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|doc| doc["id"].clone())
+        .collect();
+    assert_eq!(ids, [json!(1), json!(3)]);
+}
+
+#[actix_rt::test]
+async fn delete_documents_by_filter_errors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index
+        .add_documents(json!([{ "id": 0, "color": "red" }]), Some("id"))
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.delete_by_filter(json!({})).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    // The syntax of the filter is checked before the task is registered.
+    for filter in [
+        json!("color ="),
+        json!(42),
+        json!("_geoBoundingBox([50, 3])"),
+    ] {
+        let (response, code) = index.delete_by_filter(json!({ "filter": filter })).await;
+        assert_eq!(code, 400, "{}", response);
+        assert_eq!(response["code"], "invalid_filter");
+    }
+
+    let (response, code) = index.delete_by_filter(json!({ "filter": "id = 0" })).await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(response["error"]["code"], "invalid_filter");
+    assert_eq!(response["details"]["deletedDocuments"], 0);
+}
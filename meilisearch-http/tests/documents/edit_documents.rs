@@ -0,0 +1,150 @@
+use serde_json::json;
+
+use crate::common::{GetAllDocumentsOptions, Server};
+
+#[actix_rt::test]
+async fn edit_documents_by_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["year"]}))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "title": "Dune", "year": 1965, "stock": 3 },
+                { "id": 1, "title": "Hyperion", "year": 1989, "stock": 1 },
+                { "id": 2, "title": "Leviathan Wakes", "year": 2011, "stock": 0 },
+                { "id": 3, "title": "Project Hail Mary", "year": 2021, "stock": 5 },
+            ]),
+            Some("id"),
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let function = "doc.archived = true; doc.stock = doc.stock - 1; doc.title = upper(doc.title)";
+    let (response, code) = index
+        .edit_documents(json!({ "filter": "year < 2015", "function": function }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["type"], "documentEdition");
+    assert_eq!(response["details"]["originalFilter"], "year < 2015");
+    assert_eq!(response["details"]["function"], function);
+    assert_eq!(response["details"]["editedDocuments"], 3);
+
+    let (response, code) = index.get_document(0, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 0, "title": "DUNE", "year": 1965, "stock": 2, "archived": true })
+    );
+
+    let (response, code) = index.get_document(3, None).await;
+    assert_eq!(code, 200);
+    assert_eq!(
+        response,
+        json!({ "id": 3, "title": "Project Hail Mary", "year": 2021, "stock": 5 })
+    );
+
+    // the edited documents are indexed
+    index
+        .search(json!({ "q": "leviathan" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"][0]["title"], "LEVIATHAN WAKES");
+        })
+        .await;
+
+    // without a filter, all the documents are edited, and the unchanged ones aren't counted
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.stock = if(doc.stock < 0, 0, doc.stock)" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(3).await;
+    assert_eq!(response["status"], "succeeded", "{}", response);
+    assert_eq!(response["details"]["originalFilter"], json!(null));
+    assert_eq!(response["details"]["editedDocuments"], 1);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions::default())
+        .await;
+    assert_eq!(code, 200);
+    let stocks: Vec<_> = response["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|doc| doc["stock"].clone())
+        .collect();
+    assert_eq!(stocks, [json!(2), json!(0), json!(0), json!(5)]);
+}
+
+#[actix_rt::test]
+async fn edit_documents_errors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(
+            json!([{ "id": 0, "title": "Dune" }, { "id": 1, "title": null }]),
+            Some("id"),
+        )
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.edit_documents(json!({})).await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+
+    // the syntax errors are returned before the task is registered
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.title = exec('rm -rf /')" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_document_edition_function");
+    assert_eq!(
+        response["message"],
+        "Invalid document edition function: unknown function `exec` at position 12."
+    );
+
+    // so are the syntax errors of the filter
+    let (response, code) = index
+        .edit_documents(json!({ "filter": "title =", "function": "doc.title = 'Dune'" }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+
+    // the task fails without editing any document when the function fails on one of them
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.title = upper(doc.title)" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(1).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(
+        response["error"]["code"],
+        "invalid_document_edition_function"
+    );
+    assert_eq!(
+        response["error"]["message"],
+        "The document edition function failed on the document `1`: expected a string, found null."
+    );
+    assert_eq!(response["details"]["editedDocuments"], 0);
+
+    let (response, _code) = index.get_document(0, None).await;
+    assert_eq!(response["title"], "Dune");
+
+    let (response, code) = index
+        .edit_documents(json!({ "function": "doc.id = doc.id + 1" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+
+    let response = index.wait_task(2).await;
+    assert_eq!(response["status"], "failed", "{}", response);
+    assert_eq!(
+        response["error"]["message"],
+        "The document edition function failed on the document `0`: the primary key `id` can't be modified."
+    );
+}
@@ -0,0 +1,127 @@
+use actix_web::http::StatusCode;
+use actix_web::test;
+use meilisearch_http::{analytics, create_app};
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+/// Returns the content type and the raw body of the export, which isn't JSON.
+async fn export(server: &Server, query: &str) -> (StatusCode, String, String) {
+    let app = test::init_service(create_app!(
+        &server.service.meilisearch,
+        &server.service.auth,
+        true,
+        &server.service.options,
+        analytics::MockAnalytics::new(&server.service.options).0
+    ))
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/indexes/test/documents/export?{}", query))
+        .to_request();
+    let res = test::call_service(&app, req).await;
+    let status_code = res.status();
+    let content_type = res
+        .headers()
+        .get("content-type")
+        .map(|content_type| content_type.to_str().unwrap().to_string())
+        .unwrap_or_default();
+    let body = test::read_body(res).await;
+    (
+        status_code,
+        content_type,
+        String::from_utf8(body.to_vec()).unwrap(),
+    )
+}
+
+async fn index_with_documents(server: &Server) {
+    let index = server.index("test");
+    index
+        .update_settings(json!({"filterableAttributes": ["color"]}))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "color": "red", "price": { "eur": 10 } },
+                { "id": 1, "color": "blue, green", "price": { "eur": 20 } },
+                { "id": 2, "color": "red" },
+            ]),
+            Some("id"),
+        )
+        .await;
+    index.wait_task(1).await;
+}
+
+#[actix_rt::test]
+async fn export_documents_ndjson() {
+    let server = Server::new().await;
+    index_with_documents(&server).await;
+
+    let (code, content_type, body) = export(&server, "").await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(content_type, "application/x-ndjson");
+    let documents: Vec<Value> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        documents,
+        [
+            json!({ "id": 0, "color": "red", "price": { "eur": 10 } }),
+            json!({ "id": 1, "color": "blue, green", "price": { "eur": 20 } }),
+            json!({ "id": 2, "color": "red" }),
+        ]
+    );
+
+    let (code, _, body) = export(&server, "filter=color%20%3D%20red&fields=id").await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(body, "{\"id\":0}\n{\"id\":2}\n");
+}
+
+#[actix_rt::test]
+async fn export_documents_csv() {
+    let server = Server::new().await;
+    index_with_documents(&server).await;
+
+    let (code, content_type, body) = export(&server, "format=csv&fields=id,color,price.eur").await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(content_type, "text/csv");
+    assert_eq!(
+        body,
+        "id,color,price.eur\n0,red,10\n1,\"blue, green\",20\n2,red,\n"
+    );
+}
+
+#[actix_rt::test]
+async fn export_documents_csv_without_fields() {
+    let server = Server::new().await;
+    index_with_documents(&server).await;
+
+    // The nested fields are in the column of their parent.
+    let (code, _, body) = export(&server, "format=csv").await;
+    assert_eq!(code, 200, "{}", body);
+    assert_eq!(
+        body,
+        "id,color,price\n0,red,\"{\"\"eur\"\":10}\"\n1,\"blue, green\",\"{\"\"eur\"\":20}\"\n2,red,\n"
+    );
+}
+
+#[actix_rt::test]
+async fn export_documents_errors() {
+    let server = Server::new().await;
+    index_with_documents(&server).await;
+
+    let (code, _, body) = export(&server, "filter=id%20%3D%200").await;
+    assert_eq!(code, 400, "{}", body);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["code"], "invalid_filter");
+
+    let (code, _, body) = export(&server, "format=xml").await;
+    assert_eq!(code, 400, "{}", body);
+
+    let server = Server::new().await;
+    let (code, _, body) = export(&server, "").await;
+    assert_eq!(code, 404, "{}", body);
+    let response: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(response["code"], "index_not_found");
+}
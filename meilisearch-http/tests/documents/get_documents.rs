@@ -400,3 +400,100 @@ async fn get_documents_displayed_attributes_is_ignored() {
     assert_eq!(response.as_object().unwrap().keys().count(), 16);
     assert!(response.as_object().unwrap().get("gender").is_some());
 }
+
+#[actix_rt::test]
+async fn get_all_documents_filter_and_sort() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["color"],
+            "sortableAttributes": ["price"],
+        }))
+        .await;
+    index
+        .add_documents(
+            json!([
+                { "id": 0, "color": "red", "price": 30 },
+                { "id": 1, "color": "blue", "price": 10 },
+                { "id": 2, "color": "red", "price": 20 },
+                { "id": 3, "color": "red", "price": 10 },
+            ]),
+            Some("id"),
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let ids = |response: &serde_json::Value| -> Vec<u64> {
+        response["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|doc| doc["id"].as_u64().unwrap())
+            .collect()
+    };
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            filter: Some("color = red"),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [0, 2, 3]);
+    assert_eq!(response["total"], 3);
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            filter: Some("color = red"),
+            sort: Some(vec!["price:asc"]),
+            limit: Some(2),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [3, 2]);
+    assert_eq!(response["total"], 3);
+
+    let (response, code) = index
+        .fetch_documents(json!({
+            "filter": ["color = red"],
+            "sort": ["price:desc"],
+            "offset": 1,
+            "fields": ["id"],
+        }))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"], json!([{ "id": 2 }, { "id": 3 }]));
+    assert_eq!(response["total"], 3);
+
+    let (response, code) = index.fetch_documents(json!({ "limit": 1 })).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(ids(&response), [0]);
+    assert_eq!(response["total"], 4);
+}
+
+#[actix_rt::test]
+async fn get_all_documents_filter_and_sort_errors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 0, "color": "red" }]), Some("id"))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .get_all_documents(GetAllDocumentsOptions {
+            filter: Some("color = red"),
+            ..Default::default()
+        })
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_filter");
+
+    let (response, code) = index
+        .fetch_documents(json!({ "sort": ["color:asc"] }))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_sort");
+}
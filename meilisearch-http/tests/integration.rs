@@ -9,6 +9,7 @@ mod settings;
 mod snapshot;
 mod stats;
 mod tasks;
+mod webhooks;
 
 // Tests are isolated by features in different modules to allow better readability, test
 // targetability, and improved incremental compilation times.
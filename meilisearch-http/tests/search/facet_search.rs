@@ -0,0 +1,127 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::index::Index;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "name": "iPhone", "brand": "Apple", "color": "red" },
+        { "id": 2, "name": "MacBook", "brand": "Apple", "color": "grey" },
+        { "id": 3, "name": "Galaxy", "brand": "Samsung", "color": "red" },
+        { "id": 4, "name": "Walkman", "brand": "Sony", "color": "black" },
+        { "id": 5, "name": "Xperia", "brand": "Sony", "color": "red" },
+    ])
+});
+
+async fn index_with_documents(server: &Server) -> Index<'_> {
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["brand", "color"] }))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+    index
+}
+
+/// The facet hits as `(lowercased value, count)` pairs.
+fn facet_hits(response: &Value) -> Vec<(String, u64)> {
+    response["facetHits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| {
+            (
+                hit["value"].as_str().unwrap().to_lowercase(),
+                hit["count"].as_u64().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[actix_rt::test]
+async fn simple_facet_search() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "brand", "facetQuery": "s"}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["facetQuery"], "s");
+    assert_eq!(
+        facet_hits(&response),
+        vec![("samsung".to_string(), 1), ("sony".to_string(), 2)]
+    );
+
+    let (response, code) = index.facet_search(json!({"facetName": "brand"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(facet_hits(&response).len(), 3);
+}
+
+#[actix_rt::test]
+async fn facet_search_with_typo() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "brand", "facetQuery": "appke"}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(facet_hits(&response), vec![("apple".to_string(), 2)]);
+}
+
+#[actix_rt::test]
+async fn facet_search_with_query_and_filter() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "brand", "filter": "color = red"}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        facet_hits(&response),
+        vec![
+            ("apple".to_string(), 1),
+            ("samsung".to_string(), 1),
+            ("sony".to_string(), 1)
+        ]
+    );
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "color", "q": "walkman"}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(facet_hits(&response), vec![("black".to_string(), 1)]);
+}
+
+#[actix_rt::test]
+async fn facet_search_on_non_filterable_attribute() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    let (response, code) = index
+        .facet_search(json!({"facetName": "name", "facetQuery": "iph"}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_facet_search_facet_name");
+}
+
+#[actix_rt::test]
+async fn facet_search_returns_all_the_values() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "filterableAttributes": ["model"] }))
+        .await;
+    let documents: Vec<_> = (0..150)
+        .map(|id| json!({ "id": id, "model": format!("model {id}") }))
+        .collect();
+    index.add_documents(json!(documents), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.facet_search(json!({"facetName": "model"})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(facet_hits(&response).len(), 150);
+}
@@ -0,0 +1,161 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        {
+            "id": 1,
+            "name": "Taco Truck",
+            "_geo": { "lat": 45.4777599, "lng": 9.1967508 },
+        },
+        {
+            "id": 2,
+            "name": "La Bella Italia",
+            "_geo": { "lat": 45.4632046, "lng": 9.1719421 },
+        },
+        {
+            "id": 3,
+            "name": "Crêpe Truck",
+            "_geo": { "lat": 48.8566, "lng": 2.3522 },
+        },
+        {
+            "id": 4,
+            "name": "Le Bistrot",
+            "_geo": { "lat": 50.6292, "lng": 3.0573 },
+        },
+    ])
+});
+
+fn hits_ids(response: &Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn geo_bounding_box() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["_geo", "name"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    // around Milan
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([45.5, 9.3], [45.4, 9.1])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let mut ids = hits_ids(&response);
+                ids.sort_unstable();
+                assert_eq!(ids, [1, 2]);
+                assert_eq!(response["estimatedTotalHits"], 2);
+            },
+        )
+        .await;
+
+    // the bounding box can be combined with the rest of the filter
+    index
+        .search(
+            json!({ "filter": "_geoBoundingBox([45.5, 9.3], [45.4, 9.1]) AND name = 'Taco Truck'" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hits_ids(&response), [1]);
+            },
+        )
+        .await;
+
+    // around France
+    index
+        .search(
+            json!({ "filter": ["_geoBoundingBox([51.1, 8.2], [42.3, -4.8])"], "limit": 1 }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert_eq!(response["estimatedTotalHits"], 2);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn geo_polygon() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["_geo"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    // a triangle between Paris, Lille and Strasbourg
+    index
+        .search(
+            json!({ "filter": "_geoPolygon([48.5, 2.0], [51.0, 3.0], [48.6, 7.8])" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let mut ids = hits_ids(&response);
+                ids.sort_unstable();
+                assert_eq!(ids, [3, 4]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn geo_distance_with_geo_radius_filter() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["_geo"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "filter": "_geoRadius(48.8566, 2.3522, 1000)" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hits_ids(&response), [3]);
+                assert_eq!(response["hits"][0]["_geoDistance"], 0);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn geo_shapes_errors() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["name"]}))
+        .await;
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    for filter in [
+        json!("_geoBoundingBox([45.5, 9.3], [45.4, 9.1])"),
+        json!("name = 'Taco Truck' OR _geoBoundingBox([45.5, 9.3], [45.4, 9.1])"),
+        json!([["name = 'Taco Truck'", "_geoPolygon([0, 0], [1, 0], [0, 1])"]]),
+        json!("_geoPolygon([0, 0], [1, 0])"),
+    ] {
+        index
+            .search(json!({ "filter": filter }), |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "invalid_filter");
+            })
+            .await;
+    }
+}
@@ -0,0 +1,117 @@
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::common::index::Index;
+use crate::common::Server;
+
+static DOCUMENTS: Lazy<Value> = Lazy::new(|| {
+    json!([
+        { "id": 1, "title": "Man on the moon" },
+        { "id": 2, "title": "The moon is bright" },
+        { "id": 3, "title": "The sun and the stars" },
+        { "id": 4, "title": "Man" },
+    ])
+});
+
+fn hits_ids(response: &Value) -> Vec<u64> {
+    response["hits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|hit| hit["id"].as_u64().unwrap())
+        .collect()
+}
+
+async fn index_with_documents(server: &Server) -> Index<'_> {
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+    index
+}
+
+#[actix_rt::test]
+async fn matching_strategy_last() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    index
+        .search(json!({"q": "the man moon"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let ids = hits_ids(&response);
+            assert_eq!(ids[0], 1);
+            // "moon", then "man" are dropped, only "the" is left
+            assert!(ids.contains(&3), "{}", response);
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "the man moon", "matchingStrategy": "last"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert!(hits_ids(&response).contains(&3), "{}", response);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_all() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    index
+        .search(
+            json!({"q": "the man moon", "matchingStrategy": "all"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hits_ids(&response), [1]);
+                assert_eq!(response["estimatedTotalHits"], 1);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_frequency() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    // "the" is in the most documents, it is dropped first, then "man"
+    index
+        .search(
+            json!({"q": "the man moon", "matchingStrategy": "frequency"}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hits_ids(&response), [1, 2]);
+                assert_eq!(response["estimatedTotalHits"], 2);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "the man moon", "matchingStrategy": "frequency", "offset": 1}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(hits_ids(&response), [2]);
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn matching_strategy_invalid() {
+    let server = Server::new().await;
+    let index = index_with_documents(&server).await;
+
+    index
+        .search(
+            json!({"q": "the man moon", "matchingStrategy": "any"}),
+            |response, code| {
+                assert_eq!(code, 400, "{}", response);
+                assert_eq!(response["code"], "bad_request");
+            },
+        )
+        .await;
+}
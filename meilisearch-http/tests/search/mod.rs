@@ -2,7 +2,12 @@
 // should be tested in its own module to isolate tests and keep the tests readable.
 
 mod errors;
+mod facet_search;
 mod formatted;
+mod geo;
+mod matching_strategy;
+mod multi;
+mod pagination;
 
 use crate::common::Server;
 use once_cell::sync::Lazy;
@@ -605,3 +610,283 @@ async fn search_is_hard_limited() {
         )
         .await;
 }
+
+#[actix_rt::test]
+async fn search_is_limited_by_max_total_hits() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents: Vec<_> = (0..2000)
+        .map(|i| json!({ "id": i, "text": "I am unique!" }))
+        .collect();
+    index.add_documents(documents.into(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 1500 } }))
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({
+                "q": "unique",
+                "limit": 2000,
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1500);
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({
+                "offset": 1400,
+                "limit": 400,
+            }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 100);
+            },
+        )
+        .await;
+
+    index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 10 } }))
+        .await;
+    index.wait_task(2).await;
+
+    index
+        .search(json!({ "limit": 20 }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 10);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_facet_distribution_follows_faceting_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = json!([
+        { "id": 1, "color": "blue" },
+        { "id": 2, "color": "red" },
+        { "id": 3, "color": "red" },
+        { "id": 4, "color": "green" },
+        { "id": 5, "color": "red" },
+        { "id": 6, "color": "green" },
+    ]);
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({
+            "filterableAttributes": ["color"],
+            "faceting": { "maxValuesPerFacet": 2 },
+        }))
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({ "facets": ["color"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(
+                response["facetDistribution"]["color"],
+                json!({ "blue": 1, "green": 2 })
+            );
+        })
+        .await;
+
+    index
+        .update_settings(json!({
+            "faceting": {
+                "maxValuesPerFacet": 10,
+                "sortFacetValuesBy": { "color": "count" },
+            },
+        }))
+        .await;
+    index.wait_task(2).await;
+
+    index
+        .search(json!({ "facets": ["color"] }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            let colors: Vec<_> = response["facetDistribution"]["color"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect();
+            assert_eq!(colors, ["red", "green", "blue"]);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_facet_stats() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    index
+        .update_settings(json!({"filterableAttributes": ["father", "doggos.age"]}))
+        .await;
+
+    let documents = NESTED_DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(1).await;
+
+    index
+        .search(
+            json!({ "facets": ["father", "doggos.age"] }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "doggos.age": { "min": 2.0, "max": 8.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({ "facets": ["doggos.age"], "filter": "father = jean" }),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(
+                    response["facetStats"],
+                    json!({ "doggos.age": { "min": 2.0, "max": 4.0 } })
+                );
+            },
+        )
+        .await;
+
+    index
+        .search(json!({ "q": "jean" }), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response.get("facetStats").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_cutoff_ms() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "gless"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert!(response.get("degraded").is_none());
+        })
+        .await;
+
+    // Without any time left, the typos are ignored.
+    index
+        .search(
+            json!({"q": "gless", "searchCutoffMs": 0}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 0);
+                assert_eq!(response["degraded"], json!(true));
+                assert!(response["processingTimeMs"].is_u64());
+            },
+        )
+        .await;
+
+    index.update_settings(json!({ "searchCutoffMs": 0 })).await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({"q": "glass"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert_eq!(response["degraded"], json!(true));
+        })
+        .await;
+
+    // The search that misses the deadline is replaced by a search without typos.
+    index
+        .search(
+            json!({"q": "gless", "searchCutoffMs": 1}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let hits = response["hits"].as_array().unwrap().len();
+                if response["degraded"] == json!(true) {
+                    assert_eq!(hits, 0, "{}", response);
+                } else {
+                    assert_eq!(hits, 1, "{}", response);
+                }
+            },
+        )
+        .await;
+
+    // The query overrides the setting of the index.
+    index
+        .search(
+            json!({"q": "gless", "searchCutoffMs": 10000}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+                assert!(response.get("degraded").is_none());
+            },
+        )
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_ranking_score() {
+    let server = Server::new().await;
+    let index = server.index("test");
+
+    let documents = DOCUMENTS.clone();
+    index.add_documents(documents, None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"q": "glass"}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert!(response["hits"][0].get("_rankingScore").is_none());
+            assert!(response["hits"][0].get("_rankingScoreDetails").is_none());
+        })
+        .await;
+
+    index
+        .search(
+            json!({"q": "glass", "showRankingScore": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                assert_eq!(response["hits"][0]["_rankingScore"], 1.0);
+                assert!(response["hits"][0].get("_rankingScoreDetails").is_none());
+            },
+        )
+        .await;
+
+    index
+        .search(
+            json!({"q": "glass", "showRankingScoreDetails": true}),
+            |response, code| {
+                assert_eq!(code, 200, "{}", response);
+                let details = &response["hits"][0]["_rankingScoreDetails"];
+                assert_eq!(
+                    details["words"],
+                    json!({"order": 0, "matchingWords": 1, "maxMatchingWords": 1, "score": 1.0})
+                );
+                assert_eq!(details["typo"]["typoCount"], 0);
+                assert_eq!(details["attribute"]["bestAttribute"], "title");
+                assert_eq!(
+                    details["exactness"],
+                    json!({"order": 4, "matchType": "exactMatch", "score": 1.0})
+                );
+                assert!(response["hits"][0].get("_rankingScore").is_none());
+            },
+        )
+        .await;
+}
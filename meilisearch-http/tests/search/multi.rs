@@ -0,0 +1,390 @@
+use serde_json::json;
+
+use super::{DOCUMENTS, NESTED_DOCUMENTS};
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_empty_list() {
+    let server = Server::new().await;
+
+    let (response, code) = server.multi_search(json!({"queries": []})).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response, json!({"results": []}));
+}
+
+#[actix_rt::test]
+async fn simple_multi_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "nested", "q": "pesti"},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["hits"].as_array().unwrap().len(), 1);
+    assert_eq!(results[0]["hits"][0]["title"], "Glass");
+    assert_eq!(results[1]["indexUid"], "nested");
+    assert_eq!(results[1]["hits"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn multi_search_same_index_twice() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "test", "q": "captain", "limit": 0},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"][0]["hits"][0]["title"], "Glass");
+    assert_eq!(response["results"][1]["hits"], json!([]));
+    assert_eq!(response["results"][1]["limit"], 0);
+}
+
+#[actix_rt::test]
+async fn multi_search_missing_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "missing", "q": "glass"},
+        ]}))
+        .await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "index_not_found");
+}
+
+#[actix_rt::test]
+async fn multi_search_missing_index_uid() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [{"q": "glass"}]}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn federated_search() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "nested", "q": "pesti"},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], 3);
+    assert_eq!(response["limit"], 20);
+    assert_eq!(response["offset"], 0);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0]["title"], "Glass");
+    assert_eq!(
+        hits[0]["_federation"],
+        json!({"indexUid": "test", "queriesPosition": 0, "weightedRankingScore": 1.0})
+    );
+    // The hits of a query keep their order, and `cattos` isn't the first searchable attribute.
+    let score =
+        |hit: &serde_json::Value| hit["_federation"]["weightedRankingScore"].as_f64().unwrap();
+    assert_eq!(hits[1]["_federation"]["indexUid"], "nested");
+    assert_eq!(hits[1]["cattos"], "pesti");
+    assert_eq!(hits[2]["_federation"]["indexUid"], "nested");
+    assert!(score(&hits[1]) < 1.0, "{}", response);
+    assert!(score(&hits[2]) < score(&hits[1]), "{}", response);
+}
+
+#[actix_rt::test]
+async fn federated_search_keeps_the_order_of_each_index() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let queries = json!([
+        {"indexUid": "test", "q": "the"},
+        {"indexUid": "nested", "q": "jean"},
+    ]);
+    let (response, code) = server.multi_search(json!({"queries": queries})).await;
+    assert_eq!(code, 200, "{}", response);
+    let (federated, code) = server
+        .multi_search(json!({"federation": {}, "queries": queries}))
+        .await;
+    assert_eq!(code, 200, "{}", federated);
+
+    for (position, result) in response["results"].as_array().unwrap().iter().enumerate() {
+        let expected: Vec<_> = result["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|hit| hit["id"].clone())
+            .collect();
+        let merged: Vec<_> = federated["hits"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|hit| hit["_federation"]["queriesPosition"] == position)
+            .map(|hit| hit["id"].clone())
+            .collect();
+        assert_eq!(merged, expected, "{}", federated);
+    }
+}
+
+#[actix_rt::test]
+async fn federated_search_with_weights() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "test", "q": "captain"},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    // Both queries run on the same index, their estimations are not summed.
+    assert_eq!(response["estimatedTotalHits"], 1);
+
+    // "Glass" is an exact match, "Captain Marvel" only starts with the query.
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0]["title"], "Glass");
+    assert_eq!(hits[0]["_federation"]["weightedRankingScore"], 1.0);
+    assert_eq!(hits[1]["title"], "Captain Marvel");
+    assert!(
+        hits[1]["_federation"]["weightedRankingScore"]
+            .as_f64()
+            .unwrap()
+            < 1.0
+    );
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "test", "q": "captain", "federationOptions": {"weight": 2.0}},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0]["title"], "Captain Marvel");
+    assert!(
+        hits[0]["_federation"]["weightedRankingScore"]
+            .as_f64()
+            .unwrap()
+            > 1.0
+    );
+    assert_eq!(hits[1]["title"], "Glass");
+    assert_eq!(hits[1]["_federation"]["weightedRankingScore"], 1.0);
+}
+
+#[actix_rt::test]
+async fn federated_search_pagination() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let index = server.index("nested");
+    index.add_documents(NESTED_DOCUMENTS.clone(), None).await;
+    index.wait_task(1).await;
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {"limit": 1, "offset": 1}, "queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "nested", "q": "pesti"},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["estimatedTotalHits"], 3);
+    assert_eq!(response["limit"], 1);
+    assert_eq!(response["offset"], 1);
+
+    let hits = response["hits"].as_array().unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["_federation"]["indexUid"], "nested");
+    assert_eq!(hits[0]["cattos"], "pesti");
+}
+
+#[actix_rt::test]
+async fn federated_search_negative_weight() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "glass", "federationOptions": {"weight": -1.0}},
+        ]}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn federated_search_zero_weight() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .multi_search(json!({"federation": {}, "queries": [
+            {"indexUid": "test", "q": "glass"},
+            {"indexUid": "test", "q": "glass", "federationOptions": {"weight": 0.0}},
+        ]}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+    assert_eq!(
+        response["message"],
+        "The federated search weight of the query at position 1 must be a positive number, found `0`."
+    );
+}
+
+#[actix_rt::test]
+async fn multi_search_unknown_field() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "test", "q": "glass", "unknownField": true},
+        ]}))
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert!(
+        response["message"]
+            .as_str()
+            .unwrap()
+            .contains("unknown field `unknownField`"),
+        "{}",
+        response
+    );
+}
+
+#[actix_rt::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn multi_search_tenant_token_search_rules() {
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use time::format_description::well_known::Rfc3339;
+    use time::{Duration, OffsetDateTime};
+
+    let documents = json!([
+        { "id": 1, "title": "Shazam!", "color": "green" },
+        { "id": 2, "title": "Captain Marvel", "color": "blue" },
+        { "id": 3, "title": "Escape Room", "color": "red" },
+        { "id": 4, "title": "Glass", "color": "blue" },
+    ]);
+
+    let mut server = Server::new_auth().await;
+    server.use_admin_key("MASTER_KEY").await;
+    for (task_id, uid) in ["sales", "products"].iter().enumerate() {
+        let index = server.index(uid);
+        index.add_documents(documents.clone(), None).await;
+        index.wait_task(task_id as u64 * 2).await;
+        index
+            .update_settings(json!({"filterableAttributes": ["color"]}))
+            .await;
+        index.wait_task(task_id as u64 * 2 + 1).await;
+    }
+
+    let (response, code) = server
+        .add_api_key(json!({
+            "indexes": ["*"],
+            "actions": ["search"],
+            "expiresAt": (OffsetDateTime::now_utc() + Duration::days(1)).format(&Rfc3339).unwrap(),
+        }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    let key = response["key"].as_str().unwrap();
+    let uid = response["uid"].as_str().unwrap();
+
+    // Each index has its own search rules, and the `other` index can't be searched.
+    let token = encode(
+        &Header::default(),
+        &json!({
+            "apiKeyUid": uid,
+            "searchRules": {
+                "sales": {"filter": "color = blue"},
+                "products": {},
+            },
+            "exp": (OffsetDateTime::now_utc() + Duration::hours(1)).unix_timestamp(),
+        }),
+        &EncodingKey::from_secret(key.as_bytes()),
+    )
+    .unwrap();
+    server.use_api_key(&token);
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "sales"},
+            {"indexUid": "products"},
+            {"indexUid": "sales", "filter": "color = green"},
+        ]}))
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(
+        results[0]["hits"].as_array().unwrap().len(),
+        2,
+        "{}",
+        response
+    );
+    assert_eq!(
+        results[1]["hits"].as_array().unwrap().len(),
+        4,
+        "{}",
+        response
+    );
+    // The filter of the query is added to the one of the search rules.
+    assert_eq!(results[2]["hits"], json!([]), "{}", response);
+
+    let (response, code) = server
+        .multi_search(json!({"queries": [
+            {"indexUid": "sales"},
+            {"indexUid": "other"},
+        ]}))
+        .await;
+    assert_eq!(code, 403, "{}", response);
+    assert_eq!(response["code"], "invalid_api_key", "{}", response);
+}
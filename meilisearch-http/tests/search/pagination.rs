@@ -0,0 +1,128 @@
+use serde_json::json;
+
+use super::DOCUMENTS;
+use crate::common::Server;
+
+#[actix_rt::test]
+async fn search_with_page() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"page": 1, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert_eq!(response["page"], 1);
+            assert_eq!(response["hitsPerPage"], 2);
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 3);
+            assert!(response.get("estimatedTotalHits").is_none());
+            assert!(response.get("limit").is_none());
+            assert!(response.get("offset").is_none());
+        })
+        .await;
+
+    index
+        .search(json!({"page": 3, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert_eq!(response["page"], 3);
+            assert_eq!(response["totalPages"], 3);
+        })
+        .await;
+
+    index
+        .search(json!({"page": 4, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalHits"], 5);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_defaults() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"hitsPerPage": 4}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 4);
+            assert_eq!(response["page"], 1);
+            assert_eq!(response["totalPages"], 2);
+        })
+        .await;
+
+    index
+        .search(json!({"page": 1}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 5);
+            assert_eq!(response["hitsPerPage"], 20);
+            assert_eq!(response["totalPages"], 1);
+        })
+        .await;
+
+    index
+        .search(json!({"page": 0}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalHits"], 5);
+        })
+        .await;
+
+    index
+        .search(json!({"hitsPerPage": 0}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"], json!([]));
+            assert_eq!(response["totalHits"], 5);
+            assert_eq!(response["totalPages"], 0);
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_offset_is_unchanged() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .search(json!({"offset": 1, "limit": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 2);
+            assert_eq!(response["offset"], 1);
+            assert_eq!(response["limit"], 2);
+            assert_eq!(response["estimatedTotalHits"], 5);
+            assert!(response.get("totalHits").is_none());
+            assert!(response.get("totalPages").is_none());
+        })
+        .await;
+}
+
+#[actix_rt::test]
+async fn search_with_page_is_limited_by_max_total_hits() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.add_documents(DOCUMENTS.clone(), None).await;
+    index.wait_task(0).await;
+
+    index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 3 } }))
+        .await;
+    index.wait_task(1).await;
+
+    index
+        .search(json!({"page": 2, "hitsPerPage": 2}), |response, code| {
+            assert_eq!(code, 200, "{}", response);
+            assert_eq!(response["hits"].as_array().unwrap().len(), 1);
+            assert_eq!(response["totalHits"], 3);
+            assert_eq!(response["totalPages"], 2);
+        })
+        .await;
+}
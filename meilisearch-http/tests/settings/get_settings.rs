@@ -43,7 +43,7 @@ async fn get_settings() {
     let (response, code) = index.settings().await;
     assert_eq!(code, 200);
     let settings = response.as_object().unwrap();
-    assert_eq!(settings.keys().len(), 9);
+    assert_eq!(settings.keys().len(), 12);
     assert_eq!(settings["displayedAttributes"], json!(["*"]));
     assert_eq!(settings["searchableAttributes"], json!(["*"]));
     assert_eq!(settings["filterableAttributes"], json!([]));
@@ -61,6 +61,76 @@ async fn get_settings() {
         ])
     );
     assert_eq!(settings["stopWords"], json!([]));
+    assert_eq!(settings["pagination"], json!({"maxTotalHits": 1000}));
+    assert_eq!(
+        settings["faceting"],
+        json!({"maxValuesPerFacet": 100, "sortFacetValuesBy": {"*": "alpha"}})
+    );
+    assert_eq!(settings["searchCutoffMs"], json!(1500));
+}
+
+#[actix_rt::test]
+async fn update_pagination_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({ "pagination": { "maxTotalHits": 20 } }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["pagination"], json!({ "maxTotalHits": 20 }));
+
+    let (response, code) = server
+        .service
+        .delete("/indexes/test/settings/pagination")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["pagination"], json!({ "maxTotalHits": 1000 }));
+}
+
+#[actix_rt::test]
+async fn update_faceting_settings() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .update_settings(json!({
+            "faceting": {
+                "maxValuesPerFacet": 10,
+                "sortFacetValuesBy": { "*": "alpha", "genres": "count" }
+            }
+        }))
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["faceting"],
+        json!({
+            "maxValuesPerFacet": 10,
+            "sortFacetValuesBy": { "*": "alpha", "genres": "count" }
+        })
+    );
+
+    let (response, code) = server
+        .service
+        .delete("/indexes/test/settings/faceting")
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    index.wait_task(1).await;
+
+    let (response, code) = index.settings().await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(
+        response["faceting"],
+        json!({ "maxValuesPerFacet": 100, "sortFacetValuesBy": { "*": "alpha" } })
+    );
 }
 
 #[actix_rt::test]
@@ -189,6 +189,265 @@ async fn list_tasks_status_and_type_filtered() {
     assert_eq!(response["results"].as_array().unwrap().len(), 2);
 }
 
+#[actix_rt::test]
+async fn list_tasks_uids_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index
+        .add_documents(
+            serde_json::from_str(include_str!("../assets/test_set.json")).unwrap(),
+            None,
+        )
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = index.service.get("/tasks?uids=1").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+    assert_eq!(response["results"][0]["uid"], 1);
+
+    let (response, code) = index.service.get("/tasks?uids=0,1,42").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn list_tasks_date_filtered() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .get("/tasks?afterEnqueuedAt=2000-01-01T00:00:00Z&beforeFinishedAt=3000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+
+    let (response, code) = index
+        .service
+        .get("/tasks?afterEnqueuedAt=3000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["results"].as_array().unwrap().is_empty());
+
+    let (response, code) = index
+        .service
+        .get("/tasks?beforeStartedAt=2000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 200, "{}", response);
+    assert!(response["results"].as_array().unwrap().is_empty());
+
+    let (response, code) = index.service.get("/tasks?afterEnqueuedAt=yesterday").await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = index
+        .service
+        .get("/tasks?afterStartedAt=2000-01-02T00:00:00Z&beforeStartedAt=2000-01-01T00:00:00Z")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(
+        response["message"],
+        "The `afterStartedAt` task date filter must be earlier than the `beforeStartedAt` one."
+    );
+}
+
+#[actix_rt::test]
+async fn date_filtered_tasks_reject_unknown_parameters() {
+    let server = Server::new().await;
+
+    for route in ["/tasks", "/tasks/stream"] {
+        let url = format!("{}?afterEnqueuedAt=2000-01-01T00:00:00Z&color=red", route);
+        let (response, code) = server.service.get(url).await;
+        assert_eq!(code, 400, "{}", response);
+        assert_eq!(response["code"], "bad_request");
+    }
+
+    let (response, code) = server
+        .service
+        .delete("/tasks?afterEnqueuedAt=2000-01-01T00:00:00Z&color=red")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = server
+        .service
+        .post(
+            "/tasks/cancel?afterEnqueuedAt=2000-01-01T00:00:00Z&color=red",
+            json!(null),
+        )
+        .await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn stream_tasks_bad_filter() {
+    let server = Server::new().await;
+
+    let (response, code) = server.service.get("/tasks/stream?status=finished").await;
+    assert_eq!(code, 400, "{}", response);
+
+    let (response, code) = server.service.get("/tasks/stream?limit=2").await;
+    assert_eq!(code, 400, "{}", response);
+}
+
+#[actix_rt::test]
+async fn stream_tasks_events() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let stream = index
+        .service
+        .get_event_stream("/tasks/stream?indexUid=test", None, 2);
+    let addition = async {
+        index
+            .add_documents(json!([{ "id": 1, "content": "foobar" }]), None)
+            .await;
+    };
+    let ((events, code), ()) = futures::join!(stream, addition);
+
+    assert_eq!(code, 200);
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert!(event_id(&events[0]).ends_with("-1"), "{}", events[0]);
+    assert!(events[0].contains("\nevent: processing\n"), "{}", events[0]);
+    assert!(event_id(&events[1]).ends_with("-1"), "{}", events[1]);
+    assert!(events[1].contains("\nevent: succeeded\n"), "{}", events[1]);
+    assert!(event_id(&events[0]) < event_id(&events[1]));
+
+    let data = events[1]
+        .lines()
+        .find_map(|line| line.strip_prefix("data: "));
+    let task: serde_json::Value = serde_json::from_str(data.unwrap()).unwrap();
+    assert_eq!(task["uid"], 1);
+    assert_eq!(task["status"], "succeeded");
+}
+
+#[actix_rt::test]
+async fn stream_tasks_replay() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index
+        .add_documents(json!([{ "id": 1, "content": "foobar" }]), None)
+        .await;
+    index.wait_task(1).await;
+    server.index("other").create(None).await;
+    server.index("other").wait_task(2).await;
+
+    // The tasks changed after the last event are replayed in the order of the changes.
+    let (events, code) = index
+        .service
+        .get_event_stream("/tasks/stream?indexUid=test", Some("0-0"), 2)
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert!(event_id(&events[0]).ends_with("-0"), "{}", events[0]);
+    assert!(event_id(&events[1]).ends_with("-1"), "{}", events[1]);
+
+    let (events, code) = index
+        .service
+        .get_event_stream("/tasks/stream?indexUid=test", Some(event_id(&events[0])), 1)
+        .await;
+    assert_eq!(code, 200);
+    assert_eq!(events.len(), 1, "{:?}", events);
+    assert!(event_id(&events[0]).ends_with("-1"), "{}", events[0]);
+    assert!(events[0].contains("\nevent: succeeded\n"), "{}", events[0]);
+
+    let (events, code) = index
+        .service
+        .get_event_stream("/tasks/stream", Some(event_id(&events[0])), 1)
+        .await;
+    assert_eq!(code, 200);
+    assert!(event_id(&events[0]).ends_with("-2"), "{}", events[0]);
+    assert!(events[0].contains("\nevent: succeeded\n"), "{}", events[0]);
+}
+
+/// Returns the id of a server-sent event.
+fn event_id(event: &str) -> &str {
+    event
+        .lines()
+        .find_map(|line| line.strip_prefix("id: "))
+        .unwrap_or_default()
+}
+
+#[actix_rt::test]
+async fn cancel_tasks_without_filters() {
+    let server = Server::new().await;
+    let (response, code) = server.service.post("/tasks/cancel", json!(null)).await;
+
+    let expected_response = json!({
+        "message": "Task cancelation requires at least one of the following filters: `type`, `status`, `indexUid`, `uids`, `beforeEnqueuedAt`, `afterEnqueuedAt`, `beforeStartedAt`, `afterStartedAt`, `beforeFinishedAt`, `afterFinishedAt`",
+        "code": "missing_task_filters",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#missing_task_filters"
+    });
+
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 400);
+}
+
+#[actix_rt::test]
+async fn cancel_finished_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .post("/tasks/cancel?indexUid=test", json!(null))
+        .await;
+    assert_eq!(code, 200, "{:?}", response);
+    assert_eq!(
+        response,
+        json!({ "canceled": [], "processing": [], "notCancelable": [] })
+    );
+
+    let (response, code) = index.filtered_tasks(&[], &["canceled"]).await;
+    assert_eq!(code, 200, "{:?}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 0);
+
+    let (response, code) = index.get_task(0).await;
+    assert_eq!(code, 200);
+    assert_eq!(response["status"], "succeeded");
+}
+
+#[actix_rt::test]
+async fn delete_tasks_without_filters() {
+    let server = Server::new().await;
+    let (response, code) = server.service.delete("/tasks").await;
+
+    assert_eq!(code, 400, "{:?}", response);
+    assert_eq!(response["code"], "missing_task_filters");
+}
+
+#[actix_rt::test]
+async fn delete_finished_tasks() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+
+    let (response, code) = index
+        .service
+        .delete("/tasks?indexUid=test&status=succeeded")
+        .await;
+    assert_eq!(code, 200, "{:?}", response);
+    assert_eq!(response, json!({ "deletedTasks": 1 }));
+
+    let (response, code) = index.list_tasks().await;
+    assert_eq!(code, 200);
+    assert!(response["results"].as_array().unwrap().is_empty());
+
+    // The ids of the deleted tasks are never reused.
+    let (response, _) = index.add_documents(json!([{"id": 1}]), None).await;
+    assert_eq!(response["taskUid"], 1);
+}
+
 macro_rules! assert_valid_summarized_task {
     ($response:expr, $task_type:literal, $index:literal) => {{
         assert_eq!($response.as_object().unwrap().len(), 5);
@@ -231,3 +490,86 @@ async fn test_summarized_task_view() {
     let (response, _) = index.delete().await;
     assert_valid_summarized_task!(response, "indexDeletion", "test");
 }
+
+#[actix_rt::test]
+async fn register_task_with_priority() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/indexes?priority=high", json!({ "uid": "test" }))
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    server.index("test").wait_task(0).await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/indexes/test/documents?priority=low",
+            json!([{ "id": 1, "content": "foobar" }]),
+        )
+        .await;
+    assert_eq!(code, 202, "{}", response);
+    let response = server.index("test").wait_task(1).await;
+    assert_eq!(response["status"], "succeeded");
+
+    let (response, code) = server
+        .service
+        .delete("/indexes/test/settings?priority=urgent")
+        .await;
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "bad_request");
+}
+
+#[actix_rt::test]
+async fn get_batches() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(0).await;
+    index
+        .add_documents(json!([{ "id": 1, "content": "foobar" }]), None)
+        .await;
+    index.wait_task(1).await;
+
+    let (response, code) = server.service.get("/batches").await;
+    assert_eq!(code, 200, "{}", response);
+    let results = response["results"].as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["uid"], 1);
+    assert_eq!(results[0]["indexUid"], "test");
+    assert_eq!(results[0]["taskUids"], json!([1]));
+    assert_eq!(
+        results[0]["size"],
+        json!({ "tasks": 1, "receivedDocuments": 1 })
+    );
+    assert!(results[0]["finishedAt"].is_string());
+
+    let (response, code) = server.service.get("/batches?limit=1&from=0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"][0]["uid"], 0);
+    assert_eq!(response["next"], json!(null));
+
+    let (response, code) = server.service.get("/batches/0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["taskUids"], json!([0]));
+
+    let (response, code) = server.service.get("/batches/42").await;
+    assert_eq!(code, 404, "{}", response);
+    assert_eq!(response["code"], "batch_not_found");
+}
+
+#[actix_rt::test]
+async fn finished_task_has_no_progress() {
+    let server = Server::new().await;
+    let index = server.index("test");
+    index
+        .add_documents(json!([{ "id": 1, "content": "foobar" }]), None)
+        .await;
+    index.wait_task(0).await;
+
+    let (response, code) = server.service.get("/tasks/0").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["status"], "succeeded");
+    assert!(response.get("progress").is_none());
+}
@@ -0,0 +1,155 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+use crate::common::Server;
+
+/// Starts a server answering `200 OK` to every request and returning the bodies it received.
+fn webhook_receiver() -> (String, mpsc::UnboundedReceiver<Value>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => break,
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            loop {
+                let mut content_length = 0;
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    break;
+                }
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                    let header = line.trim_end();
+                    if header.is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .unwrap();
+
+                if sender.send(serde_json::from_slice(&body).unwrap()).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    (url, receiver)
+}
+
+#[actix_rt::test]
+async fn create_get_and_delete_webhook() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/webhooks",
+            json!({ "url": "http://localhost:8080/hook", "indexUid": "test" }),
+        )
+        .await;
+    assert_eq!(code, 201, "{}", response);
+    assert_eq!(response["url"], "http://localhost:8080/hook");
+    assert_eq!(response["indexUid"], "test");
+    let uid = response["uid"].as_str().unwrap().to_string();
+
+    let (response, code) = server.service.get(format!("/webhooks/{}", uid)).await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["uid"], uid);
+
+    let (response, code) = server.service.get("/webhooks").await;
+    assert_eq!(code, 200, "{}", response);
+    assert_eq!(response["results"].as_array().unwrap().len(), 1);
+
+    let (_response, code) = server.service.delete(format!("/webhooks/{}", uid)).await;
+    assert_eq!(code, 204);
+
+    let (response, code) = server.service.get(format!("/webhooks/{}", uid)).await;
+    assert_eq!(code, 404);
+    assert_eq!(response["code"], "webhook_not_found");
+}
+
+#[actix_rt::test]
+async fn create_webhook_bad_url() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post("/webhooks", json!({ "url": "ftp://localhost/hook" }))
+        .await;
+
+    let expected_response = json!({
+        "message": "`ftp://localhost/hook` is not a valid webhook url. It must be an absolute `http` or `https` url.",
+        "code": "invalid_webhook_url",
+        "type": "invalid_request",
+        "link": "https://docs.meilisearch.com/errors#invalid_webhook_url"
+    });
+    assert_eq!(response, expected_response);
+    assert_eq!(code, 400);
+}
+
+#[actix_rt::test]
+async fn create_webhook_bad_index_uid() {
+    let server = Server::new().await;
+
+    let (response, code) = server
+        .service
+        .post(
+            "/webhooks",
+            json!({ "url": "http://localhost/hook", "indexUid": "bad index" }),
+        )
+        .await;
+
+    assert_eq!(code, 400, "{}", response);
+    assert_eq!(response["code"], "invalid_index_uid");
+}
+
+#[actix_rt::test]
+async fn webhook_called_on_finished_task() {
+    let server = Server::new().await;
+    let (url, mut receiver) = webhook_receiver();
+
+    let (response, code) = server
+        .service
+        .post("/webhooks", json!({ "url": url, "indexUid": "test" }))
+        .await;
+    assert_eq!(code, 201, "{}", response);
+
+    // the tasks of the other indexes are not sent to the webhook.
+    let other = server.index("other");
+    other.create(None).await;
+    other.wait_task(0).await;
+
+    let index = server.index("test");
+    index.create(None).await;
+    index.wait_task(1).await;
+
+    let payload = tokio::time::timeout(Duration::from_secs(10), receiver.recv())
+        .await
+        .expect("the webhook was not called")
+        .unwrap();
+    assert_eq!(payload["uid"], 1);
+    assert_eq!(payload["indexUid"], "test");
+    assert_eq!(payload["status"], "succeeded");
+    assert_eq!(payload["type"], "indexCreation");
+}
@@ -7,7 +7,8 @@ use uuid::Uuid;
 use crate::index::{Settings, Unchecked};
 use crate::tasks::batch::BatchId;
 use crate::tasks::task::{
-    DocumentDeletion, TaskContent as NewTaskContent, TaskEvent as NewTaskEvent, TaskId, TaskResult,
+    DocumentDeletion, TaskContent as NewTaskContent, TaskEvent as NewTaskEvent, TaskId,
+    TaskPriority, TaskResult,
 };
 use crate::IndexUid;
 
@@ -112,13 +113,16 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 index_uid,
                 content_uuid,
                 merge_strategy,
+                merge_patch: false,
                 primary_key,
                 documents_count,
                 allow_index_creation,
+                priority: TaskPriority::Normal,
             },
             TaskContent::DocumentDeletion(deletion) => NewTaskContent::DocumentDeletion {
                 index_uid,
                 deletion,
+                priority: TaskPriority::Normal,
             },
             TaskContent::SettingsUpdate {
                 settings,
@@ -129,15 +133,21 @@ impl From<(IndexUid, TaskContent)> for NewTaskContent {
                 settings,
                 is_deletion,
                 allow_index_creation,
+                priority: TaskPriority::Normal,
+            },
+            TaskContent::IndexDeletion => NewTaskContent::IndexDeletion {
+                index_uid,
+                priority: TaskPriority::Normal,
             },
-            TaskContent::IndexDeletion => NewTaskContent::IndexDeletion { index_uid },
             TaskContent::IndexCreation { primary_key } => NewTaskContent::IndexCreation {
                 index_uid,
                 primary_key,
+                priority: TaskPriority::Normal,
             },
             TaskContent::IndexUpdate { primary_key } => NewTaskContent::IndexUpdate {
                 index_uid,
                 primary_key,
+                priority: TaskPriority::Normal,
             },
             TaskContent::Dump { uid } => NewTaskContent::Dump { uid },
         }
@@ -10,7 +10,7 @@ use milli::update::{IndexDocumentsConfig, IndexerConfig};
 use serde::{Deserialize, Serialize};
 
 use crate::document_formats::read_ndjson;
-use crate::index::updates::apply_settings_to_builder;
+use crate::index::updates::{apply_meilisearch_settings, apply_settings_to_builder};
 
 use super::error::Result;
 use super::{index::Index, Settings, Unchecked};
@@ -120,6 +120,8 @@ impl Index {
 
         builder.execute(|_| ())?;
 
+        apply_meilisearch_settings(&index, &mut txn, &settings)?;
+
         let document_file_path = src.as_ref().join(DATA_FILE_NAME);
         let reader = BufReader::new(File::open(&document_file_path)?);
 
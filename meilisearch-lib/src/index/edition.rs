@@ -0,0 +1,989 @@
+//! The document edition functions, a small expression language to edit documents in place.
+//!
+//! A function is a list of assignments to the fields of the document, separated by `;`:
+//!
+//! ```text
+//! doc.stock = doc.stock - 1; doc.title = upper(trim(doc.title)) + " (" + doc.year + ")"
+//! ```
+//!
+//! The fields are accessed with `doc.field.nested` or `doc["field with spaces"]`, and a missing
+//! field is `null`. The expressions support the JSON literals, the arithmetic operators
+//! (`+ - * / %`), the comparisons (`== != < <= > >=`), the boolean operators (`&& || !`), the
+//! string concatenation with `+`, and the functions `upper`, `lower`, `trim`, `len`,
+//! `contains`, `starts_with`, `ends_with`, `replace`, `to_string` and `if(condition, then, else)`.
+//!
+//! There are no loops nor variables, and each evaluation is bounded in operations and in the
+//! size of the strings it builds.
+
+use std::cmp::Ordering;
+
+use meilisearch_error::{Code, ErrorCode};
+use serde_json::{Number, Value};
+
+use super::index::Document;
+
+/// The maximum length of a function, in bytes.
+const MAX_FUNCTION_LENGTH: usize = 4096;
+/// The maximum nesting of the expressions of a function.
+const MAX_EXPRESSION_DEPTH: usize = 64;
+/// The maximum number of operations evaluated on a document.
+const MAX_OPERATIONS: usize = 1000;
+/// The maximum length, in bytes, of the strings built by a function.
+const MAX_STRING_LENGTH: usize = 64 * 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum EditionFunctionError {
+    #[error("Invalid document edition function: {message} at position {position}.")]
+    Syntax { position: usize, message: String },
+    #[error("The document edition function failed on the document `{document_id}`: {message}.")]
+    Evaluation {
+        document_id: String,
+        message: String,
+    },
+}
+
+impl ErrorCode for EditionFunctionError {
+    fn error_code(&self) -> Code {
+        Code::InvalidDocumentEditionFunction
+    }
+}
+
+/// A parsed document edition function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditionFunction {
+    assignments: Vec<Assignment>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Assignment {
+    field: Vec<String>,
+    value: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Field(Vec<String>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Or => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Upper,
+    Lower,
+    Trim,
+    Len,
+    Contains,
+    StartsWith,
+    EndsWith,
+    Replace,
+    ToString,
+    If,
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "upper" => Some(Function::Upper),
+            "lower" => Some(Function::Lower),
+            "trim" => Some(Function::Trim),
+            "len" => Some(Function::Len),
+            "contains" => Some(Function::Contains),
+            "starts_with" => Some(Function::StartsWith),
+            "ends_with" => Some(Function::EndsWith),
+            "replace" => Some(Function::Replace),
+            "to_string" => Some(Function::ToString),
+            "if" => Some(Function::If),
+            _ => None,
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Function::Upper
+            | Function::Lower
+            | Function::Trim
+            | Function::Len
+            | Function::ToString => 1,
+            Function::Contains | Function::StartsWith | Function::EndsWith => 2,
+            Function::Replace | Function::If => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Number),
+    String(String),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", ".", ",", ";", "(", ")", "[", "]", "=", "<", ">", "+", "-",
+    "*", "/", "%", "!",
+];
+
+fn syntax_error(position: usize, message: impl Into<String>) -> EditionFunctionError {
+    EditionFunctionError::Syntax {
+        position,
+        message: message.into(),
+    }
+}
+
+/// Splits the function into tokens, with their position in bytes.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, EditionFunctionError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut end = position;
+            let mut is_float = false;
+            while let Some(&(i, c)) = chars.peek() {
+                let is_decimal_point = c == '.'
+                    && !is_float
+                    && source[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+                if !c.is_ascii_digit() && !is_decimal_point {
+                    break;
+                }
+                is_float |= is_decimal_point;
+                end = i + 1;
+                chars.next();
+            }
+            let literal = &source[position..end];
+            let number = if is_float {
+                literal.parse::<f64>().ok().and_then(Number::from_f64)
+            } else {
+                literal.parse::<i64>().ok().map(Number::from)
+            };
+            match number {
+                Some(number) => tokens.push((position, Token::Number(number))),
+                None => {
+                    return Err(syntax_error(
+                        position,
+                        format!("invalid number `{}`", literal),
+                    ))
+                }
+            }
+        } else if c == '"' || c == '\'' {
+            chars.next();
+            let mut string = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, end)) if end == c => break,
+                    Some((i, '\\')) => match chars.next() {
+                        Some((_, 'n')) => string.push('\n'),
+                        Some((_, 't')) => string.push('\t'),
+                        Some((_, escaped @ ('\\' | '"' | '\''))) => string.push(escaped),
+                        _ => return Err(syntax_error(i, "invalid escape sequence")),
+                    },
+                    Some((_, c)) => string.push(c),
+                    None => return Err(syntax_error(position, "unterminated string")),
+                }
+            }
+            tokens.push((position, Token::String(string)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut ident = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if !c.is_alphanumeric() && c != '_' {
+                    break;
+                }
+                ident.push(c);
+                chars.next();
+            }
+            tokens.push((position, Token::Ident(ident)));
+        } else {
+            let rest = &source[position..];
+            match SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+                Some(symbol) => {
+                    for _ in 0..symbol.len() {
+                        chars.next();
+                    }
+                    tokens.push((position, Token::Symbol(symbol)));
+                }
+                None => {
+                    return Err(syntax_error(
+                        position,
+                        format!("unexpected character `{}`", c),
+                    ))
+                }
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// The length of the function, which is the position of the errors at the end.
+    end: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.end, |(position, _)| *position)
+    }
+
+    fn error(&self, message: impl Into<String>) -> EditionFunctionError {
+        syntax_error(self.position(), message)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.next).map(|(_, token)| token.clone());
+        self.next += 1;
+        token
+    }
+
+    /// Consumes the next token if it is `symbol`.
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), EditionFunctionError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", symbol)))
+        }
+    }
+
+    /// Goes one level deeper in the expression, which is limited to keep the parsing and the
+    /// evaluation of the function from overflowing the stack.
+    fn enter(&mut self) -> Result<(), EditionFunctionError> {
+        self.depth += 1;
+        if self.depth > MAX_EXPRESSION_DEPTH {
+            return Err(self.error(format!(
+                "the expression is nested more than {} levels deep",
+                MAX_EXPRESSION_DEPTH
+            )));
+        }
+        Ok(())
+    }
+
+    fn parse_function(&mut self) -> Result<EditionFunction, EditionFunctionError> {
+        let mut assignments = Vec::new();
+        while self.peek().is_some() {
+            assignments.push(self.parse_assignment()?);
+            if self.peek().is_some() {
+                self.expect(";")?;
+            }
+        }
+
+        if assignments.is_empty() {
+            return Err(self.error("the function is empty"));
+        }
+
+        Ok(EditionFunction { assignments })
+    }
+
+    fn parse_assignment(&mut self) -> Result<Assignment, EditionFunctionError> {
+        match self.bump() {
+            Some(Token::Ident(ident)) if ident == "doc" => (),
+            _ => {
+                self.next -= 1;
+                return Err(self.error("expected an assignment to a field of `doc`"));
+            }
+        }
+        let field = self.parse_field()?;
+        self.expect("=")?;
+        let value = self.parse_expr()?;
+
+        Ok(Assignment { field, value })
+    }
+
+    /// Parses the path of a field, after `doc`.
+    fn parse_field(&mut self) -> Result<Vec<String>, EditionFunctionError> {
+        let mut field = Vec::new();
+        loop {
+            if self.eat(".") {
+                match self.bump() {
+                    Some(Token::Ident(name)) => field.push(name),
+                    _ => {
+                        self.next -= 1;
+                        return Err(self.error("expected a field name"));
+                    }
+                }
+            } else if self.eat("[") {
+                match self.bump() {
+                    Some(Token::String(name)) => field.push(name),
+                    _ => {
+                        self.next -= 1;
+                        return Err(self.error("expected a field name string"));
+                    }
+                }
+                self.expect("]")?;
+            } else {
+                break;
+            }
+        }
+
+        if field.is_empty() {
+            return Err(self.error("expected a field of `doc`"));
+        }
+
+        Ok(field)
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, EditionFunctionError> {
+        self.parse_binary(0)
+    }
+
+    /// Parses the binary operations from the given precedence level, the lowest being `||`.
+    fn parse_binary(&mut self, level: usize) -> Result<Expr, EditionFunctionError> {
+        const LEVELS: &[&[(&str, BinaryOp)]] = &[
+            &[("||", BinaryOp::Or)],
+            &[("&&", BinaryOp::And)],
+            &[
+                ("==", BinaryOp::Eq),
+                ("!=", BinaryOp::Ne),
+                ("<=", BinaryOp::Le),
+                (">=", BinaryOp::Ge),
+                ("<", BinaryOp::Lt),
+                (">", BinaryOp::Gt),
+            ],
+            &[("+", BinaryOp::Add), ("-", BinaryOp::Sub)],
+            &[
+                ("*", BinaryOp::Mul),
+                ("/", BinaryOp::Div),
+                ("%", BinaryOp::Rem),
+            ],
+        ];
+
+        let operators = match LEVELS.get(level) {
+            Some(operators) => operators,
+            None => return self.parse_unary(),
+        };
+
+        let depth = self.depth;
+        let mut left = self.parse_binary(level + 1)?;
+        while let Some(&(_, op)) = operators.iter().find(|(symbol, _)| self.eat(symbol)) {
+            // A chain of operations is as deep as its length, since it is evaluated recursively.
+            self.depth += 1;
+            if self.depth > MAX_EXPRESSION_DEPTH {
+                return Err(self.error(format!(
+                    "the expression is more than {} operations deep, \
+                     the long chains of operations can be split with parentheses",
+                    MAX_EXPRESSION_DEPTH
+                )));
+            }
+            let right = self.parse_binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, EditionFunctionError> {
+        self.enter()?;
+        let expr = if self.eat("-") {
+            Expr::Unary(UnaryOp::Neg, Box::new(self.parse_unary()?))
+        } else if self.eat("!") {
+            Expr::Unary(UnaryOp::Not, Box::new(self.parse_unary()?))
+        } else {
+            self.parse_primary()?
+        };
+        self.depth -= 1;
+
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, EditionFunctionError> {
+        let position = self.position();
+        match self.bump() {
+            Some(Token::Number(number)) => Ok(Expr::Literal(Value::Number(number))),
+            Some(Token::String(string)) => Ok(Expr::Literal(Value::String(string))),
+            Some(Token::Symbol("(")) => {
+                let expr = self.parse_expr()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Ident(ident)) => match ident.as_str() {
+                "true" => Ok(Expr::Literal(Value::Bool(true))),
+                "false" => Ok(Expr::Literal(Value::Bool(false))),
+                "null" => Ok(Expr::Literal(Value::Null)),
+                "doc" => Ok(Expr::Field(self.parse_field()?)),
+                name => {
+                    if !matches!(self.peek(), Some(Token::Symbol("("))) {
+                        return Err(syntax_error(
+                            position,
+                            format!("unknown identifier `{}`", name),
+                        ));
+                    }
+                    let function = Function::from_name(name).ok_or_else(|| {
+                        syntax_error(position, format!("unknown function `{}`", name))
+                    })?;
+                    self.expect("(")?;
+                    let mut arguments = Vec::new();
+                    if !self.eat(")") {
+                        loop {
+                            arguments.push(self.parse_expr()?);
+                            if self.eat(")") {
+                                break;
+                            }
+                            self.expect(",")?;
+                        }
+                    }
+                    if arguments.len() != function.arity() {
+                        return Err(syntax_error(
+                            position,
+                            format!(
+                                "`{}` takes {} arguments, but {} were given",
+                                name,
+                                function.arity(),
+                                arguments.len()
+                            ),
+                        ));
+                    }
+                    Ok(Expr::Call(function, arguments))
+                }
+            },
+            _ => Err(syntax_error(position, "expected an expression")),
+        }
+    }
+}
+
+impl EditionFunction {
+    pub fn parse(source: &str) -> Result<Self, EditionFunctionError> {
+        if source.len() > MAX_FUNCTION_LENGTH {
+            return Err(syntax_error(
+                MAX_FUNCTION_LENGTH,
+                format!("the function is longer than {} bytes", MAX_FUNCTION_LENGTH),
+            ));
+        }
+
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            next: 0,
+            end: source.len(),
+            depth: 0,
+        };
+        parser.parse_function()
+    }
+
+    /// Applies the assignments to the document, in order. Returns whether the document was
+    /// modified, or the reason the evaluation failed.
+    pub fn apply(&self, document: &mut Document) -> Result<bool, String> {
+        let mut evaluator = Evaluator { operations: 0 };
+        let mut modified = false;
+        for Assignment { field, value } in &self.assignments {
+            let value = evaluator.evaluate(document, value)?;
+            modified |= set_field(document, field, value)?;
+        }
+
+        Ok(modified)
+    }
+}
+
+fn get_field<'a>(document: &'a Document, field: &[String]) -> Option<&'a Value> {
+    let (first, rest) = field.split_first()?;
+    rest.iter()
+        .try_fold(document.get(first)?, |value, name| value.get(name))
+}
+
+/// Sets the field of the document, creating the missing objects on the way. Returns whether the
+/// field changed.
+fn set_field(document: &mut Document, field: &[String], value: Value) -> Result<bool, String> {
+    let (last, parents) = field.split_last().expect("a field can't be empty");
+
+    let mut object = document;
+    for (i, name) in parents.iter().enumerate() {
+        let parent = object
+            .entry(name.clone())
+            .or_insert_with(|| Value::Object(Default::default()));
+        if parent.is_null() {
+            *parent = Value::Object(Default::default());
+        }
+        object = match parent {
+            Value::Object(parent) => parent,
+            other => {
+                return Err(format!(
+                    "can't assign `{}`, because `{}` is {}",
+                    field.join("."),
+                    field[..=i].join("."),
+                    type_name(other),
+                ))
+            }
+        };
+    }
+
+    let modified = object.get(last) != Some(&value);
+    object.insert(last.clone(), value);
+
+    Ok(modified)
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Returns the value as it is concatenated to a string.
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn string_length_error() -> String {
+    format!(
+        "the function built a string longer than {} bytes",
+        MAX_STRING_LENGTH
+    )
+}
+
+fn string_value(string: String) -> Result<Value, String> {
+    if string.len() > MAX_STRING_LENGTH {
+        Err(string_length_error())
+    } else {
+        Ok(Value::String(string))
+    }
+}
+
+fn number_value(number: f64) -> Result<Value, String> {
+    Number::from_f64(number)
+        .map(Value::Number)
+        .ok_or_else(|| "the result is not a finite number".to_string())
+}
+
+fn as_bool(value: &Value) -> Result<bool, String> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(format!("expected a boolean, found {}", type_name(other))),
+    }
+}
+
+fn as_str(value: &Value) -> Result<&str, String> {
+    match value {
+        Value::String(s) => Ok(s),
+        other => Err(format!("expected a string, found {}", type_name(other))),
+    }
+}
+
+struct Evaluator {
+    operations: usize,
+}
+
+impl Evaluator {
+    fn evaluate(&mut self, document: &Document, expr: &Expr) -> Result<Value, String> {
+        self.operations += 1;
+        if self.operations > MAX_OPERATIONS {
+            return Err(format!(
+                "the function exceeded the limit of {} operations",
+                MAX_OPERATIONS
+            ));
+        }
+
+        match expr {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Field(field) => Ok(get_field(document, field).cloned().unwrap_or(Value::Null)),
+            Expr::Unary(UnaryOp::Neg, expr) => match self.evaluate(document, expr)? {
+                Value::Number(n) => match n.as_i64().and_then(i64::checked_neg) {
+                    Some(n) => Ok(Value::from(n)),
+                    None => number_value(-n.as_f64().unwrap_or_default()),
+                },
+                other => Err(format!("can't negate {}", type_name(&other))),
+            },
+            Expr::Unary(UnaryOp::Not, expr) => {
+                Ok(Value::Bool(!as_bool(&self.evaluate(document, expr)?)?))
+            }
+            Expr::Binary(BinaryOp::And, left, right) => {
+                let result = as_bool(&self.evaluate(document, left)?)?
+                    && as_bool(&self.evaluate(document, right)?)?;
+                Ok(Value::Bool(result))
+            }
+            Expr::Binary(BinaryOp::Or, left, right) => {
+                let result = as_bool(&self.evaluate(document, left)?)?
+                    || as_bool(&self.evaluate(document, right)?)?;
+                Ok(Value::Bool(result))
+            }
+            Expr::Binary(op, left, right) => {
+                let left = self.evaluate(document, left)?;
+                let right = self.evaluate(document, right)?;
+                binary(*op, &left, &right)
+            }
+            Expr::Call(Function::If, arguments) => {
+                let branch = if as_bool(&self.evaluate(document, &arguments[0])?)? {
+                    &arguments[1]
+                } else {
+                    &arguments[2]
+                };
+                self.evaluate(document, branch)
+            }
+            Expr::Call(function, arguments) => {
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(document, argument))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(*function, &arguments)
+            }
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, String> {
+    let type_error = || {
+        format!(
+            "can't apply `{}` to {} and {}",
+            op.symbol(),
+            type_name(left),
+            type_name(right)
+        )
+    };
+
+    match (op, left, right) {
+        (BinaryOp::Eq, left, right) => Ok(Value::Bool(equals(left, right))),
+        (BinaryOp::Ne, left, right) => Ok(Value::Bool(!equals(left, right))),
+        (BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge, left, right) => {
+            let ordering = match (left, right) {
+                (Value::Number(left), Value::Number(right)) => left
+                    .as_f64()
+                    .partial_cmp(&right.as_f64())
+                    .ok_or_else(type_error)?,
+                (Value::String(left), Value::String(right)) => left.cmp(right),
+                _ => return Err(type_error()),
+            };
+            let result = match op {
+                BinaryOp::Lt => ordering == Ordering::Less,
+                BinaryOp::Le => ordering != Ordering::Greater,
+                BinaryOp::Gt => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            };
+            Ok(Value::Bool(result))
+        }
+        (_, Value::Number(left), Value::Number(right)) => arithmetic(op, left, right),
+        (BinaryOp::Add, Value::String(_), _) | (BinaryOp::Add, _, Value::String(_)) => {
+            if display_len(left) + display_len(right) > MAX_STRING_LENGTH {
+                return Err(string_length_error());
+            }
+            string_value(display(left) + &display(right))
+        }
+        _ => Err(type_error()),
+    }
+}
+
+/// Returns the length of the string a value is displayed as, without copying the strings.
+fn display_len(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.len(),
+        other => other.to_string().len(),
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        (left, right) => left == right,
+    }
+}
+
+/// Computes an arithmetic operation on integers when the result is an integer, and on floats
+/// otherwise.
+fn arithmetic(op: BinaryOp, left: &Number, right: &Number) -> Result<Value, String> {
+    if let (Some(left), Some(right)) = (left.as_i64(), right.as_i64()) {
+        let result = match op {
+            BinaryOp::Add => left.checked_add(right),
+            BinaryOp::Sub => left.checked_sub(right),
+            BinaryOp::Mul => left.checked_mul(right),
+            BinaryOp::Div => left
+                .checked_rem(right)
+                .filter(|rem| *rem == 0)
+                .and_then(|_| left.checked_div(right)),
+            _ => left.checked_rem(right),
+        };
+        if let Some(result) = result {
+            return Ok(Value::from(result));
+        }
+    }
+
+    let (left, right) = (
+        left.as_f64().unwrap_or_default(),
+        right.as_f64().unwrap_or_default(),
+    );
+    let result = match op {
+        BinaryOp::Add => left + right,
+        BinaryOp::Sub => left - right,
+        BinaryOp::Mul => left * right,
+        BinaryOp::Div | BinaryOp::Rem if right == 0.0 => return Err("division by zero".to_string()),
+        BinaryOp::Div => left / right,
+        _ => left % right,
+    };
+    number_value(result)
+}
+
+fn call(function: Function, arguments: &[Value]) -> Result<Value, String> {
+    match function {
+        Function::Upper => string_value(as_str(&arguments[0])?.to_uppercase()),
+        Function::Lower => string_value(as_str(&arguments[0])?.to_lowercase()),
+        Function::Trim => string_value(as_str(&arguments[0])?.trim().to_string()),
+        Function::Len => match &arguments[0] {
+            Value::String(s) => Ok(Value::from(s.chars().count())),
+            Value::Array(array) => Ok(Value::from(array.len())),
+            Value::Object(object) => Ok(Value::from(object.len())),
+            other => Err(format!("`len` can't be applied to {}", type_name(other))),
+        },
+        Function::Contains => match &arguments[0] {
+            Value::String(s) => Ok(Value::Bool(s.contains(as_str(&arguments[1])?))),
+            Value::Array(array) => Ok(Value::Bool(
+                array.iter().any(|value| equals(value, &arguments[1])),
+            )),
+            other => Err(format!(
+                "`contains` can't be applied to {}",
+                type_name(other)
+            )),
+        },
+        Function::StartsWith => {
+            let (s, prefix) = (as_str(&arguments[0])?, as_str(&arguments[1])?);
+            Ok(Value::Bool(s.starts_with(prefix)))
+        }
+        Function::EndsWith => {
+            let (s, suffix) = (as_str(&arguments[0])?, as_str(&arguments[1])?);
+            Ok(Value::Bool(s.ends_with(suffix)))
+        }
+        Function::Replace => {
+            let s = as_str(&arguments[0])?;
+            let (from, to) = (as_str(&arguments[1])?, as_str(&arguments[2])?);
+            if from.is_empty() {
+                return Err("the pattern to replace can't be empty".to_string());
+            }
+            // The length of the result is checked before it is built.
+            let matches = s.matches(from).count();
+            if s.len() - matches * from.len() + matches * to.len() > MAX_STRING_LENGTH {
+                return Err(string_length_error());
+            }
+            string_value(s.replace(from, to))
+        }
+        Function::ToString => string_value(display(&arguments[0])),
+        Function::If => unreachable!("`if` is evaluated lazily"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn apply(function: &str, document: Value) -> Result<Value, String> {
+        let function = EditionFunction::parse(function).map_err(|e| e.to_string())?;
+        let mut document = match document {
+            Value::Object(document) => document,
+            _ => panic!("the document must be an object"),
+        };
+        function.apply(&mut document)?;
+        Ok(Value::Object(document))
+    }
+
+    #[test]
+    fn test_assignments() {
+        let document = json!({ "id": 1, "stock": 10, "price": 9.5, "title": "  le petit prince " });
+
+        assert_eq!(
+            apply("doc.stock = doc.stock - 1", document.clone()).unwrap()["stock"],
+            json!(9)
+        );
+        assert_eq!(
+            apply("doc.price = doc.price * 2 + 1", document.clone()).unwrap()["price"],
+            json!(20.0)
+        );
+        assert_eq!(
+            apply("doc.stock = doc.stock / 4", document.clone()).unwrap()["stock"],
+            json!(2.5)
+        );
+        assert_eq!(
+            apply(
+                "doc.title = upper(trim(doc.title)); doc.label = doc.title + ' #' + doc.id",
+                document.clone()
+            )
+            .unwrap()["label"],
+            json!("LE PETIT PRINCE #1")
+        );
+        assert_eq!(
+            apply(
+                "doc.stats.sold = 1; doc['in stock'] = doc.stock > 0;",
+                document.clone()
+            )
+            .unwrap(),
+            json!({
+                "id": 1,
+                "stock": 10,
+                "price": 9.5,
+                "title": "  le petit prince ",
+                "stats": { "sold": 1 },
+                "in stock": true,
+            })
+        );
+        assert_eq!(
+            apply(
+                r#"doc.kind = if(doc.stock >= 10 && !contains(doc.title, "x"), "big", "small")"#,
+                document.clone()
+            )
+            .unwrap()["kind"],
+            json!("big")
+        );
+        assert_eq!(
+            apply(
+                "doc.title = replace(doc.missing, 'a', 'b')",
+                document.clone()
+            )
+            .unwrap_err(),
+            "expected a string, found null"
+        );
+    }
+
+    #[test]
+    fn test_modified() {
+        let function = EditionFunction::parse("doc.stock = doc.stock + 0").unwrap();
+        let mut document = json!({ "id": 1, "stock": 10 }).as_object().unwrap().clone();
+        assert!(!function.apply(&mut document).unwrap());
+
+        let function = EditionFunction::parse("doc.stock = null").unwrap();
+        assert!(function.apply(&mut document).unwrap());
+        assert_eq!(document["stock"], Value::Null);
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        for (function, message) in [
+            ("", "the function is empty at position 0"),
+            ("doc = 1", "expected a field of `doc` at position 4"),
+            ("doc.a + 1", "expected `=` at position 6"),
+            ("doc.a = ", "expected an expression at position 8"),
+            ("doc.a = 1 doc.b = 2", "expected `;` at position 10"),
+            ("doc.a = 'abc", "unterminated string at position 8"),
+            (
+                "doc.a = exec('ls')",
+                "unknown function `exec` at position 8",
+            ),
+            ("doc.a = stock", "unknown identifier `stock` at position 8"),
+            (
+                "doc.a = upper('a', 'b')",
+                "`upper` takes 1 arguments, but 2 were given at position 8",
+            ),
+            ("doc.a = 1 # 2", "unexpected character `#` at position 10"),
+        ] {
+            let error = EditionFunction::parse(function).unwrap_err().to_string();
+            assert_eq!(
+                error,
+                format!("Invalid document edition function: {}.", message),
+                "{}",
+                function
+            );
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let nested = format!("doc.a = {}1{}", "(".repeat(100), ")".repeat(100));
+        assert!(EditionFunction::parse(&nested)
+            .unwrap_err()
+            .to_string()
+            .contains("nested more than 64 levels"));
+
+        let chain = format!("doc.a = {}1", "1 + ".repeat(100));
+        assert!(EditionFunction::parse(&chain)
+            .unwrap_err()
+            .to_string()
+            .contains("more than 64 operations deep"));
+        let split = format!("doc.a = ({0}1) + ({0}1)", "1 + ".repeat(50));
+        assert!(EditionFunction::parse(&split).is_ok());
+
+        let long = format!("doc.a = {}", "1 + ".repeat(MAX_FUNCTION_LENGTH));
+        assert!(EditionFunction::parse(&long).is_err());
+
+        // Each assignment doubles the length of the string.
+        let function = format!("doc.a = 'ab'{}", "; doc.a = doc.a + doc.a".repeat(20));
+        assert_eq!(
+            apply(&function, json!({})).unwrap_err(),
+            "the function built a string longer than 65536 bytes"
+        );
+
+        // Each assignment evaluates 101 operations.
+        let assignment = format!("doc.a = {}1", "1 + ".repeat(50));
+        let function = vec![assignment; 9].join(";");
+        assert!(apply(&function, json!({})).is_ok());
+        let function = format!("{}; {}", function, function);
+        assert_eq!(
+            apply(&function, json!({})).unwrap_err(),
+            "the function exceeded the limit of 1000 operations"
+        );
+
+        assert_eq!(
+            apply("doc.a = 1 / 0", json!({})).unwrap_err(),
+            "division by zero"
+        );
+        assert_eq!(
+            apply("doc.a.b = 1", json!({ "a": 1 })).unwrap_err(),
+            "can't assign `a.b`, because `a` is a number"
+        );
+    }
+}
@@ -3,6 +3,7 @@ use std::error::Error;
 use meilisearch_error::{internal_error, Code, ErrorCode};
 use serde_json::Value;
 
+use super::EditionFunctionError;
 use crate::{error::MilliError, update_file_store};
 
 pub type Result<T> = std::result::Result<T, IndexError>;
@@ -17,6 +18,8 @@ pub enum IndexError {
     Facet(#[from] FacetError),
     #[error("{0}")]
     Milli(#[from] milli::Error),
+    #[error("{0}")]
+    EditionFunction(#[from] EditionFunctionError),
 }
 
 internal_error!(
@@ -25,7 +28,9 @@ internal_error!(
     fst::Error,
     serde_json::Error,
     update_file_store::UpdateFileStoreError,
-    milli::documents::Error
+    milli::documents::Error,
+    csv::Error,
+    grenad::Error<std::convert::Infallible>
 );
 
 impl ErrorCode for IndexError {
@@ -35,6 +40,7 @@ impl ErrorCode for IndexError {
             IndexError::DocumentNotFound(_) => Code::DocumentNotFound,
             IndexError::Facet(e) => e.error_code(),
             IndexError::Milli(e) => MilliError(e).error_code(),
+            IndexError::EditionFunction(e) => e.error_code(),
         }
     }
 }
@@ -43,12 +49,18 @@ impl ErrorCode for IndexError {
 pub enum FacetError {
     #[error("Invalid syntax for the filter parameter: `expected {}, found: {1}`.", .0.join(", "))]
     InvalidExpression(&'static [&'static str], Value),
+    #[error("Attribute `{0}` is not filterable. Available filterable attributes are: `{}`.", .1.join(", "))]
+    AttributeNotFilterable(String, Vec<String>),
+    #[error("Invalid geo filter: {0}")]
+    InvalidGeoShape(String),
 }
 
 impl ErrorCode for FacetError {
     fn error_code(&self) -> Code {
         match self {
             FacetError::InvalidExpression(_, _) => Code::Filter,
+            FacetError::AttributeNotFilterable(_, _) => Code::InvalidFacetSearchFacetName,
+            FacetError::InvalidGeoShape(_) => Code::Filter,
         }
     }
 }
@@ -0,0 +1,203 @@
+use std::collections::HashSet;
+use std::io::Write;
+
+use milli::heed::RoTxn;
+use milli::{obkv_to_json, FieldsIdsMap};
+use roaring::RoaringBitmap;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::error::Result;
+use super::index::{Document, Index};
+
+/// The formats the documents of an index can be exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocumentExportFormat {
+    Ndjson,
+    Csv,
+}
+
+impl Default for DocumentExportFormat {
+    fn default() -> Self {
+        DocumentExportFormat::Ndjson
+    }
+}
+
+impl DocumentExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            DocumentExportFormat::Ndjson => "application/x-ndjson",
+            DocumentExportFormat::Csv => "text/csv",
+        }
+    }
+}
+
+impl Index {
+    /// Writes all the documents matching the filter to `writer`, from a single read transaction.
+    ///
+    /// Nothing is written when the filter is invalid. In CSV, the columns are the
+    /// `attributes_to_retrieve`, or all the top-level fields of the documents.
+    pub fn export_documents<W: Write>(
+        &self,
+        format: DocumentExportFormat,
+        attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<Value>,
+        mut writer: W,
+    ) -> Result<()> {
+        let txn = self.read_txn()?;
+
+        let docids = match filter {
+            Some(filter) => self.filtered_docids(&txn, &filter)?,
+            None => None,
+        };
+
+        let attributes_to_retrieve = attributes_to_retrieve.as_deref();
+        let for_each_document = |f: &mut dyn FnMut(Document) -> Result<()>| {
+            self.for_each_document(&txn, docids.as_ref(), attributes_to_retrieve, f)
+        };
+
+        match format {
+            DocumentExportFormat::Ndjson => for_each_document(&mut |document| {
+                serde_json::to_writer(&mut writer, &document)?;
+                writer.write_all(b"\n")?;
+                Ok(())
+            })?,
+            DocumentExportFormat::Csv => {
+                let header: Vec<String> = match attributes_to_retrieve {
+                    Some(attributes_to_retrieve) => attributes_to_retrieve.to_vec(),
+                    None => top_level_fields(&self.fields_ids_map(&txn)?),
+                };
+
+                let mut csv = csv::Writer::from_writer(&mut writer);
+                csv.write_record(&header)?;
+                for_each_document(&mut |document| {
+                    csv.write_record(header.iter().map(|field| csv_field(&document, field)))?;
+                    Ok(())
+                })?;
+                csv.flush()?;
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Calls `f` on the documents of `docids`, or on all the documents of the index.
+    fn for_each_document(
+        &self,
+        txn: &RoTxn,
+        docids: Option<&RoaringBitmap>,
+        attributes_to_retrieve: Option<&[String]>,
+        f: &mut dyn FnMut(Document) -> Result<()>,
+    ) -> Result<()> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut export = |obkv| {
+            let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+            let document = match attributes_to_retrieve {
+                Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
+                    &document,
+                    attributes_to_retrieve.iter().map(|s| s.as_ref()),
+                ),
+                None => document,
+            };
+            f(document)
+        };
+
+        match docids {
+            Some(docids) => {
+                for docid in docids {
+                    for (_id, obkv) in self.documents(txn, std::iter::once(docid))? {
+                        export(obkv)?;
+                    }
+                }
+            }
+            None => {
+                // Without a filter, the documents are read in a single pass over the database.
+                for entry in self.documents.range(txn, &(..))? {
+                    let (_id, obkv) = entry?;
+                    export(obkv)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the fields of the index that are at the top level of the documents. The index also
+/// knows the fields nested in objects, like `price.eur`, which are in the column of their parent.
+fn top_level_fields(fields_ids_map: &FieldsIdsMap) -> Vec<String> {
+    let names: HashSet<_> = fields_ids_map.iter().map(|(_, name)| name).collect();
+    fields_ids_map
+        .iter()
+        .map(|(_, name)| name)
+        .filter(|name| {
+            !name
+                .match_indices('.')
+                .any(|(i, _)| names.contains(&name[..i]))
+        })
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the CSV representation of the field of the document, which can be nested, like
+/// `price.eur`. The strings are written as is, and the other values in JSON.
+fn csv_field(document: &Document, field: &str) -> String {
+    let mut value = document.get(field);
+    if value.is_none() {
+        let mut parts = field.split('.');
+        value = parts
+            .next()
+            .and_then(|first| document.get(first))
+            .and_then(|value| parts.try_fold(value, |value, part| value.get(part)));
+    }
+
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_top_level_fields() {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        for field in ["id", "price", "price.eur", "dims.width", "price.eur.cents"] {
+            fields_ids_map.insert(field);
+        }
+        assert_eq!(
+            top_level_fields(&fields_ids_map),
+            ["id", "price", "dims.width"]
+        );
+    }
+
+    #[test]
+    fn test_csv_field() {
+        let document = json!({
+            "id": 1,
+            "title": "Le Petit Prince",
+            "price": { "eur": 9.5 },
+            "tags": ["tale", "classic"],
+            "author": null,
+        });
+        let document = document.as_object().unwrap();
+
+        assert_eq!(csv_field(document, "id"), "1");
+        assert_eq!(csv_field(document, "title"), "Le Petit Prince");
+        assert_eq!(csv_field(document, "price.eur"), "9.5");
+        assert_eq!(csv_field(document, "price"), r#"{"eur":9.5}"#);
+        assert_eq!(csv_field(document, "tags"), r#"["tale","classic"]"#);
+        assert_eq!(csv_field(document, "author"), "");
+        assert_eq!(csv_field(document, "isbn"), "");
+    }
+}
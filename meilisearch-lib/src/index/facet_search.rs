@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use milli::heed::types::ByteSlice;
+use milli::heed::RoTxn;
+use milli::tokenizer::{Analyzer, AnalyzerConfig};
+use milli::FieldId;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::error::{FacetError, Result};
+use super::geo::extract_geo_shapes;
+use super::index::Index;
+use super::search::parse_filter;
+use super::typo::{levenshtein, TypoTolerance};
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct FacetSearchQuery {
+    pub facet_name: String,
+    pub facet_query: Option<String>,
+    pub q: Option<String>,
+    pub filter: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetSearchResult {
+    pub facet_hits: Vec<FacetValueHit>,
+    pub facet_query: Option<String>,
+    pub processing_time_ms: u128,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FacetValueHit {
+    pub value: String,
+    pub count: u64,
+}
+
+impl Index {
+    /// Returns the values of a filterable attribute matching the facet query, along with their
+    /// number of occurrences in the documents matching the query and the filter.
+    pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+        let before_search = Instant::now();
+        let rtxn = self.read_txn()?;
+
+        let filterable_fields = self.filterable_fields(&rtxn)?;
+        if !filterable_fields.contains(&query.facet_name) {
+            let mut filterable_fields: Vec<_> = filterable_fields.into_iter().collect();
+            filterable_fields.sort_unstable();
+            return Err(
+                FacetError::AttributeNotFilterable(query.facet_name, filterable_fields).into(),
+            );
+        }
+
+        let (filter, geo_shapes) = match query.filter {
+            Some(ref filter) => extract_geo_shapes(filter)?,
+            None => (None, Vec::new()),
+        };
+
+        let mut search = self.search(&rtxn);
+
+        if let Some(ref q) = query.q {
+            search.query(q);
+        }
+
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+
+        if !geo_shapes.is_empty() {
+            search.candidates(self.geo_shapes_docids(&rtxn, &geo_shapes)?);
+        }
+
+        // We only need the candidates, to count the facet values in them.
+        search.limit(0);
+        let milli::SearchResult { candidates, .. } = search.execute()?;
+
+        let values = match self.fields_ids_map(&rtxn)?.id(&query.facet_name) {
+            Some(field_id) => self.facet_values_count(&rtxn, field_id, &candidates)?,
+            None => BTreeMap::new(),
+        };
+
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        let typo_tolerance = TypoTolerance::new(self, &rtxn)?;
+        let facet_query = query
+            .facet_query
+            .as_deref()
+            .map(|facet_query| normalized_words(&analyzer, facet_query).join(" "))
+            .filter(|facet_query| !facet_query.is_empty());
+
+        let facet_hits = values
+            .into_iter()
+            .filter(|(value, _)| match facet_query {
+                Some(ref facet_query) => facet_value_matches(
+                    &normalized_words(&analyzer, value),
+                    facet_query,
+                    typo_tolerance.allowed_typos(facet_query),
+                ),
+                None => true,
+            })
+            .map(|(value, count)| FacetValueHit { value, count })
+            .collect();
+
+        Ok(FacetSearchResult {
+            facet_hits,
+            facet_query: query.facet_query,
+            processing_time_ms: before_search.elapsed().as_millis(),
+        })
+    }
+}
+
+impl Index {
+    /// Counts the documents of `candidates` containing each value of the facet, unlike the facet
+    /// distribution of the engine which only returns its first values.
+    fn facet_values_count(
+        &self,
+        rtxn: &RoTxn,
+        field_id: FieldId,
+        candidates: &RoaringBitmap,
+    ) -> Result<BTreeMap<String, u64>> {
+        let mut values = BTreeMap::new();
+
+        for result in self.facet_string_values(rtxn, field_id)? {
+            let (value, docids) = result?;
+            let count = (&docids & candidates).len();
+            if count != 0 {
+                *values.entry(value.to_string()).or_default() += count;
+            }
+        }
+
+        for result in self.facet_number_values(rtxn, field_id)? {
+            let (value, docids) = result?;
+            let count = (&docids & candidates).len();
+            if count != 0 {
+                *values.entry(value.to_string()).or_default() += count;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Iterates over the string values of a facet, in their original form, along with the
+    /// documents containing them.
+    pub(super) fn facet_string_values<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        field_id: FieldId,
+    ) -> Result<impl Iterator<Item = Result<(&'t str, RoaringBitmap)>> + 't> {
+        // The keys of the level 0 are the field id, the level and the normalized value.
+        let mut prefix = field_id.to_be_bytes().to_vec();
+        prefix.push(0);
+
+        let iter = self
+            .facet_id_string_docids
+            .remap_key_type::<ByteSlice>()
+            .prefix_iter(rtxn, &prefix)?;
+
+        Ok(iter.map(|result| {
+            let (_, (original, docids)) = result?;
+            Ok((original, docids))
+        }))
+    }
+
+    /// Iterates over the number values of a facet in ascending order, along with the documents
+    /// containing them.
+    pub(super) fn facet_number_values<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        field_id: FieldId,
+    ) -> Result<impl Iterator<Item = Result<(f64, RoaringBitmap)>> + 't> {
+        // The level 0 contains a key for each value, whose bounds are both the value.
+        let left = (field_id, 0, f64::MIN, f64::MIN);
+        let right = (field_id, 0, f64::MAX, f64::MAX);
+        let iter = self.facet_id_f64_docids.range(rtxn, &(left..=right))?;
+
+        Ok(iter.map(|result| {
+            let ((_, _, value, _), docids) = result?;
+            Ok((value, docids))
+        }))
+    }
+
+    /// Same as [`Self::facet_number_values`], from the highest number to the lowest.
+    pub(super) fn facet_number_values_rev<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        field_id: FieldId,
+    ) -> Result<impl Iterator<Item = Result<(f64, RoaringBitmap)>> + 't> {
+        let left = (field_id, 0, f64::MIN, f64::MIN);
+        let right = (field_id, 0, f64::MAX, f64::MAX);
+        let iter = self.facet_id_f64_docids.rev_range(rtxn, &(left..=right))?;
+
+        Ok(iter.map(|result| {
+            let ((_, _, value, _), docids) = result?;
+            Ok((value, docids))
+        }))
+    }
+}
+
+pub(super) fn normalized_words<A: AsRef<[u8]>>(analyzer: &Analyzer<A>, text: &str) -> Vec<String> {
+    let analyzed = analyzer.analyze(text);
+    analyzed
+        .tokens()
+        .filter(|token| token.is_word())
+        .map(|token| token.text().to_string())
+        .collect()
+}
+
+/// Whether the facet query is the beginning of the value, or of any of its words, with at most
+/// `allowed_typos` typos.
+fn facet_value_matches(words: &[String], facet_query: &str, allowed_typos: usize) -> bool {
+    let query_len = facet_query.chars().count();
+    (0..words.len()).any(|start| {
+        let text = words[start..].join(" ");
+        // A typo can make the query shorter or longer than the part of the value it matches.
+        (query_len.saturating_sub(allowed_typos)..=query_len + allowed_typos).any(|len| {
+            let prefix: String = text.chars().take(len).collect();
+            levenshtein(facet_query, &prefix) <= allowed_typos
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn words(value: &str) -> Vec<String> {
+        value.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn test_facet_value_matches() {
+        let value = words("new york city");
+        assert!(facet_value_matches(&value, "new", 0));
+        assert!(facet_value_matches(&value, "yor", 0));
+        assert!(facet_value_matches(&value, "york ci", 0));
+        assert!(facet_value_matches(&value, "nwe york", 2));
+        assert!(!facet_value_matches(&value, "nwe york", 0));
+        assert!(facet_value_matches(&value, "yrk", 1));
+        assert!(!facet_value_matches(&value, "paris", 1));
+        assert!(!facet_value_matches(&value, "citys", 0));
+    }
+}
@@ -0,0 +1,504 @@
+//! The geo shapes that can be used to filter the documents on their `_geo` field, on top of the
+//! `_geoRadius` rule that the engine supports.
+//!
+//! The engine doesn't know about these shapes, so they are extracted from the filter before it
+//! is parsed, and the documents that are in them are computed from the geo rtree of the index.
+//! The shapes can only be combined with the rest of the filter with an `AND`.
+
+use lazy_static::lazy_static;
+use milli::heed::RoTxn;
+use regex::Regex;
+use roaring::RoaringBitmap;
+use rstar::AABB;
+use serde_json::Value;
+
+use super::error::{FacetError, IndexError, Result};
+use super::index::Index;
+
+const BOUNDING_BOX: &str = "_geoBoundingBox";
+const POLYGON: &str = "_geoPolygon";
+const GEO_RADIUS: &str = "_geoRadius";
+
+lazy_static! {
+    static ref GEO_POINT_REGEX: Regex =
+        Regex::new(r"\[\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\]").unwrap();
+}
+
+/// An area of the globe, the coordinates of its points being `[latitude, longitude]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoShape {
+    /// `_geoBoundingBox([top, right], [bottom, left])`, the box can cross the antimeridian.
+    BoundingBox {
+        top_right: [f64; 2],
+        bottom_left: [f64; 2],
+    },
+    /// `_geoPolygon([lat, lng], [lat, lng], [lat, lng], ...)`.
+    Polygon(Vec<[f64; 2]>),
+}
+
+impl GeoShape {
+    pub fn contains(&self, [lat, lng]: [f64; 2]) -> bool {
+        match self {
+            GeoShape::BoundingBox {
+                top_right: [top, right],
+                bottom_left: [bottom, left],
+            } => {
+                let in_longitudes = if left <= right {
+                    (*left..=*right).contains(&lng)
+                } else {
+                    lng >= *left || lng <= *right
+                };
+                (*bottom..=*top).contains(&lat) && in_longitudes
+            }
+            GeoShape::Polygon(points) => {
+                // Casts a ray from the point along the latitudes, the point is inside the
+                // polygon if the ray crosses an odd number of its edges.
+                let mut inside = false;
+                let mut previous = points[points.len() - 1];
+                for &point in points {
+                    let [lat_a, lng_a] = point;
+                    let [lat_b, lng_b] = previous;
+                    if (lng_a > lng) != (lng_b > lng)
+                        && lat < (lat_b - lat_a) * (lng - lng_a) / (lng_b - lng_a) + lat_a
+                    {
+                        inside = !inside;
+                    }
+                    previous = point;
+                }
+                inside
+            }
+        }
+    }
+
+    /// The smallest box containing the shape in the coordinates of the geo rtree of the index,
+    /// which are the cartesian coordinates of the points on a sphere of radius 1.
+    fn envelope(&self) -> AABB<[f64; 3]> {
+        let ([bottom, left], [top, right]) = match self {
+            GeoShape::BoundingBox {
+                top_right: [top, right],
+                bottom_left: [bottom, left],
+            } if left > right => ([*bottom, -180.0], [*top, 180.0]),
+            GeoShape::BoundingBox {
+                top_right,
+                bottom_left,
+            } => (*bottom_left, *top_right),
+            GeoShape::Polygon(points) => points.iter().fold(
+                ([f64::MAX, f64::MAX], [f64::MIN, f64::MIN]),
+                |([bottom, left], [top, right]), [lat, lng]| {
+                    (
+                        [bottom.min(*lat), left.min(*lng)],
+                        [top.max(*lat), right.max(*lng)],
+                    )
+                },
+            ),
+        };
+
+        // The range of a function of an angle in `[from, to]` are its values at the bounds, and
+        // at the extremums of the function that are between them.
+        let range = |from: f64, to: f64, f: fn(f64) -> f64, extremums: &[f64]| {
+            extremums
+                .iter()
+                .filter(|angle| (from..=to).contains(*angle))
+                .chain([from, to].iter())
+                .map(|angle| f(angle.to_radians()))
+                .fold((f64::MAX, f64::MIN), |(min, max), v| {
+                    (min.min(v), max.max(v))
+                })
+        };
+        let (cos_lat_min, cos_lat_max) = range(bottom, top, f64::cos, &[0.0]);
+        let (cos_lng_min, cos_lng_max) = range(left, right, f64::cos, &[-180.0, 0.0, 180.0]);
+        let (sin_lng_min, sin_lng_max) = range(left, right, f64::sin, &[-90.0, 90.0]);
+        let (z_min, z_max) = range(bottom, top, f64::sin, &[]);
+
+        // The cosine of a latitude is positive, it scales the cosine and sine of the longitude.
+        let scale = |min: f64, max: f64| {
+            let low = if min < 0.0 { cos_lat_max } else { cos_lat_min };
+            let high = if max < 0.0 { cos_lat_min } else { cos_lat_max };
+            (min * low, max * high)
+        };
+        let (x_min, x_max) = scale(cos_lng_min, cos_lng_max);
+        let (y_min, y_max) = scale(sin_lng_min, sin_lng_max);
+
+        // Keeps the points on the edges of the shape despite the rounding errors.
+        let margin = 1e-9;
+        AABB::from_corners(
+            [x_min - margin, y_min - margin, z_min - margin],
+            [x_max + margin, y_max + margin, z_max + margin],
+        )
+    }
+
+    fn parse(expr: &str) -> Result<Option<Self>> {
+        let (name, args) = match expr.split_once('(') {
+            Some((name, args)) => (name.trim(), args),
+            None => return Ok(None),
+        };
+        if name != BOUNDING_BOX && name != POLYGON {
+            return Ok(None);
+        }
+        let args = args.strip_suffix(')').ok_or_else(|| {
+            invalid_geo_shape(format!("Missing closing parenthesis in `{}`.", expr))
+        })?;
+
+        let mut points = Vec::new();
+        for capture in GEO_POINT_REGEX.captures_iter(args) {
+            let coordinate = |i: usize| {
+                capture[i].parse::<f64>().map_err(|_| {
+                    invalid_geo_shape(format!("`{}` is not a valid coordinate.", &capture[i]))
+                })
+            };
+            let (lat, lng) = (coordinate(1)?, coordinate(2)?);
+            if !(-90.0..=90.0).contains(&lat) {
+                return Err(invalid_geo_shape(format!(
+                    "Bad latitude `{}`. Latitude must be contained between -90 and 90 degrees.",
+                    lat
+                )));
+            }
+            if !(-180.0..=180.0).contains(&lng) {
+                return Err(invalid_geo_shape(format!(
+                    "Bad longitude `{}`. Longitude must be contained between -180 and 180 degrees.",
+                    lng
+                )));
+            }
+            points.push([lat, lng]);
+        }
+
+        let rest = GEO_POINT_REGEX.replace_all(args, "");
+        if rest.chars().any(|c| c != ',' && !c.is_whitespace()) {
+            return Err(invalid_geo_shape(format!(
+                "The points of `{}` must be written as `[latitude, longitude]`, found `{}`.",
+                name, expr
+            )));
+        }
+
+        if name == BOUNDING_BOX {
+            match points[..] {
+                [top_right, bottom_left] if top_right[0] >= bottom_left[0] => {
+                    Ok(Some(GeoShape::BoundingBox {
+                        top_right,
+                        bottom_left,
+                    }))
+                }
+                [_, _] => Err(invalid_geo_shape(format!(
+                    "The top right corner of `{}` is below its bottom left corner.",
+                    expr
+                ))),
+                _ => Err(invalid_geo_shape(format!(
+                    "`{}` expects two points, its top right and bottom left corners, found `{}`.",
+                    BOUNDING_BOX, expr
+                ))),
+            }
+        } else if points.len() < 3 {
+            Err(invalid_geo_shape(format!(
+                "`{}` expects at least three points, found `{}`.",
+                POLYGON, expr
+            )))
+        } else {
+            Ok(Some(GeoShape::Polygon(points)))
+        }
+    }
+}
+
+fn invalid_geo_shape(message: String) -> IndexError {
+    FacetError::InvalidGeoShape(message).into()
+}
+
+/// Removes the geo shapes from the filter, and returns what remains of the filter along with the
+/// shapes the documents must be in.
+pub fn extract_geo_shapes(filter: &Value) -> Result<(Option<Value>, Vec<GeoShape>)> {
+    let mut shapes = Vec::new();
+    let filter = match filter {
+        Value::String(expr) => extract_from_expr(expr, &mut shapes)?.map(Value::String),
+        Value::Array(ands) => {
+            let mut remaining = Vec::new();
+            for value in ands {
+                match value {
+                    Value::String(expr) => {
+                        if let Some(expr) = extract_from_expr(expr, &mut shapes)? {
+                            remaining.push(Value::String(expr));
+                        }
+                    }
+                    Value::Array(ors) => {
+                        let contains_shape =
+                            ors.iter().filter_map(Value::as_str).any(contains_geo_shape);
+                        if contains_shape {
+                            return Err(invalid_geo_shape(format!(
+                                "`{}` and `{}` can't be used in an `OR`.",
+                                BOUNDING_BOX, POLYGON
+                            )));
+                        }
+                        remaining.push(value.clone());
+                    }
+                    // The filter parser reports the invalid values.
+                    other => remaining.push(other.clone()),
+                }
+            }
+            (!remaining.is_empty()).then(|| Value::Array(remaining))
+        }
+        other => Some(other.clone()),
+    };
+
+    Ok((filter, shapes))
+}
+
+/// Extracts the geo shapes of an expression, if they are combined with the rest of the expression
+/// with `AND`s.
+fn extract_from_expr(expr: &str, shapes: &mut Vec<GeoShape>) -> Result<Option<String>> {
+    if !contains_geo_shape(expr) {
+        return Ok(Some(expr.to_string()));
+    }
+
+    let terms = split_top_level(expr, "OR");
+    if terms.len() > 1 {
+        return Err(invalid_geo_shape(format!(
+            "`{}` and `{}` can't be used in an `OR`.",
+            BOUNDING_BOX, POLYGON
+        )));
+    }
+
+    let mut remaining = Vec::new();
+    for term in split_top_level(expr, "AND") {
+        let term = term.trim();
+        match GeoShape::parse(term)? {
+            Some(shape) => shapes.push(shape),
+            None if contains_geo_shape(term) => {
+                return Err(invalid_geo_shape(format!(
+                    "`{}` and `{}` can only be combined with an `AND`, found `{}`.",
+                    BOUNDING_BOX, POLYGON, term
+                )))
+            }
+            None => remaining.push(term),
+        }
+    }
+
+    if remaining.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(remaining.join(" AND ")))
+    }
+}
+
+/// Returns the center of the first `_geoRadius` of the filter, if its coordinates are valid.
+pub fn geo_radius_center(filter: &Value) -> Option<[f64; 2]> {
+    match filter {
+        Value::String(expr) => unquoted_parts(expr).find_map(|part| {
+            let (_, args) = part.split_once(GEO_RADIUS)?;
+            let (args, _) = args.trim_start().strip_prefix('(')?.split_once(')')?;
+            let mut args = args.split(',').map(|arg| arg.trim().parse::<f64>().ok());
+            match (args.next()??, args.next()??) {
+                (lat, lng) if lat.is_finite() && lng.is_finite() => Some([lat, lng]),
+                _ => None,
+            }
+        }),
+        Value::Array(values) => values.iter().find_map(geo_radius_center),
+        _ => None,
+    }
+}
+
+/// Whether a geo shape is used in the expression, outside of a quoted value.
+fn contains_geo_shape(expr: &str) -> bool {
+    unquoted_parts(expr).any(|part| part.contains(BOUNDING_BOX) || part.contains(POLYGON))
+}
+
+fn unquoted_parts(expr: &str) -> impl Iterator<Item = &str> {
+    let mut quote = None;
+    expr.split(move |c: char| match quote {
+        Some(q) if c == q => {
+            quote = None;
+            true
+        }
+        Some(_) => false,
+        None if c == '"' || c == '\'' => {
+            quote = Some(c);
+            true
+        }
+        None => false,
+    })
+    .step_by(2)
+}
+
+/// Splits the expression on the `keyword`s that are neither in parentheses, brackets, nor quotes.
+fn split_top_level<'a>(expr: &'a str, keyword: &str) -> Vec<&'a str> {
+    let is_boundary = |c: Option<char>| c.map_or(true, |c| c.is_whitespace() || "()".contains(c));
+
+    let mut terms = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in expr.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0 && expr[i..].starts_with(keyword) => {
+                let before = expr[..i].chars().next_back();
+                let after = expr[i + keyword.len()..].chars().next();
+                if i > start && is_boundary(before) && is_boundary(after) {
+                    terms.push(&expr[start..i]);
+                    start = i + keyword.len();
+                }
+            }
+            _ => (),
+        }
+    }
+    terms.push(&expr[start..]);
+    terms
+}
+
+impl Index {
+    /// Returns the documents whose `_geo` field is in all the `shapes`.
+    pub(super) fn geo_shapes_docids(
+        &self,
+        rtxn: &RoTxn,
+        shapes: &[GeoShape],
+    ) -> Result<RoaringBitmap> {
+        let filterable_fields = self.filterable_fields(rtxn)?;
+        if !filterable_fields.contains("_geo") {
+            return Err(invalid_geo_shape(format!(
+                "The `_geo` attribute must be filterable to use `{}` or `{}`.",
+                BOUNDING_BOX, POLYGON
+            )));
+        }
+
+        let (rtree, envelope) = match (self.geo_rtree(rtxn)?, shapes.first()) {
+            (Some(rtree), Some(shape)) => (rtree, shape.envelope()),
+            _ => return Ok(RoaringBitmap::new()),
+        };
+
+        // The envelope of the first shape contains all the points that can be in all the shapes.
+        let docids = rtree
+            .locate_in_envelope_intersecting(&envelope)
+            .filter(|point| shapes.iter().all(|shape| shape.contains(point.data.1)))
+            .map(|point| point.data.0)
+            .collect();
+        Ok(docids)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rstar::Envelope;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_contains() {
+        let paris = [48.8566, 2.3522];
+        let lille = [50.6292, 3.0573];
+        let shape = GeoShape::BoundingBox {
+            top_right: [50.0, 3.0],
+            bottom_left: [48.0, 2.0],
+        };
+        assert!(shape.contains(paris));
+        assert!(!shape.contains(lille));
+
+        // crosses the antimeridian
+        let shape = GeoShape::BoundingBox {
+            top_right: [10.0, -170.0],
+            bottom_left: [-10.0, 170.0],
+        };
+        assert!(shape.contains([0.0, 175.0]));
+        assert!(shape.contains([0.0, -175.0]));
+        assert!(!shape.contains([0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_envelope_contains_shape() {
+        let shapes = [
+            GeoShape::BoundingBox {
+                top_right: [50.0, 3.0],
+                bottom_left: [48.0, 2.0],
+            },
+            GeoShape::BoundingBox {
+                top_right: [10.0, -170.0],
+                bottom_left: [-10.0, 170.0],
+            },
+            GeoShape::Polygon(vec![[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]),
+        ];
+        for shape in shapes {
+            let envelope = shape.envelope();
+            for lat in (-90..=90).map(f64::from) {
+                for lng in (-180..=180).map(f64::from) {
+                    if shape.contains([lat, lng]) {
+                        let point = milli::lat_lng_to_xyz(&[lat, lng]);
+                        assert!(envelope.contains_point(&point), "{:?} {:?}", shape, point);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        let triangle = GeoShape::Polygon(vec![[0.0, 0.0], [10.0, 0.0], [0.0, 10.0]]);
+        assert!(triangle.contains([2.0, 2.0]));
+        assert!(!triangle.contains([8.0, 8.0]));
+        assert!(!triangle.contains([-1.0, 2.0]));
+    }
+
+    #[test]
+    fn test_extract_geo_shapes() {
+        let (filter, shapes) = extract_geo_shapes(&json!("color = red")).unwrap();
+        assert_eq!(filter, Some(json!("color = red")));
+        assert!(shapes.is_empty());
+
+        let (filter, shapes) = extract_geo_shapes(&json!(
+            "color = red AND _geoBoundingBox([50, 3], [48, 2]) AND (a = 1 OR b = 2)"
+        ))
+        .unwrap();
+        assert_eq!(filter, Some(json!("color = red AND (a = 1 OR b = 2)")));
+        assert_eq!(
+            shapes,
+            [GeoShape::BoundingBox {
+                top_right: [50.0, 3.0],
+                bottom_left: [48.0, 2.0]
+            }]
+        );
+
+        let (filter, shapes) = extract_geo_shapes(&json!([
+            "_geoPolygon([0, 0], [10, 0], [0, 10])",
+            ["a = 1", "b = 2"]
+        ]))
+        .unwrap();
+        assert_eq!(filter, Some(json!([["a = 1", "b = 2"]])));
+        assert_eq!(shapes.len(), 1);
+
+        let (filter, _) = extract_geo_shapes(&json!("_geoBoundingBox([50, 3], [48, 2])")).unwrap();
+        assert_eq!(filter, None);
+
+        // a quoted value is not a geo shape
+        let (filter, shapes) = extract_geo_shapes(&json!("title = '_geoPolygon'")).unwrap();
+        assert_eq!(filter, Some(json!("title = '_geoPolygon'")));
+        assert!(shapes.is_empty());
+    }
+
+    #[test]
+    fn test_geo_radius_center() {
+        let filter = json!(["city = Lille", "_geoRadius( 50.6, 3.05 , 1000)"]);
+        assert_eq!(geo_radius_center(&filter), Some([50.6, 3.05]));
+
+        let filter = json!("title = '_geoRadius(1, 2, 3)' AND _geoRadius(4, 5, 6)");
+        assert_eq!(geo_radius_center(&filter), Some([4.0, 5.0]));
+
+        assert_eq!(geo_radius_center(&json!("_geoRadius(-, 2, 3)")), None);
+        assert_eq!(geo_radius_center(&json!("_geoRadius(1")), None);
+    }
+
+    #[test]
+    fn test_extract_invalid_geo_shapes() {
+        for filter in [
+            json!("a = 1 OR _geoBoundingBox([50, 3], [48, 2])"),
+            json!("NOT _geoBoundingBox([50, 3], [48, 2])"),
+            json!([["a = 1", "_geoBoundingBox([50, 3], [48, 2])"]]),
+            json!("_geoBoundingBox([50, 3])"),
+            json!("_geoBoundingBox([48, 3], [50, 2])"),
+            json!("_geoBoundingBox([91, 3], [48, 2])"),
+            json!("_geoPolygon([0, 0], [10, 0])"),
+            json!("_geoPolygon(0, 0, 10)"),
+        ] {
+            assert!(extract_geo_shapes(&filter).is_err(), "{}", filter);
+        }
+    }
+}
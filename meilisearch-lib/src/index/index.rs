@@ -1,4 +1,4 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::create_dir_all;
 use std::marker::PhantomData;
 use std::ops::Deref;
@@ -6,9 +6,10 @@ use std::path::Path;
 use std::sync::Arc;
 
 use fst::IntoStreamer;
+use milli::heed::types::{OwnedType, SerdeJson, Str};
 use milli::heed::{EnvOpenOptions, RoTxn};
 use milli::update::{IndexerConfig, Setting};
-use milli::{obkv_to_json, FieldDistribution};
+use milli::{obkv_to_json, FieldDistribution, DEFAULT_VALUES_PER_FACET};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use time::OffsetDateTime;
@@ -18,11 +19,23 @@ use crate::EnvSizer;
 
 use super::error::IndexError;
 use super::error::Result;
-use super::updates::{MinWordSizeTyposSetting, TypoSettings};
+use super::search::{DEFAULT_PAGINATION_MAX_TOTAL_HITS, DEFAULT_SEARCH_CUTOFF_MS};
+use super::updates::{
+    FacetValuesSort, FacetingSettings, MinWordSizeTyposSetting, PaginationSettings, TypoSettings,
+};
 use super::{Checked, Settings};
 
 pub type Document = Map<String, Value>;
 
+/// The key of the main database under which the `pagination.maxTotalHits` setting is stored.
+pub(crate) const MAX_TOTAL_HITS_KEY: &str = "meilisearch-max-total-hits";
+/// The key of the main database under which the `faceting.maxValuesPerFacet` setting is stored.
+pub(crate) const MAX_VALUES_PER_FACET_KEY: &str = "meilisearch-max-values-per-facet";
+/// The key of the main database under which the `faceting.sortFacetValuesBy` setting is stored.
+pub(crate) const SORT_FACET_VALUES_BY_KEY: &str = "meilisearch-sort-facet-values-by";
+/// The key of the main database under which the `searchCutoffMs` setting is stored.
+pub(crate) const SEARCH_CUTOFF_MS_KEY: &str = "meilisearch-search-cutoff-ms";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IndexMeta {
@@ -195,6 +208,15 @@ impl Index {
             disable_on_attributes: Setting::Set(disabled_attributes),
         };
 
+        let pagination = PaginationSettings {
+            max_total_hits: Setting::Set(self.max_total_hits(txn)?),
+        };
+
+        let faceting = FacetingSettings {
+            max_values_per_facet: Setting::Set(self.max_values_per_facet(txn)?),
+            sort_facet_values_by: Setting::Set(self.sort_facet_values_by(txn)?),
+        };
+
         Ok(Settings {
             displayed_attributes: match displayed_attributes {
                 Some(attrs) => Setting::Set(attrs),
@@ -214,28 +236,101 @@ impl Index {
             },
             synonyms: Setting::Set(synonyms),
             typo_tolerance: Setting::Set(typo_tolerance),
+            pagination: Setting::Set(pagination),
+            faceting: Setting::Set(faceting),
+            search_cutoff_ms: Setting::Set(self.search_cutoff_ms(txn)?),
             _kind: PhantomData,
         })
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// The maximum number of hits a search on this index can return.
+    pub fn max_total_hits(&self, txn: &RoTxn) -> Result<usize> {
+        let max_total_hits = self
+            .main
+            .get::<_, Str, OwnedType<u64>>(txn, MAX_TOTAL_HITS_KEY)?;
+        Ok(max_total_hits.map_or(DEFAULT_PAGINATION_MAX_TOTAL_HITS, |max| max as usize))
+    }
+
+    /// The maximum number of values returned for each facet of a facet distribution.
+    pub fn max_values_per_facet(&self, txn: &RoTxn) -> Result<usize> {
+        let max_values_per_facet = self
+            .main
+            .get::<_, Str, OwnedType<u64>>(txn, MAX_VALUES_PER_FACET_KEY)?;
+        Ok(max_values_per_facet.map_or(DEFAULT_VALUES_PER_FACET, |max| max as usize))
+    }
+
+    /// The time, in milliseconds, after which a search returns degraded results.
+    pub fn search_cutoff_ms(&self, txn: &RoTxn) -> Result<u64> {
+        let search_cutoff_ms = self
+            .main
+            .get::<_, Str, OwnedType<u64>>(txn, SEARCH_CUTOFF_MS_KEY)?;
+        Ok(search_cutoff_ms.unwrap_or(DEFAULT_SEARCH_CUTOFF_MS))
+    }
+
+    /// The order of the values of each facet in a facet distribution. The `*` entry applies to
+    /// all the facets that don't have their own entry.
+    pub fn sort_facet_values_by(&self, txn: &RoTxn) -> Result<BTreeMap<String, FacetValuesSort>> {
+        let sort_facet_values_by = self
+            .main
+            .get::<_, Str, SerdeJson<BTreeMap<String, FacetValuesSort>>>(
+                txn,
+                SORT_FACET_VALUES_BY_KEY,
+            )?;
+        Ok(sort_facet_values_by
+            .unwrap_or_else(|| BTreeMap::from([("*".to_string(), FacetValuesSort::default())])))
+    }
+
+    /// Return the total number of documents matching the filter + the selected documents,
+    /// sorted by `sort` when it is specified.
     pub fn retrieve_documents<S: AsRef<str>>(
         &self,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<S>>,
+        filter: Option<Value>,
+        sort: Option<Vec<String>>,
     ) -> Result<(u64, Vec<Document>)> {
         let txn = self.read_txn()?;
 
         let fields_ids_map = self.fields_ids_map(&txn)?;
         let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
 
-        let iter = self.documents.range(&txn, &(..))?.skip(offset).take(limit);
+        let (number_of_documents, obkvs): (u64, Vec<_>) = match (filter, sort) {
+            (filter, Some(sort)) => {
+                let (number_of_documents, docids) =
+                    self.sorted_docids(&txn, filter.as_ref(), &sort, offset, limit)?;
+                let obkvs = self.documents(&txn, docids)?;
+                (
+                    number_of_documents,
+                    obkvs.into_iter().map(|(_id, obkv)| obkv).collect(),
+                )
+            }
+            (Some(filter), None) => {
+                let docids = match self.filtered_docids(&txn, &filter)? {
+                    Some(docids) => docids,
+                    None => self.documents_ids(&txn)?,
+                };
+                let obkvs = self.documents(&txn, docids.iter().skip(offset).take(limit))?;
+                (
+                    docids.len(),
+                    obkvs.into_iter().map(|(_id, obkv)| obkv).collect(),
+                )
+            }
+            (None, None) => {
+                let obkvs = self
+                    .documents
+                    .range(&txn, &(..))?
+                    .skip(offset)
+                    .take(limit)
+                    .map(|entry| entry.map(|(_id, obkv)| obkv))
+                    .collect::<milli::heed::Result<_>>()?;
+                (self.number_of_documents(&txn)?, obkvs)
+            }
+        };
 
         let mut documents = Vec::new();
 
-        for entry in iter {
-            let (_id, obkv) = entry?;
+        for obkv in obkvs {
             let document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
             let document = match &attributes_to_retrieve {
                 Some(attributes_to_retrieve) => permissive_json_pointer::select_values(
@@ -247,8 +342,6 @@ impl Index {
             documents.push(document);
         }
 
-        let number_of_documents = self.number_of_documents(&txn)?;
-
         Ok((number_of_documents, documents))
     }
 
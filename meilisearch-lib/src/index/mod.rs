@@ -1,12 +1,22 @@
+pub use edition::{EditionFunction, EditionFunctionError};
+pub use export::DocumentExportFormat;
+pub use facet_search::{FacetSearchQuery, FacetSearchResult};
 pub use search::{
-    SearchQuery, SearchResult, DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER,
-    DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
+    validate_filter, FacetStats, HitsInfo, MatchingStrategy, SearchHit, SearchQuery, SearchResult,
+    DEFAULT_CROP_LENGTH, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
+    DEFAULT_HIGHLIGHT_PRE_TAG, DEFAULT_SEARCH_LIMIT,
 };
-pub use updates::{apply_settings_to_builder, Checked, Facets, Settings, Unchecked};
+pub use updates::{apply_settings_to_builder, BatchedUpdate, Checked, Facets, Settings, Unchecked};
 
 mod dump;
+mod edition;
 pub mod error;
+mod export;
+mod facet_search;
+mod geo;
+mod score_details;
 mod search;
+mod typo;
 pub mod updates;
 
 #[allow(clippy::module_inception)]
@@ -24,19 +34,24 @@ pub use test::MockIndex as Index;
 /// code for unit testing, in places where an index would normally be used.
 #[cfg(test)]
 pub mod test {
+    use std::io::Write;
     use std::path::Path;
     use std::path::PathBuf;
     use std::sync::Arc;
 
     use milli::update::IndexerConfig;
-    use milli::update::{DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsMethod};
+    use milli::update::{DocumentAdditionResult, IndexDocumentsMethod, UpdateIndexingStep};
     use nelson::Mocker;
+    use serde_json::Value;
     use uuid::Uuid;
 
     use super::error::Result;
     use super::index::Index;
     use super::Document;
-    use super::{Checked, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings};
+    use super::{
+        BatchedUpdate, Checked, DocumentExportFormat, FacetSearchQuery, FacetSearchResult,
+        IndexMeta, IndexStats, SearchQuery, SearchResult, Settings,
+    };
     use crate::update_file_store::UpdateFileStore;
 
     #[derive(Clone)]
@@ -101,10 +116,27 @@ pub mod test {
             offset: usize,
             limit: usize,
             attributes_to_retrieve: Option<Vec<S>>,
+            filter: Option<Value>,
+            sort: Option<Vec<String>>,
         ) -> Result<(u64, Vec<Document>)> {
             match self {
                 MockIndex::Real(index) => {
-                    index.retrieve_documents(offset, limit, attributes_to_retrieve)
+                    index.retrieve_documents(offset, limit, attributes_to_retrieve, filter, sort)
+                }
+                MockIndex::Mock(_) => todo!(),
+            }
+        }
+
+        pub fn export_documents<W: Write>(
+            &self,
+            format: DocumentExportFormat,
+            attributes_to_retrieve: Option<Vec<String>>,
+            filter: Option<Value>,
+            writer: W,
+        ) -> Result<()> {
+            match self {
+                MockIndex::Real(index) => {
+                    index.export_documents(format, attributes_to_retrieve, filter, writer)
                 }
                 MockIndex::Mock(_) => todo!(),
             }
@@ -149,6 +181,13 @@ pub mod test {
             }
         }
 
+        pub fn perform_facet_search(&self, query: FacetSearchQuery) -> Result<FacetSearchResult> {
+            match self {
+                MockIndex::Real(index) => index.perform_facet_search(query),
+                MockIndex::Mock(m) => unsafe { m.get("perform_facet_search").call(query) },
+            }
+        }
+
         pub fn dump(&self, path: impl AsRef<Path>) -> Result<()> {
             match self {
                 MockIndex::Real(index) => index.dump(path),
@@ -159,14 +198,21 @@ pub mod test {
         pub fn update_documents(
             &self,
             method: IndexDocumentsMethod,
+            merge_patch: bool,
             primary_key: Option<String>,
             file_store: UpdateFileStore,
             contents: impl Iterator<Item = Uuid>,
+            progress: impl Fn(&[Uuid], UpdateIndexingStep) + Sync,
         ) -> Result<DocumentAdditionResult> {
             match self {
-                MockIndex::Real(index) => {
-                    index.update_documents(method, primary_key, file_store, contents)
-                }
+                MockIndex::Real(index) => index.update_documents(
+                    method,
+                    merge_patch,
+                    primary_key,
+                    file_store,
+                    contents,
+                    progress,
+                ),
                 MockIndex::Mock(mocker) => unsafe {
                     mocker
                         .get("update_documents")
@@ -175,10 +221,14 @@ pub mod test {
             }
         }
 
-        pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
+        pub fn apply_updates(
+            &self,
+            updates: impl IntoIterator<Item = BatchedUpdate>,
+            progress: impl Fn(UpdateIndexingStep) + Sync,
+        ) -> Result<Vec<Result<u64>>> {
             match self {
-                MockIndex::Real(index) => index.update_settings(settings),
-                MockIndex::Mock(m) => unsafe { m.get("update_settings").call(settings) },
+                MockIndex::Real(index) => index.apply_updates(updates, progress),
+                MockIndex::Mock(m) => unsafe { m.get("apply_updates").call(updates) },
             }
         }
 
@@ -188,20 +238,6 @@ pub mod test {
                 MockIndex::Mock(m) => unsafe { m.get("update_primary_key").call(primary_key) },
             }
         }
-
-        pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
-            match self {
-                MockIndex::Real(index) => index.delete_documents(ids),
-                MockIndex::Mock(m) => unsafe { m.get("delete_documents").call(ids) },
-            }
-        }
-
-        pub fn clear_documents(&self) -> Result<()> {
-            match self {
-                MockIndex::Real(index) => index.clear_documents(),
-                MockIndex::Mock(m) => unsafe { m.get("clear_documents").call(()) },
-            }
-        }
     }
 
     #[test]
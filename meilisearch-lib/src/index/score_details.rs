@@ -0,0 +1,594 @@
+use std::collections::HashSet;
+
+use milli::tokenizer::Analyzer;
+use milli::{Criterion, MatcherBuilder};
+use serde_json::{json, Map, Value};
+
+use super::search::Document;
+use super::typo::{levenshtein, TypoTolerance};
+
+/// The maximum proximity cost between two query words, also used when
+/// they are found in different attributes.
+const MAX_PROXIMITY: usize = 7;
+
+/// The gap between the positions of two values of the same attribute,
+/// so that the words of different values are never considered close.
+const VALUES_POSITION_GAP: usize = 8;
+
+/// Explains how each ranking rule of an index ranked a hit, by ranking rule.
+pub type ScoreDetails = Map<String, Value>;
+
+/// The rank of a hit for a ranking rule, from `1` for the worst hits to `max_rank` for the best.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Rank {
+    rank: usize,
+    max_rank: usize,
+}
+
+impl Rank {
+    fn new(rank: usize, max_rank: usize) -> Self {
+        let max_rank = max_rank.max(1);
+        Self {
+            rank: rank.clamp(1, max_rank),
+            max_rank,
+        }
+    }
+}
+
+/// Combines the ranks of the ranking rules into a score in `[0, 1]`, the same way the rules are
+/// applied: a rule only tells apart the hits that are equally ranked by the previous ones.
+///
+/// The score only depends on how well the hit matches the query, so the scores of different
+/// searches can be compared.
+fn global_score(ranks: impl IntoIterator<Item = Rank>) -> f64 {
+    let (mut rank, mut max_rank) = (1.0, 1.0);
+    for local in ranks {
+        rank = (rank - 1.0) * local.max_rank as f64 + local.rank as f64;
+        max_rank *= local.max_rank as f64;
+    }
+    rank / max_rank
+}
+
+/// Computes the ranking score and the [`ScoreDetails`] of the hits of a search.
+///
+/// The engine only returns the documents sorted by relevancy, and the words it matched, so the
+/// details are computed again from the documents, by matching the query words against the words
+/// the engine matched in their searchable attributes, the same way the ranking rules do.
+///
+/// The score is thus an approximation of the engine's ranking, which can tell the hits apart
+/// differently, e.g. for the proximity of words split by a separator. The engine's order is
+/// authoritative: [`Self::follow_engine_order`] keeps the scores from increasing along the hits.
+pub struct ScoreDetailsBuilder<'a> {
+    criteria: Vec<Criterion>,
+    sort: &'a [String],
+    /// Finds the words matched by the engine. Without it, all the words of the documents can
+    /// match the query words.
+    engine_matches: Option<&'a MatcherBuilder>,
+    query_words: Vec<String>,
+    /// Whether the last query word can match the beginning of a document word.
+    prefix: bool,
+    searchable_attributes: Vec<String>,
+    typo_tolerance: TypoTolerance,
+}
+
+/// A word of a document, and whether the engine matched it with one of the query words.
+struct DocumentWord {
+    text: String,
+    matched: bool,
+}
+
+/// The best occurrence of a query word in a document.
+#[derive(Debug, Clone, Copy)]
+struct WordMatch {
+    typos: usize,
+    exact: bool,
+    attribute: usize,
+    position: usize,
+}
+
+impl<'a> ScoreDetailsBuilder<'a> {
+    pub fn new<A: AsRef<[u8]>>(
+        criteria: Vec<Criterion>,
+        sort: &'a [String],
+        query: Option<&str>,
+        analyzer: &Analyzer<A>,
+        searchable_attributes: Vec<String>,
+        typo_tolerance: TypoTolerance,
+    ) -> Self {
+        let mut query_words = Vec::new();
+        let mut prefix = false;
+        if let Some(query) = query {
+            let analyzed = analyzer.analyze(query);
+            for token in analyzed.tokens() {
+                prefix = token.is_word();
+                if token.is_word() {
+                    query_words.push(token.text().to_string());
+                }
+            }
+        }
+
+        Self {
+            criteria,
+            sort,
+            engine_matches: None,
+            query_words,
+            prefix,
+            searchable_attributes,
+            typo_tolerance,
+        }
+    }
+
+    /// Only lets the query words match the document words that the engine matched.
+    pub fn engine_matches(&mut self, matcher_builder: &'a MatcherBuilder) -> &mut Self {
+        self.engine_matches = Some(matcher_builder);
+        self
+    }
+
+    /// Builds the ranking score and the details of a document, which must contain all its
+    /// searchable attributes. Only the ranking rules matching the query words are scored, the
+    /// sort rules don't change the score.
+    pub fn build<A: AsRef<[u8]>>(
+        &self,
+        document: &Document,
+        analyzer: &Analyzer<A>,
+    ) -> (f64, ScoreDetails) {
+        let attributes = self.tokenize_attributes(document, analyzer);
+        let matches = self.match_query_words(&attributes);
+        let placeholder = self.query_words.is_empty();
+
+        let mut details = ScoreDetails::new();
+        let mut ranks = Vec::new();
+        let mut order = 0;
+        let mut push = |name: String, detail: Value| {
+            let mut entry = Map::new();
+            entry.insert("order".to_string(), json!(order));
+            if let Value::Object(detail) = detail {
+                entry.extend(detail);
+            }
+            order += 1;
+            details.insert(name, Value::Object(entry));
+        };
+
+        for criterion in &self.criteria {
+            match criterion {
+                Criterion::Words if !placeholder => {
+                    let (detail, rank) = self.words_details(&matches);
+                    push("words".to_string(), detail);
+                    ranks.push(rank);
+                }
+                Criterion::Typo if !placeholder => {
+                    let (detail, rank) = self.typo_details(&matches);
+                    push("typo".to_string(), detail);
+                    ranks.push(rank);
+                }
+                Criterion::Proximity if !placeholder => {
+                    let (detail, rank) = proximity_details(&matches);
+                    push("proximity".to_string(), detail);
+                    ranks.push(rank);
+                }
+                Criterion::Attribute if !placeholder => {
+                    let (detail, rank) = self.attribute_details(&matches);
+                    push("attribute".to_string(), detail);
+                    ranks.push(rank);
+                }
+                Criterion::Exactness if !placeholder => {
+                    let (detail, rank) = self.exactness_details(&attributes, &matches);
+                    push("exactness".to_string(), detail);
+                    ranks.push(rank);
+                }
+                Criterion::Sort => {
+                    for sort in self.sort {
+                        push(sort.clone(), sort_details(document, sort));
+                    }
+                }
+                Criterion::Asc(field) => {
+                    let name = format!("{}:asc", field);
+                    push(name, json!({ "value": field_value(document, field) }));
+                }
+                Criterion::Desc(field) => {
+                    let name = format!("{}:desc", field);
+                    push(name, json!({ "value": field_value(document, field) }));
+                }
+                _ => (),
+            }
+        }
+
+        (global_score(ranks), details)
+    }
+
+    /// Bounds the score of a hit by the score of the hit the engine ranked before it, so that the
+    /// scores never increase along the hits of a search.
+    ///
+    /// The scores are left as is when a sort rule is applied before a scored ranking rule: the
+    /// hits are then not sorted by relevancy, and the sort rules don't change the score.
+    pub fn follow_engine_order(&self, score: f64, previous: Option<f64>) -> f64 {
+        match previous {
+            Some(previous) if self.sorts_by_relevancy() => score.min(previous),
+            _ => score,
+        }
+    }
+
+    /// Whether the engine sorts the hits by the ranking rules that are scored.
+    fn sorts_by_relevancy(&self) -> bool {
+        if self.query_words.is_empty() {
+            return true;
+        }
+        let scored = |criterion: &Criterion| {
+            matches!(
+                criterion,
+                Criterion::Words
+                    | Criterion::Typo
+                    | Criterion::Proximity
+                    | Criterion::Attribute
+                    | Criterion::Exactness
+            )
+        };
+        let sorts = |criterion: &Criterion| match criterion {
+            Criterion::Sort => !self.sort.is_empty(),
+            Criterion::Asc(_) | Criterion::Desc(_) => true,
+            _ => false,
+        };
+        match self.criteria.iter().rposition(scored) {
+            Some(last_scored) => !self.criteria[..last_scored].iter().any(sorts),
+            None => true,
+        }
+    }
+
+    /// Returns the words of each value of the searchable attributes, in the ranking order of the
+    /// attributes.
+    fn tokenize_attributes<A: AsRef<[u8]>>(
+        &self,
+        document: &Document,
+        analyzer: &Analyzer<A>,
+    ) -> Vec<Vec<Vec<DocumentWord>>> {
+        self.searchable_attributes
+            .iter()
+            .map(|attribute| {
+                let mut values = Vec::new();
+                if let Some(value) = field_value(document, attribute) {
+                    collect_strings(value, &mut values);
+                }
+                values
+                    .iter()
+                    .map(|value| {
+                        let analyzed = analyzer.analyze(value);
+                        let tokens: Vec<_> = analyzed.tokens().collect();
+                        let matched_starts = self.engine_matches.map(|builder| {
+                            let mut matcher = builder.build(&tokens[..], value);
+                            let matches = matcher.matches();
+                            matches.iter().map(|m| m.start).collect::<HashSet<_>>()
+                        });
+                        tokens
+                            .iter()
+                            .filter(|token| token.is_word())
+                            .map(|token| DocumentWord {
+                                text: token.text().to_string(),
+                                matched: matched_starts
+                                    .as_ref()
+                                    .map_or(true, |starts| starts.contains(&token.byte_start)),
+                            })
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn match_query_words(&self, attributes: &[Vec<Vec<DocumentWord>>]) -> Vec<Option<WordMatch>> {
+        let last = self.query_words.len().saturating_sub(1);
+        self.query_words
+            .iter()
+            .enumerate()
+            .map(|(index, query_word)| {
+                let allowed_typos = self.typo_tolerance.allowed_typos(query_word);
+                let is_prefix = self.prefix && index == last;
+                let key = |m: &WordMatch| (m.typos, !m.exact, m.attribute, m.position);
+                let mut best: Option<WordMatch> = None;
+
+                for (attribute, values) in attributes.iter().enumerate() {
+                    let mut offset = 0;
+                    for words in values {
+                        for (position, word) in words.iter().enumerate() {
+                            if !word.matched {
+                                continue;
+                            }
+                            let word = &word.text;
+                            let word_match = if word == query_word {
+                                (0, true)
+                            } else if is_prefix && word.starts_with(query_word.as_str()) {
+                                (0, false)
+                            } else {
+                                match levenshtein(query_word, word) {
+                                    typos if typos <= allowed_typos => (typos, false),
+                                    _ => continue,
+                                }
+                            };
+
+                            let candidate = WordMatch {
+                                typos: word_match.0,
+                                exact: word_match.1,
+                                attribute,
+                                position: offset + position,
+                            };
+                            if best.map_or(true, |best| key(&candidate) < key(&best)) {
+                                best = Some(candidate);
+                            }
+                        }
+                        offset += words.len() + VALUES_POSITION_GAP;
+                    }
+                }
+
+                best
+            })
+            .collect()
+    }
+
+    fn words_details(&self, matches: &[Option<WordMatch>]) -> (Value, Rank) {
+        let matching_words = matches.iter().flatten().count();
+        let max_matching_words = self.query_words.len();
+        let detail = json!({
+            "matchingWords": matching_words,
+            "maxMatchingWords": max_matching_words,
+            "score": matching_words as f64 / max_matching_words as f64,
+        });
+        (detail, Rank::new(matching_words, max_matching_words))
+    }
+
+    fn typo_details(&self, matches: &[Option<WordMatch>]) -> (Value, Rank) {
+        let typo_count: usize = matches.iter().flatten().map(|m| m.typos).sum();
+        let max_typo_count: usize = self
+            .query_words
+            .iter()
+            .map(|word| self.typo_tolerance.allowed_typos(word))
+            .sum();
+        let detail = json!({
+            "typoCount": typo_count,
+            "maxTypoCount": max_typo_count,
+            "score": 1.0 - typo_count as f64 / (max_typo_count + 1) as f64,
+        });
+        let max_rank = max_typo_count + 1;
+        (
+            detail,
+            Rank::new(max_rank.saturating_sub(typo_count), max_rank),
+        )
+    }
+
+    fn attribute_details(&self, matches: &[Option<WordMatch>]) -> (Value, Rank) {
+        let best = matches
+            .iter()
+            .flatten()
+            .min_by_key(|m| (m.attribute, m.position));
+        let attributes = self.searchable_attributes.len();
+        let score = match best {
+            Some(best) => 1.0 - best.attribute as f64 / attributes as f64,
+            None => 0.0,
+        };
+        let detail = json!({
+            "bestAttribute": best.map(|best| &self.searchable_attributes[best.attribute]),
+            "score": score,
+        });
+        let rank = best.map_or(0, |best| attributes - best.attribute);
+        (detail, Rank::new(rank, attributes))
+    }
+
+    fn exactness_details(
+        &self,
+        attributes: &[Vec<Vec<DocumentWord>>],
+        matches: &[Option<WordMatch>],
+    ) -> (Value, Rank) {
+        let values = || attributes.iter().flatten();
+        let starts_with_query = |words: &[DocumentWord]| {
+            words.len() >= self.query_words.len()
+                && words
+                    .iter()
+                    .zip(&self.query_words)
+                    .all(|(w, q)| w.text == *q)
+        };
+        // The exact matches rank above the matches at the start of a value, which rank above
+        // the hits ranked by their number of exact words.
+        let max_rank = self.query_words.len() + 3;
+        let (match_type, score, rank) = if values()
+            .any(|words| words.len() == self.query_words.len() && starts_with_query(words))
+        {
+            ("exactMatch", 1.0, max_rank)
+        } else if values().any(|words| starts_with_query(words)) {
+            ("matchesStart", 0.75, max_rank - 1)
+        } else {
+            let exact_words = matches.iter().flatten().filter(|m| m.exact).count();
+            let score = 0.5 * exact_words as f64 / self.query_words.len() as f64;
+            ("noExactMatch", score, exact_words + 1)
+        };
+        let detail = json!({ "matchType": match_type, "score": score });
+        (detail, Rank::new(rank, max_rank))
+    }
+}
+
+fn proximity_details(matches: &[Option<WordMatch>]) -> (Value, Rank) {
+    let matches: Vec<_> = matches.iter().flatten().collect();
+    let pairs = matches.len().saturating_sub(1);
+    let cost: usize = matches
+        .windows(2)
+        .map(|pair| {
+            let (left, right) = (pair[0], pair[1]);
+            if left.attribute != right.attribute {
+                MAX_PROXIMITY
+            } else {
+                let distance = left.position.abs_diff(right.position).max(1);
+                (distance - 1).min(MAX_PROXIMITY)
+            }
+        })
+        .sum();
+    let score = if pairs == 0 {
+        1.0
+    } else {
+        1.0 - cost as f64 / (pairs * MAX_PROXIMITY) as f64
+    };
+    let max_rank = pairs * MAX_PROXIMITY + 1;
+    (
+        json!({ "score": score }),
+        Rank::new(max_rank - cost, max_rank),
+    )
+}
+
+fn sort_details(document: &Document, sort: &str) -> Value {
+    if sort.starts_with("_geoPoint(") {
+        json!({ "distance": document.get("_geoDistance") })
+    } else {
+        let field = sort.rsplit_once(':').map_or(sort, |(field, _)| field);
+        json!({ "value": field_value(document, field) })
+    }
+}
+
+/// Returns the value of a possibly nested field, written with dots.
+fn field_value<'a>(document: &'a Document, field: &str) -> Option<&'a Value> {
+    document.get(field).or_else(|| {
+        let (head, tail) = field.split_once('.')?;
+        match document.get(head)? {
+            Value::Object(object) => field_value(object, tail),
+            _ => None,
+        }
+    })
+}
+
+fn collect_strings(value: &Value, strings: &mut Vec<String>) {
+    match value {
+        Value::String(s) => strings.push(s.clone()),
+        Value::Number(n) => strings.push(n.to_string()),
+        Value::Array(values) => values.iter().for_each(|v| collect_strings(v, strings)),
+        Value::Object(object) => object.values().for_each(|v| collect_strings(v, strings)),
+        Value::Null | Value::Bool(_) => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use milli::tokenizer::AnalyzerConfig;
+
+    use super::*;
+
+    fn builder(criteria: &[Criterion], query: &str) -> ScoreDetailsBuilder<'static> {
+        let analyzer = Analyzer::new(AnalyzerConfig::<Vec<u8>>::default());
+        ScoreDetailsBuilder::new(
+            criteria.to_vec(),
+            &[],
+            Some(query),
+            &analyzer,
+            vec!["title".to_string(), "overview".to_string()],
+            TypoTolerance {
+                enabled: true,
+                min_word_len_one_typo: 5,
+                min_word_len_two_typos: 9,
+            },
+        )
+    }
+
+    #[test]
+    fn test_score_details() {
+        let criteria = [
+            Criterion::Words,
+            Criterion::Typo,
+            Criterion::Proximity,
+            Criterion::Attribute,
+            Criterion::Exactness,
+            Criterion::Asc("year".to_string()),
+        ];
+        let document: Document = serde_json::from_value(json!({
+            "title": "How to Train Your Dragon",
+            "overview": "a viking and a dragn",
+            "year": 2010,
+        }))
+        .unwrap();
+        let analyzer = Analyzer::new(AnalyzerConfig::<Vec<u8>>::default());
+
+        let (score, details) = builder(&criteria, "train dragon").build(&document, &analyzer);
+        assert!(score < 1.0, "{}", score);
+        assert_eq!(
+            Value::Object(details),
+            json!({
+                "words": { "order": 0, "matchingWords": 2, "maxMatchingWords": 2, "score": 1.0 },
+                "typo": { "order": 1, "typoCount": 0, "maxTypoCount": 2, "score": 1.0 },
+                "proximity": { "order": 2, "score": 1.0 - 1.0 / 7.0 },
+                "attribute": { "order": 3, "bestAttribute": "title", "score": 1.0 },
+                "exactness": { "order": 4, "matchType": "noExactMatch", "score": 0.5 },
+                "year:asc": { "order": 5, "value": 2010 },
+            })
+        );
+
+        let (_, details) = builder(&criteria, "viking drag").build(&document, &analyzer);
+        assert_eq!(details["words"]["matchingWords"], json!(2));
+        assert_eq!(details["typo"]["typoCount"], json!(0));
+        assert_eq!(details["attribute"]["bestAttribute"], json!("title"));
+
+        let (_, details) = builder(&criteria, "vikings").build(&document, &analyzer);
+        assert_eq!(details["typo"]["typoCount"], json!(1));
+        assert_eq!(details["attribute"]["bestAttribute"], json!("overview"));
+    }
+
+    #[test]
+    fn test_ranking_score() {
+        let criteria = [
+            Criterion::Words,
+            Criterion::Typo,
+            Criterion::Proximity,
+            Criterion::Attribute,
+            Criterion::Exactness,
+        ];
+        let analyzer = Analyzer::new(AnalyzerConfig::<Vec<u8>>::default());
+        let score = |query: &str, document: Value| {
+            let document: Document = serde_json::from_value(document).unwrap();
+            builder(&criteria, query).build(&document, &analyzer).0
+        };
+
+        // The score doesn't depend on the other hits of the search.
+        assert_eq!(score("glass", json!({ "title": "Glass" })), 1.0);
+        let typo = score("glass", json!({ "title": "Glas" }));
+        let other_attribute = score("glass", json!({ "title": "", "overview": "Glass" }));
+        assert!(typo < 1.0 && other_attribute < 1.0);
+        // The rules are applied in order: a typo costs more than a worse attribute, and a missing
+        // word costs more than a typo.
+        assert!(typo < other_attribute, "{} < {}", typo, other_attribute);
+        let missing_word = score("glass house", json!({ "title": "Glass" }));
+        let typo = score("glass house", json!({ "title": "Glas house" }));
+        assert!(missing_word < typo, "{} < {}", missing_word, typo);
+        // Sort rules don't change the score, nor do the placeholder searches.
+        assert_eq!(score("", json!({ "title": "Glass" })), 1.0);
+    }
+
+    #[test]
+    fn test_ranking_scores_follow_the_engine_order() {
+        let criteria = [Criterion::Words, Criterion::Typo, Criterion::Attribute];
+        let analyzer = Analyzer::new(AnalyzerConfig::<Vec<u8>>::default());
+        // The approximation scores the second hit higher than the first one.
+        let hits = [
+            json!({ "title": "", "overview": "Glass house" }),
+            json!({ "title": "Glass house" }),
+            json!({ "title": "Glass" }),
+            json!({ "title": "Glass house" }),
+        ];
+        let scores = |criteria: &[Criterion]| {
+            let builder = builder(criteria, "glass house");
+            let mut previous = None;
+            hits.iter()
+                .map(|hit| {
+                    let document: Document = serde_json::from_value(hit.clone()).unwrap();
+                    let (score, _) = builder.build(&document, &analyzer);
+                    let score = builder.follow_engine_order(score, previous);
+                    previous = Some(score);
+                    score
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let ranked = scores(&criteria);
+        assert!(
+            ranked.windows(2).all(|pair| pair[0] >= pair[1]),
+            "{:?}",
+            ranked
+        );
+        assert!(ranked[2] < ranked[1], "{:?}", ranked);
+
+        // The hits sorted before being ranked keep their own scores.
+        let sorted = scores(&[Criterion::Asc("year".to_string()), Criterion::Words]);
+        assert!(sorted[2] < sorted[3], "{:?}", sorted);
+    }
+}
@@ -1,21 +1,31 @@
-use std::cmp::min;
+use std::cmp::{min, Reverse};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::str::FromStr;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use std::{panic, thread};
 
+use crossbeam_channel::RecvTimeoutError;
 use either::Either;
+use indexmap::IndexMap;
+use milli::heed::RoTxn;
 use milli::tokenizer::{Analyzer, AnalyzerConfig};
 use milli::{
-    AscDesc, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder, SortError,
+    AscDesc, DocumentId, FieldId, FieldsIdsMap, Filter, FormatOptions, MatchBounds, MatcherBuilder,
+    Member, SortError,
 };
-use regex::Regex;
+use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::index::error::FacetError;
 
 use super::error::{IndexError, Result};
+use super::facet_search::normalized_words;
+use super::geo::{extract_geo_shapes, geo_radius_center};
 use super::index::Index;
+use super::score_details::{ScoreDetails, ScoreDetailsBuilder};
+use super::typo::TypoTolerance;
+use super::updates::FacetValuesSort;
 
 pub type Document = serde_json::Map<String, Value>;
 type MatchesPosition = BTreeMap<String, Vec<MatchBounds>>;
@@ -26,9 +36,13 @@ pub const DEFAULT_CROP_MARKER: fn() -> String = || "…".to_string();
 pub const DEFAULT_HIGHLIGHT_PRE_TAG: fn() -> String = || "<em>".to_string();
 pub const DEFAULT_HIGHLIGHT_POST_TAG: fn() -> String = || "</em>".to_string();
 
-/// The maximimum number of results that the engine
-/// will be able to return in one search call.
-pub const HARD_RESULT_LIMIT: usize = 1000;
+/// The maximimum number of results that the engine will be able to return in one search call,
+/// unless the `pagination.maxTotalHits` setting of the index says otherwise.
+pub const DEFAULT_PAGINATION_MAX_TOTAL_HITS: usize = 1000;
+
+/// The time, in milliseconds, after which a search returns degraded results, unless the
+/// `searchCutoffMs` setting of the index or the query says otherwise.
+pub const DEFAULT_SEARCH_CUTOFF_MS: u64 = 1500;
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase", deny_unknown_fields)]
@@ -37,6 +51,8 @@ pub struct SearchQuery {
     pub offset: Option<usize>,
     #[serde(default = "DEFAULT_SEARCH_LIMIT")]
     pub limit: usize,
+    pub page: Option<usize>,
+    pub hits_per_page: Option<usize>,
     pub attributes_to_retrieve: Option<BTreeSet<String>>,
     pub attributes_to_crop: Option<Vec<String>>,
     #[serde(default = "DEFAULT_CROP_LENGTH")]
@@ -54,6 +70,33 @@ pub struct SearchQuery {
     pub highlight_post_tag: String,
     #[serde(default = "DEFAULT_CROP_MARKER")]
     pub crop_marker: String,
+    // Default to false
+    #[serde(default = "Default::default")]
+    pub show_ranking_score: bool,
+    // Default to false
+    #[serde(default = "Default::default")]
+    pub show_ranking_score_details: bool,
+    pub search_cutoff_ms: Option<u64>,
+    #[serde(default)]
+    pub matching_strategy: MatchingStrategy,
+}
+
+/// Which words of the query can be ignored when no document contains all of them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchingStrategy {
+    /// The last words of the query are dropped first.
+    Last,
+    /// Only the documents containing all the words of the query are returned.
+    All,
+    /// The words of the query contained in the most documents are dropped first.
+    Frequency,
+}
+
+impl Default for MatchingStrategy {
+    fn default() -> Self {
+        Self::Last
+    }
 }
 
 #[derive(Debug, Clone, Serialize, PartialEq)]
@@ -64,63 +107,183 @@ pub struct SearchHit {
     pub formatted: Document,
     #[serde(rename = "_matchesPosition", skip_serializing_if = "Option::is_none")]
     pub matches_position: Option<MatchesPosition>,
+    /// Relevancy of the hit in `[0, 1]`, depending only on how well it matches the query.
+    #[serde(rename = "_rankingScore", skip_serializing_if = "Option::is_none")]
+    pub ranking_score: Option<f64>,
+    #[serde(
+        rename = "_rankingScoreDetails",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub ranking_score_details: Option<ScoreDetails>,
 }
 
 #[derive(Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
     pub hits: Vec<SearchHit>,
-    pub estimated_total_hits: u64,
     pub query: String,
-    pub limit: usize,
-    pub offset: usize,
     pub processing_time_ms: u128,
+    #[serde(flatten)]
+    pub hits_info: HitsInfo,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub facet_distribution: Option<BTreeMap<String, BTreeMap<String, u64>>>,
+    pub facet_distribution: Option<BTreeMap<String, IndexMap<String, u64>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facet_stats: Option<BTreeMap<String, FacetStats>>,
+    /// Whether the search ran out of time, and returned the results of a cheaper search.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub degraded: bool,
+}
+
+/// The lowest and highest values of a numeric facet among the hits of a search.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct FacetStats {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// How the hits of a search were paginated.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HitsInfo {
+    /// The search asked for a `page`, or a number of `hitsPerPage`: the totals are exhaustive.
+    #[serde(rename_all = "camelCase")]
+    Pagination {
+        hits_per_page: usize,
+        page: usize,
+        total_pages: usize,
+        total_hits: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    OffsetLimit {
+        limit: usize,
+        offset: usize,
+        estimated_total_hits: u64,
+    },
+}
+
+impl SearchQuery {
+    /// Whether the query is paginated with `page` and `hitsPerPage` instead of `offset` and
+    /// `limit`.
+    pub fn is_finite_pagination(&self) -> bool {
+        self.page.is_some() || self.hits_per_page.is_some()
+    }
 }
 
 impl Index {
+    /// Searches the index, within the time budget given by the `searchCutoffMs` of the query, or
+    /// of the index.
+    ///
+    /// The engine can't be interrupted, so the search runs in the background until the budget is
+    /// spent. When it isn't done by then, the search is run again without the typos, which are
+    /// what makes the pathological queries slow. The queries of the `frequency` matching strategy
+    /// that remain once the budget is spent are skipped. The results are then flagged as
+    /// `degraded`.
     pub fn perform_search(&self, query: SearchQuery) -> Result<SearchResult> {
         let before_search = Instant::now();
+
+        let search_cutoff_ms = match query.search_cutoff_ms {
+            Some(search_cutoff_ms) => search_cutoff_ms,
+            None => self.search_cutoff_ms(&self.read_txn()?)?,
+        };
+        // There is no deadline when the cutoff is too far in the future to be represented.
+        let deadline = before_search.checked_add(Duration::from_millis(search_cutoff_ms));
+
+        let index = self.clone();
+        let full_query = query.clone();
+        let (mut result, missed_deadline) = within_deadline(
+            deadline,
+            move || index.execute_search(full_query, before_search, deadline, true),
+            || self.execute_search(query, before_search, deadline, false),
+        )?;
+        result.degraded |= missed_deadline;
+        Ok(result)
+    }
+
+    /// Runs a search and formats its hits, ignoring the typos unless `authorize_typos` is set.
+    fn execute_search(
+        &self,
+        query: SearchQuery,
+        before_search: Instant,
+        deadline: Option<Instant>,
+        authorize_typos: bool,
+    ) -> Result<SearchResult> {
         let rtxn = self.read_txn()?;
 
+        // The geo shapes are not supported by the engine, the documents in them are given to it
+        // as the candidates of the search.
+        let (filter, geo_shapes) = match query.filter {
+            Some(ref filter) => extract_geo_shapes(filter)?,
+            None => (None, Vec::new()),
+        };
+
         let mut search = self.search(&rtxn);
 
         if let Some(ref query) = query.q {
             search.query(query);
         }
 
-        // Make sure that a user can't get more documents than the hard limit,
-        // we align that on the offset too.
-        let offset = min(query.offset.unwrap_or(0), HARD_RESULT_LIMIT);
-        let limit = min(query.limit, HARD_RESULT_LIMIT.saturating_sub(offset));
+        search.optional_words(query.matching_strategy != MatchingStrategy::All);
 
-        search.offset(offset);
-        search.limit(limit);
+        // Make sure that a user can't get more documents than the max total hits of the index,
+        // we align that on the offset too.
+        let max_total_hits = self.max_total_hits(&rtxn)?;
+        let (offset, limit) = if query.is_finite_pagination() {
+            let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            // The pages start at 1, the page 0 never contains any hit.
+            let page = query.page.unwrap_or(1);
+            let offset = hits_per_page.saturating_mul(page.saturating_sub(1));
+            let limit = if page == 0 { 0 } else { hits_per_page };
+            (offset, limit)
+        } else {
+            (query.offset.unwrap_or(0), query.limit)
+        };
+        let offset = min(offset, max_total_hits);
+        let limit = min(limit, max_total_hits.saturating_sub(offset));
 
-        if let Some(ref filter) = query.filter {
+        if let Some(ref filter) = filter {
             if let Some(facets) = parse_filter(filter)? {
                 search.filter(facets);
             }
         }
 
+        if !geo_shapes.is_empty() {
+            search.candidates(self.geo_shapes_docids(&rtxn, &geo_shapes)?);
+        }
+
+        let mut geo_center = None;
         if let Some(ref sort) = query.sort {
-            let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+            let sort: Vec<_> = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
                 Ok(sorts) => sorts,
                 Err(asc_desc_error) => {
                     return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
                 }
             };
 
+            geo_center = sort.iter().find_map(geo_point);
             search.sort_criteria(sort);
         }
+        // Without a `_geoPoint` sort, the distance is computed from the first `_geoRadius`.
+        let geo_center = geo_center.or_else(|| query.filter.as_ref().and_then(geo_radius_center));
+
+        let mut degraded = false;
+        if !authorize_typos {
+            search.authorize_typos(false);
+        }
 
         let milli::SearchResult {
             documents_ids,
             matching_words,
             candidates,
             ..
-        } = search.execute()?;
+        } = match (query.matching_strategy, query.q.as_deref()) {
+            (MatchingStrategy::Frequency, Some(q)) => {
+                let (result, skipped_queries) =
+                    self.execute_frequency_search(&rtxn, &mut search, q, offset, limit, deadline)?;
+                degraded |= skipped_queries;
+                result
+            }
+            _ => search.offset(offset).limit(limit).execute()?,
+        };
 
         let fields_ids_map = self.fields_ids_map(&rtxn).unwrap();
 
@@ -185,8 +348,35 @@ impl Index {
         formatter_builder.highlight_prefix(query.highlight_pre_tag);
         formatter_builder.highlight_suffix(query.highlight_post_tag);
 
-        let mut documents = Vec::new();
+        // The ranking score is computed from the details, which are only computed when asked.
+        let score_details = if query.show_ranking_score || query.show_ranking_score_details {
+            // The details need all the searchable attributes, even the ones that are not
+            // displayed.
+            let all_ids: BTreeSet<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+            let searchable_attributes = match self.searchable_fields(&rtxn)? {
+                Some(fields) => fields.into_iter().map(String::from).collect(),
+                None => fields_ids_map
+                    .iter()
+                    .map(|(_, name)| name.to_string())
+                    .collect(),
+            };
+            let typo_tolerance = TypoTolerance::new(self, &rtxn)?;
+            let mut builder = ScoreDetailsBuilder::new(
+                self.criteria(&rtxn)?,
+                query.sort.as_deref().unwrap_or_default(),
+                query.q.as_deref(),
+                &analyzer,
+                searchable_attributes,
+                typo_tolerance,
+            );
+            builder.engine_matches(&formatter_builder);
+            Some((builder, all_ids))
+        } else {
+            None
+        };
 
+        let mut documents = Vec::new();
+        let mut previous_score = None;
         let documents_iter = self.documents(&rtxn, documents_ids)?;
 
         for (_id, obkv) in documents_iter {
@@ -210,57 +400,367 @@ impl Index {
                 &displayed_ids,
             )?;
 
-            if let Some(sort) = query.sort.as_ref() {
-                insert_geo_distance(sort, &mut document);
-            }
+            insert_geo_distance(geo_center, &mut document);
+
+            let (ranking_score, ranking_score_details) = match score_details {
+                Some((ref builder, ref all_ids)) => {
+                    let mut document = make_document(all_ids, &fields_ids_map, obkv)?;
+                    insert_geo_distance(geo_center, &mut document);
+                    let (score, details) = builder.build(&document, &analyzer);
+                    let score = builder.follow_engine_order(score, previous_score);
+                    previous_score = Some(score);
+                    (Some(score), Some(details))
+                }
+                None => (None, None),
+            };
 
             let hit = SearchHit {
                 document,
                 formatted,
                 matches_position,
+                ranking_score: ranking_score.filter(|_| query.show_ranking_score),
+                ranking_score_details: ranking_score_details
+                    .filter(|_| query.show_ranking_score_details),
             };
             documents.push(hit);
         }
 
-        let estimated_total_hits = candidates.len();
+        let hits_info = if query.is_finite_pagination() {
+            let hits_per_page = query.hits_per_page.unwrap_or_else(DEFAULT_SEARCH_LIMIT);
+            // The candidates are exhaustive, but the hits after the max total hits can't be
+            // reached through pagination.
+            let total_hits = min(candidates.len() as usize, max_total_hits);
+            let total_pages = match hits_per_page {
+                0 => 0,
+                _ => (total_hits + hits_per_page - 1) / hits_per_page,
+            };
+            HitsInfo::Pagination {
+                hits_per_page,
+                page: query.page.unwrap_or(1),
+                total_pages,
+                total_hits,
+            }
+        } else {
+            HitsInfo::OffsetLimit {
+                limit: query.limit,
+                offset: query.offset.unwrap_or_default(),
+                estimated_total_hits: candidates.len(),
+            }
+        };
 
-        let facet_distribution = match query.facets {
+        let (facet_distribution, facet_stats) = match query.facets {
             Some(ref fields) => {
                 let mut facet_distribution = self.facets_distribution(&rtxn);
                 if fields.iter().all(|f| f != "*") {
                     facet_distribution.facets(fields);
                 }
-                let distribution = facet_distribution.candidates(candidates).execute()?;
+                facet_distribution.max_values_by_facet(self.max_values_per_facet(&rtxn)?);
+                let distribution = facet_distribution
+                    .candidates(candidates.clone())
+                    .execute()?;
+
+                let stats = self.compute_facet_stats(&rtxn, distribution.keys(), &candidates)?;
+                let distribution =
+                    format_facet_distribution(distribution, &self.sort_facet_values_by(&rtxn)?);
 
-                Some(distribution)
+                (Some(distribution), Some(stats))
             }
-            None => None,
+            None => (None, None),
         };
 
         let result = SearchResult {
             hits: documents,
-            estimated_total_hits,
             query: query.q.clone().unwrap_or_default(),
-            limit: query.limit,
-            offset: query.offset.unwrap_or_default(),
             processing_time_ms: before_search.elapsed().as_millis(),
+            hits_info,
             facet_distribution,
+            facet_stats,
+            degraded,
         };
         Ok(result)
     }
 }
 
-fn insert_geo_distance(sorts: &[String], document: &mut Document) {
-    lazy_static::lazy_static! {
-        static ref GEO_REGEX: Regex =
-            Regex::new(r"_geoPoint\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\)").unwrap();
+impl Index {
+    /// Executes the search, dropping the words of the query that are contained in the most
+    /// documents first when no document contains all of them.
+    ///
+    /// The engine can only drop the last words of a query. Instead, the query is searched with
+    /// all its words required, then again each time its most frequent word is dropped. The hits
+    /// of a query are ranked after the hits of the queries that have more words.
+    ///
+    /// The queries that remain once the `deadline` is passed are skipped, which is returned along
+    /// with the result.
+    fn execute_frequency_search(
+        &self,
+        rtxn: &RoTxn,
+        search: &mut milli::Search,
+        q: &str,
+        offset: usize,
+        limit: usize,
+        deadline: Option<Instant>,
+    ) -> Result<(milli::SearchResult, bool)> {
+        let stop_words = fst::Set::default();
+        let mut config = AnalyzerConfig::default();
+        config.stop_words(&stop_words);
+        let analyzer = Analyzer::new(config);
+
+        let words = normalized_words(&analyzer, q);
+        if words.len() < 2 {
+            return Ok((search.offset(offset).limit(limit).execute()?, false));
+        }
+
+        let mut frequencies = Vec::with_capacity(words.len());
+        for word in &words {
+            let frequency = self
+                .word_docids
+                .get(rtxn, word)?
+                .map_or(0, |docids| docids.len());
+            frequencies.push(frequency);
+        }
+        // The positions of the words, from the most to the least frequent one.
+        let mut drop_order: Vec<_> = (0..words.len()).collect();
+        drop_order.sort_by_key(|&position| Reverse(frequencies[position]));
+
+        search.optional_words(false);
+        let wanted = offset.saturating_add(limit);
+        let mut result: Option<milli::SearchResult> = None;
+        let mut documents_ids = Vec::new();
+        let mut candidates = RoaringBitmap::new();
+        let mut skipped_queries = false;
+        for dropped in 0..words.len() {
+            if dropped > 0 && out_of_time(deadline) {
+                skipped_queries = true;
+                break;
+            }
+
+            let dropped = &drop_order[..dropped];
+            let query: Vec<_> = words
+                .iter()
+                .enumerate()
+                .filter(|(position, _)| !dropped.contains(position))
+                .map(|(_, word)| word.as_str())
+                .collect();
+            search.query(query.join(" "));
+
+            // The hits of the previous queries come first, so enough hits must be fetched to
+            // fill the page without them. Once it is full, only the candidates are needed.
+            let fetched = if documents_ids.len() < wanted {
+                wanted.saturating_add(candidates.len() as usize)
+            } else {
+                0
+            };
+            let bucket = search.offset(0).limit(fetched).execute()?;
+            for id in bucket.documents_ids.iter().copied() {
+                if documents_ids.len() >= wanted {
+                    break;
+                }
+                if !candidates.contains(id) {
+                    documents_ids.push(id);
+                }
+            }
+            candidates |= &bucket.candidates;
+
+            // The words to highlight are the ones of the whole query.
+            result.get_or_insert(bucket);
+        }
+
+        let mut result = result.expect("the query has at least two words");
+        result.documents_ids = documents_ids.into_iter().skip(offset).take(limit).collect();
+        result.candidates = candidates;
+        Ok((result, skipped_queries))
+    }
+
+    /// Returns the documents matching the filter, or `None` when the filter is empty.
+    pub(super) fn filtered_docids(
+        &self,
+        rtxn: &RoTxn,
+        filter: &Value,
+    ) -> Result<Option<RoaringBitmap>> {
+        let (filter, geo_shapes) = extract_geo_shapes(filter)?;
+        let mut docids = match filter {
+            Some(ref filter) => match parse_filter(filter)? {
+                Some(filter) => Some(filter.evaluate(rtxn, self)?),
+                None => None,
+            },
+            None => None,
+        };
+        if !geo_shapes.is_empty() {
+            let geo_docids = self.geo_shapes_docids(rtxn, &geo_shapes)?;
+            docids = Some(match docids {
+                Some(docids) => docids & geo_docids,
+                None => geo_docids,
+            });
+        }
+        Ok(docids)
+    }
+
+    /// Returns the number of documents matching the filter, and the requested page of them
+    /// sorted by `sort`.
+    ///
+    /// The engine can only sort the results of a search, so a search without any query is run.
+    /// Unlike the search route, the page isn't capped by the `maxTotalHits` of the index.
+    pub(super) fn sorted_docids(
+        &self,
+        rtxn: &RoTxn,
+        filter: Option<&Value>,
+        sort: &[String],
+        offset: usize,
+        limit: usize,
+    ) -> Result<(u64, Vec<DocumentId>)> {
+        let (filter, geo_shapes) = match filter {
+            Some(filter) => extract_geo_shapes(filter)?,
+            None => (None, Vec::new()),
+        };
+
+        let mut search = self.search(rtxn);
+        if let Some(ref filter) = filter {
+            if let Some(facets) = parse_filter(filter)? {
+                search.filter(facets);
+            }
+        }
+
+        let sort = match sort.iter().map(|s| AscDesc::from_str(s)).collect() {
+            Ok(sorts) => sorts,
+            Err(asc_desc_error) => {
+                return Err(IndexError::Milli(SortError::from(asc_desc_error).into()))
+            }
+        };
+        search.sort_criteria(sort);
+
+        if !geo_shapes.is_empty() {
+            search.candidates(self.geo_shapes_docids(rtxn, &geo_shapes)?);
+        }
+
+        let milli::SearchResult {
+            documents_ids,
+            candidates,
+            ..
+        } = search.offset(offset).limit(limit).execute()?;
+
+        Ok((candidates.len(), documents_ids))
+    }
+
+    /// Computes the lowest and highest numbers of each facet among the candidates. The facets
+    /// without any number in the candidates have no stats.
+    fn compute_facet_stats<'a>(
+        &self,
+        rtxn: &RoTxn,
+        facets: impl IntoIterator<Item = &'a String>,
+        candidates: &RoaringBitmap,
+    ) -> Result<BTreeMap<String, FacetStats>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let mut stats = BTreeMap::new();
+
+        for facet in facets {
+            let field_id = match fields_ids_map.id(facet) {
+                Some(field_id) => field_id,
+                None => continue,
+            };
+
+            // The lowest number is the first one of the candidates in ascending order, and the
+            // highest the first one in descending order, so only the numbers outside of the
+            // candidates are skipped.
+            let in_candidates = |result: &Result<(f64, RoaringBitmap)>| {
+                result
+                    .as_ref()
+                    .map_or(true, |(_, docids)| !docids.is_disjoint(candidates))
+            };
+            let min = self
+                .facet_number_values(rtxn, field_id)?
+                .find(in_candidates)
+                .transpose()?;
+            let max = self
+                .facet_number_values_rev(rtxn, field_id)?
+                .find(in_candidates)
+                .transpose()?;
+            let facet_stats = min
+                .zip(max)
+                .map(|((min, _), (max, _))| FacetStats { min, max });
+
+            if let Some(facet_stats) = facet_stats {
+                stats.insert(facet.clone(), facet_stats);
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Runs `search` in the background until the `deadline`. Once it is passed, `fallback` is run
+/// instead, and its result is returned along with `true`.
+///
+/// The search can't be interrupted: when it misses the deadline, it keeps running until it ends
+/// and its result is dropped.
+fn within_deadline<T: Send + 'static>(
+    deadline: Option<Instant>,
+    search: impl FnOnce() -> Result<T> + Send + 'static,
+    fallback: impl FnOnce() -> Result<T>,
+) -> Result<(T, bool)> {
+    let deadline = match deadline {
+        Some(deadline) if !out_of_time(Some(deadline)) => deadline,
+        Some(_) => return Ok((fallback()?, true)),
+        None => return Ok((search()?, false)),
     };
-    if let Some(capture_group) = sorts.iter().find_map(|sort| GEO_REGEX.captures(sort)) {
-        // TODO: TAMO: milli encountered an internal error, what do we want to do?
-        let base = [
-            capture_group[1].parse().unwrap(),
-            capture_group[2].parse().unwrap(),
-        ];
+
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    let handle = thread::spawn(move || {
+        // Nobody is waiting for the result anymore once the deadline is passed.
+        let _ = sender.send(search());
+    });
+
+    match receiver.recv_deadline(deadline) {
+        Ok(result) => Ok((result?, false)),
+        Err(RecvTimeoutError::Timeout) => Ok((fallback()?, true)),
+        Err(RecvTimeoutError::Disconnected) => match handle.join() {
+            Err(panic) => panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the search sends its result before returning"),
+        },
+    }
+}
+
+/// Whether the `deadline` of a search is passed.
+fn out_of_time(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |deadline| Instant::now() >= deadline)
+}
+
+/// Orders the values of each facet as configured by `sort_facet_values_by`.
+fn format_facet_distribution(
+    distribution: BTreeMap<String, BTreeMap<String, u64>>,
+    sort_facet_values_by: &BTreeMap<String, FacetValuesSort>,
+) -> BTreeMap<String, IndexMap<String, u64>> {
+    distribution
+        .into_iter()
+        .map(|(facet, values)| {
+            let sort = sort_facet_values_by
+                .get(&facet)
+                .or_else(|| sort_facet_values_by.get("*"))
+                .copied()
+                .unwrap_or_default();
+
+            let mut values: Vec<_> = values.into_iter().collect();
+            if sort == FacetValuesSort::Count {
+                // The sort is stable: values with the same count stay in lexicographic order.
+                values.sort_by(|(_, left), (_, right)| right.cmp(left));
+            }
+
+            (facet, values.into_iter().collect())
+        })
+        .collect()
+}
+
+/// The point of a `_geoPoint` sort.
+fn geo_point(asc_desc: &AscDesc) -> Option<[f64; 2]> {
+    match asc_desc {
+        AscDesc::Asc(Member::Geo(point)) | AscDesc::Desc(Member::Geo(point)) => Some(*point),
+        _ => None,
+    }
+}
+
+/// Inserts the distance between the document and the point the search is centered on, which is
+/// the first `_geoPoint` of the sort, or the first `_geoRadius` of the filter.
+fn insert_geo_distance(geo_center: Option<[f64; 2]>, document: &mut Document) {
+    if let Some(base) = geo_center {
         let geo_point = &document.get("_geo").unwrap_or(&json!(null));
         if let Some((lat, lng)) = geo_point["lat"].as_f64().zip(geo_point["lng"].as_f64()) {
             let distance = milli::distance_between_two_points(&base, &[lat, lng]);
@@ -559,7 +1059,16 @@ fn format_value<'a, A: AsRef<[u8]>>(
     }
 }
 
-fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
+/// Checks the syntax of a filter, its attributes can only be checked against the settings of an
+/// index.
+pub fn validate_filter(filter: &Value) -> Result<()> {
+    if let (Some(filter), _) = extract_geo_shapes(filter)? {
+        parse_filter(&filter)?;
+    }
+    Ok(())
+}
+
+pub(super) fn parse_filter(facets: &Value) -> Result<Option<Filter>> {
     match facets {
         Value::String(expr) => {
             let condition = Filter::from_str(expr)?;
@@ -602,6 +1111,54 @@ fn parse_filter_array(arr: &[Value]) -> Result<Option<Filter>> {
 mod test {
     use super::*;
 
+    fn distribution() -> BTreeMap<String, BTreeMap<String, u64>> {
+        BTreeMap::from([
+            (
+                "color".to_string(),
+                BTreeMap::from([
+                    ("blue".to_string(), 1),
+                    ("green".to_string(), 3),
+                    ("red".to_string(), 3),
+                    ("yellow".to_string(), 2),
+                ]),
+            ),
+            (
+                "price".to_string(),
+                BTreeMap::from([
+                    ("12.5".to_string(), 2),
+                    ("3".to_string(), 1),
+                    ("40".to_string(), 4),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_format_facet_distribution() {
+        let alpha = BTreeMap::from([("*".to_string(), FacetValuesSort::Alpha)]);
+        let formatted = format_facet_distribution(distribution(), &alpha);
+        let colors: Vec<_> = formatted["color"].keys().map(String::as_str).collect();
+        assert_eq!(colors, ["blue", "green", "red", "yellow"]);
+
+        let count = BTreeMap::from([
+            ("*".to_string(), FacetValuesSort::Alpha),
+            ("color".to_string(), FacetValuesSort::Count),
+        ]);
+        let formatted = format_facet_distribution(distribution(), &count);
+        let colors: Vec<_> = formatted["color"].keys().map(String::as_str).collect();
+        assert_eq!(colors, ["green", "red", "yellow", "blue"]);
+        let prices: Vec<_> = formatted["price"].keys().map(String::as_str).collect();
+        assert_eq!(prices, ["12.5", "3", "40"]);
+    }
+
+    fn geo_center(sorts: &[String], filter: Option<&Value>) -> Option<[f64; 2]> {
+        sorts
+            .iter()
+            .filter_map(|sort| AscDesc::from_str(sort).ok())
+            .find_map(|sort| geo_point(&sort))
+            .or_else(|| filter.and_then(geo_radius_center))
+    }
+
     #[test]
     fn test_insert_geo_distance() {
         let value: Document = serde_json::from_str(
@@ -618,18 +1175,18 @@ mod test {
 
         let sorters = &["_geoPoint(50.629973371633746,3.0569447399419567):desc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters = &["_geoPoint(50.629973371633746, 3.0569447399419567):asc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters =
             &["_geoPoint(   50.629973371633746   ,  3.0569447399419567   ):desc".to_string()];
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         let sorters = &[
@@ -640,7 +1197,7 @@ mod test {
         ]
         .map(|s| s.to_string());
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         // only the first geoPoint is used to compute the distance
@@ -653,13 +1210,54 @@ mod test {
         ]
         .map(|s| s.to_string());
         let mut document = value.clone();
-        insert_geo_distance(sorters, &mut document);
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
 
         // there was no _geoPoint so nothing is inserted in the document
         let sorters = &["chien:asc".to_string()];
-        let mut document = value;
-        insert_geo_distance(sorters, &mut document);
+        let mut document = value.clone();
+        insert_geo_distance(geo_center(sorters, None), &mut document);
         assert_eq!(document.get("_geoDistance"), None);
+
+        // without a `_geoPoint` sort, the first `_geoRadius` of the filter is used
+        let filter = json!([
+            "city = Lille",
+            "_geoRadius(50.629973371633746, 3.0569447399419567, 1000)"
+        ]);
+        let mut document = value.clone();
+        insert_geo_distance(geo_center(sorters, Some(&filter)), &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+
+        let sorters = &["_geoPoint(50.629973371633746, 3.0569447399419567):asc".to_string()];
+        let filter = json!("_geoRadius(0, 0, 1000)");
+        let mut document = value;
+        insert_geo_distance(geo_center(sorters, Some(&filter)), &mut document);
+        assert_eq!(document.get("_geoDistance"), Some(&json!(0)));
+    }
+
+    #[test]
+    fn search_missing_the_deadline_falls_back() {
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let slow_search = || {
+            thread::sleep(Duration::from_millis(500));
+            Ok("full")
+        };
+        let result = within_deadline(Some(deadline), slow_search, || Ok("fallback")).unwrap();
+        assert_eq!(result, ("fallback", true));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let result = within_deadline(Some(deadline), || Ok("full"), || Ok("fallback")).unwrap();
+        assert_eq!(result, ("full", false));
+
+        // The search errors are returned as is.
+        let failing_search = || Err(FacetError::InvalidGeoShape("shape".to_string()).into());
+        let result = within_deadline(Some(deadline), failing_search, || Ok("fallback"));
+        assert!(matches!(result, Err(IndexError::Facet(_))));
+
+        let result = within_deadline(None, || Ok("full"), || Ok("fallback")).unwrap();
+        assert_eq!(result, ("full", false));
+        let passed = Instant::now();
+        let result = within_deadline(Some(passed), slow_search, || Ok("fallback")).unwrap();
+        assert_eq!(result, ("fallback", true));
     }
 }
@@ -0,0 +1,63 @@
+use milli::heed::RoTxn;
+
+use super::error::Result;
+use super::index::Index;
+
+/// The typo tolerance settings of an index, used to match words the way the engine does.
+pub struct TypoTolerance {
+    pub enabled: bool,
+    pub min_word_len_one_typo: usize,
+    pub min_word_len_two_typos: usize,
+}
+
+impl TypoTolerance {
+    pub fn new(index: &Index, rtxn: &RoTxn) -> Result<Self> {
+        Ok(Self {
+            enabled: index.authorize_typos(rtxn)?,
+            min_word_len_one_typo: index.min_word_len_one_typo(rtxn)? as usize,
+            min_word_len_two_typos: index.min_word_len_two_typos(rtxn)? as usize,
+        })
+    }
+
+    /// The number of typos allowed when matching this word.
+    pub fn allowed_typos(&self, word: &str) -> usize {
+        let len = word.chars().count();
+        if !self.enabled {
+            0
+        } else if len >= self.min_word_len_two_typos {
+            2
+        } else if len >= self.min_word_len_one_typo {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// The number of single character edits needed to turn `left` into `right`.
+pub fn levenshtein(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + (l != *r) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("glass", "glass"), 0);
+        assert_eq!(levenshtein("glass", "glas"), 1);
+        assert_eq!(levenshtein("glass", "grass"), 1);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+}
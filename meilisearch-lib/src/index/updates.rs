@@ -1,18 +1,31 @@
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+use std::fs::File;
+use std::io::{Cursor, Seek, SeekFrom};
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
+use std::sync::Mutex;
 
 use log::{debug, info, trace};
-use milli::documents::DocumentBatchReader;
+use milli::documents::{DocumentBatchBuilder, DocumentBatchReader};
+use milli::heed::types::{OwnedType, SerdeJson, Str};
+use milli::heed::{RoTxn, RwTxn};
+use milli::obkv_to_json;
 use milli::update::{
     DocumentAdditionResult, DocumentDeletionResult, IndexDocumentsConfig, IndexDocumentsMethod,
-    Setting,
+    Setting, UpdateIndexingStep,
 };
 use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
 use uuid::Uuid;
 
 use super::error::Result;
-use super::index::{Index, IndexMeta};
+use super::index::{
+    Document, Index, IndexMeta, MAX_TOTAL_HITS_KEY, MAX_VALUES_PER_FACET_KEY, SEARCH_CUTOFF_MS_KEY,
+    SORT_FACET_VALUES_BY_KEY,
+};
+use super::{EditionFunction, EditionFunctionError};
 use crate::update_file_store::UpdateFileStore;
 
 fn serialize_with_wildcard<S>(
@@ -68,6 +81,48 @@ pub struct TypoSettings {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     pub disable_on_attributes: Setting<BTreeSet<String>>,
 }
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginationSettings {
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub max_total_hits: Setting<usize>,
+}
+
+/// How the values of a facet are ordered in the facet distribution of a search.
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FacetValuesSort {
+    /// The values are sorted in lexicographic order.
+    Alpha,
+    /// The values with the most matching documents come first.
+    Count,
+}
+
+impl Default for FacetValuesSort {
+    fn default() -> Self {
+        Self::Alpha
+    }
+}
+
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct FacetingSettings {
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub max_values_per_facet: Setting<usize>,
+    /// The order of the values of each facet, `*` applies to all the facets not listed.
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    pub sort_facet_values_by: Setting<BTreeMap<String, FacetValuesSort>>,
+}
+
 /// Holds all the settings for an index. `T` can either be `Checked` if they represents settings
 /// whose validity is guaranteed, or `Unchecked` if they need to be validated. In the later case, a
 /// call to `check` will return a `Settings<Checked>` from a `Settings<Unchecked>`.
@@ -114,6 +169,15 @@ pub struct Settings<T> {
     #[serde(default, skip_serializing_if = "Setting::is_not_set")]
     #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
     pub typo_tolerance: Setting<TypoSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub pagination: Setting<PaginationSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub faceting: Setting<FacetingSettings>,
+    #[serde(default, skip_serializing_if = "Setting::is_not_set")]
+    #[cfg_attr(test, proptest(strategy = "test::setting_strategy()"))]
+    pub search_cutoff_ms: Setting<u64>,
 
     #[serde(skip)]
     pub _kind: PhantomData<T>,
@@ -131,6 +195,9 @@ impl Settings<Checked> {
             synonyms: Setting::Reset,
             distinct_attribute: Setting::Reset,
             typo_tolerance: Setting::Reset,
+            pagination: Setting::Reset,
+            faceting: Setting::Reset,
+            search_cutoff_ms: Setting::Reset,
             _kind: PhantomData,
         }
     }
@@ -146,6 +213,9 @@ impl Settings<Checked> {
             synonyms,
             distinct_attribute,
             typo_tolerance,
+            pagination,
+            faceting,
+            search_cutoff_ms,
             ..
         } = self;
 
@@ -159,6 +229,9 @@ impl Settings<Checked> {
             synonyms,
             distinct_attribute,
             typo_tolerance,
+            pagination,
+            faceting,
+            search_cutoff_ms,
             _kind: PhantomData,
         }
     }
@@ -198,6 +271,9 @@ impl Settings<Unchecked> {
             synonyms: self.synonyms,
             distinct_attribute: self.distinct_attribute,
             typo_tolerance: self.typo_tolerance,
+            pagination: self.pagination,
+            faceting: self.faceting,
+            search_cutoff_ms: self.search_cutoff_ms,
             _kind: PhantomData,
         }
     }
@@ -211,6 +287,20 @@ pub struct Facets {
     pub min_level_size: Option<NonZeroUsize>,
 }
 
+/// An update of the documents or of the settings of an index, that can be applied along with other
+/// updates in a single write transaction.
+#[derive(Debug, Clone)]
+pub enum BatchedUpdate {
+    DeleteDocuments(Vec<String>),
+    DeleteDocumentsByFilter(Value),
+    ClearDocuments,
+    EditDocuments {
+        filter: Option<Value>,
+        function: String,
+    },
+    UpdateSettings(Settings<Checked>),
+}
+
 impl Index {
     fn update_primary_key_txn<'a, 'b>(
         &'a self,
@@ -233,37 +323,202 @@ impl Index {
         Ok(res)
     }
 
-    /// Deletes `ids` from the index, and returns how many documents were deleted.
-    pub fn delete_documents(&self, ids: &[String]) -> Result<DocumentDeletionResult> {
+    /// Applies the updates in a single write transaction, and returns the number of documents
+    /// affected by each of them. Every update is applied in a nested transaction, so that a failed
+    /// update is rolled back without failing the others.
+    ///
+    /// The next update is only pulled from `updates` once the previous one is applied, and the
+    /// indexing steps are reported for the update being applied.
+    pub fn apply_updates(
+        &self,
+        updates: impl IntoIterator<Item = BatchedUpdate>,
+        progress: impl Fn(UpdateIndexingStep) + Sync,
+    ) -> Result<Vec<Result<u64>>> {
         let mut txn = self.write_txn()?;
-        let mut builder = milli::update::DeleteDocuments::new(&mut txn, self)?;
+        let mut results = Vec::new();
+        for update in updates {
+            let mut nested_txn = self.env.nested_write_txn(&mut txn)?;
+            let result = self.apply_update_txn(&mut nested_txn, update, &progress);
+            if result.is_ok() {
+                nested_txn.commit()?;
+            }
+            results.push(result);
+        }
+        txn.commit()?;
+
+        Ok(results)
+    }
+
+    fn apply_update_txn<'a, 'b>(
+        &'a self,
+        txn: &mut milli::heed::RwTxn<'a, 'b>,
+        update: BatchedUpdate,
+        progress: &(impl Fn(UpdateIndexingStep) + Sync),
+    ) -> Result<u64> {
+        match update {
+            BatchedUpdate::DeleteDocuments(ids) => self.delete_documents_txn(txn, &ids),
+            BatchedUpdate::DeleteDocumentsByFilter(filter) => {
+                self.delete_documents_by_filter_txn(txn, &filter)
+            }
+            BatchedUpdate::ClearDocuments => {
+                let number_of_documents = self.number_of_documents(txn)?;
+                milli::update::ClearDocuments::new(txn, self).execute()?;
+                Ok(number_of_documents)
+            }
+            BatchedUpdate::EditDocuments { filter, function } => {
+                self.edit_documents_txn(txn, filter.as_ref(), &function, progress)
+            }
+            BatchedUpdate::UpdateSettings(settings) => {
+                self.update_settings_txn(txn, &settings, progress)?;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Deletes `ids` from the index, and returns how many documents were deleted.
+    fn delete_documents_txn<'a, 'b>(
+        &'a self,
+        txn: &mut milli::heed::RwTxn<'a, 'b>,
+        ids: &[String],
+    ) -> Result<u64> {
+        let mut builder = milli::update::DeleteDocuments::new(txn, self)?;
 
         // We ignore unexisting document ids
         ids.iter().for_each(|id| {
             builder.delete_external_id(id);
         });
 
-        let deleted = builder.execute()?;
+        let DocumentDeletionResult {
+            deleted_documents, ..
+        } = builder.execute()?;
 
-        txn.commit()?;
+        Ok(deleted_documents)
+    }
 
-        Ok(deleted)
+    /// Deletes the documents matching the filter, which has the same syntax as the search filter.
+    fn delete_documents_by_filter_txn<'a, 'b>(
+        &'a self,
+        txn: &mut milli::heed::RwTxn<'a, 'b>,
+        filter: &Value,
+    ) -> Result<u64> {
+        // The documents are resolved in the write transaction, so that the documents indexed
+        // after the task was registered can also be deleted.
+        let docids = self.filtered_docids(txn, filter)?;
+
+        let mut builder = milli::update::DeleteDocuments::new(txn, self)?;
+        // An empty filter doesn't match any document.
+        for docid in docids.unwrap_or_default() {
+            builder.delete_document(docid);
+        }
+        let DocumentDeletionResult {
+            deleted_documents, ..
+        } = builder.execute()?;
+
+        Ok(deleted_documents)
     }
 
-    pub fn clear_documents(&self) -> Result<()> {
-        let mut txn = self.write_txn()?;
-        milli::update::ClearDocuments::new(&mut txn, self).execute()?;
-        txn.commit()?;
+    /// Applies the edition function to the documents matching the filter, or to all the
+    /// documents, and returns how many documents were modified. The task fails without
+    /// modifying any document when the function fails on one of them.
+    fn edit_documents_txn<'a, 'b>(
+        &'a self,
+        txn: &mut milli::heed::RwTxn<'a, 'b>,
+        filter: Option<&Value>,
+        function: &str,
+        progress: &(impl Fn(UpdateIndexingStep) + Sync),
+    ) -> Result<u64> {
+        let function = EditionFunction::parse(function)?;
 
-        Ok(())
+        // An index without primary key doesn't have any document.
+        let primary_key = match self.primary_key(txn)? {
+            Some(primary_key) => primary_key.to_string(),
+            None => return Ok(0),
+        };
+
+        // Like for the deletions, the documents are resolved in the write transaction.
+        let docids = match filter {
+            Some(filter) => self.filtered_docids(txn, filter)?,
+            None => None,
+        };
+        let docids = match docids {
+            Some(docids) => docids,
+            None => self.documents_ids(txn)?,
+        };
+
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+
+        let mut edited_documents = 0;
+        let mut edited_file = tempfile::tempfile()?;
+        let mut batch_builder = DocumentBatchBuilder::new(&mut edited_file)?;
+        for docid in docids {
+            for (_id, obkv) in self.documents(txn, std::iter::once(docid))? {
+                let mut document = obkv_to_json(&all_fields, &fields_ids_map, obkv)?;
+                let document_id = document.get(&primary_key).cloned().unwrap_or_default();
+                let evaluation_error = |message: String| EditionFunctionError::Evaluation {
+                    document_id: match &document_id {
+                        Value::String(id) => id.clone(),
+                        id => id.to_string(),
+                    },
+                    message,
+                };
+
+                let modified = function.apply(&mut document).map_err(evaluation_error)?;
+                if document.get(&primary_key) != Some(&document_id) {
+                    let message = format!("the primary key `{}` can't be modified", primary_key);
+                    return Err(evaluation_error(message).into());
+                }
+
+                if modified {
+                    batch_builder.extend_from_json(Cursor::new(serde_json::to_vec(&document)?))?;
+                    edited_documents += 1;
+                }
+            }
+        }
+        batch_builder.finish()?;
+
+        if edited_documents == 0 {
+            return Ok(0);
+        }
+
+        edited_file.seek(SeekFrom::Start(0))?;
+        let config = IndexDocumentsConfig {
+            update_method: IndexDocumentsMethod::ReplaceDocuments,
+            ..Default::default()
+        };
+        let mut builder = milli::update::IndexDocuments::new(
+            txn,
+            self,
+            self.indexer_config.as_ref(),
+            config,
+            |indexing_step| {
+                debug!("update: {:?}", indexing_step);
+                progress(indexing_step)
+            },
+        )?;
+        builder.add_documents(DocumentBatchReader::from_reader(edited_file)?)?;
+        builder.execute()?;
+
+        info!(
+            "document edition done: {} documents edited",
+            edited_documents
+        );
+
+        Ok(edited_documents)
     }
 
+    /// Indexes the documents of the update files. With `merge_patch`, the documents are JSON
+    /// merge patches of the stored documents.
+    ///
+    /// The indexing steps are reported along with the update files being indexed.
     pub fn update_documents(
         &self,
         method: IndexDocumentsMethod,
+        merge_patch: bool,
         primary_key: Option<String>,
         file_store: UpdateFileStore,
         contents: impl IntoIterator<Item = Uuid>,
+        progress: impl Fn(&[Uuid], UpdateIndexingStep) + Sync,
     ) -> Result<DocumentAdditionResult> {
         trace!("performing document addition");
         let mut txn = self.write_txn()?;
@@ -274,27 +529,69 @@ impl Index {
             }
         }
 
+        let mut contents = contents.into_iter();
+        let mut patched_contents = Vec::new();
+        let patched_documents = if merge_patch {
+            let primary_key = self.primary_key(&txn)?;
+            let contents = contents
+                .by_ref()
+                .inspect(|content_uuid| patched_contents.push(*content_uuid));
+            Some(self.merge_patch_documents(&txn, primary_key, &file_store, contents)?)
+        } else {
+            None
+        };
+
+        // The patched documents replace the stored ones.
+        let method = if merge_patch {
+            IndexDocumentsMethod::ReplaceDocuments
+        } else {
+            method
+        };
+
+        // The update files whose documents are being processed by the indexer.
+        let indexed_contents = Mutex::new(Vec::new());
         let config = IndexDocumentsConfig {
             update_method: method,
             ..Default::default()
         };
-
-        let indexing_callback = |indexing_step| debug!("update: {:?}", indexing_step);
         let mut builder = milli::update::IndexDocuments::new(
             &mut txn,
             self,
             self.indexer_config.as_ref(),
             config,
-            indexing_callback,
+            |indexing_step| {
+                debug!("update: {:?}", indexing_step);
+                progress(&indexed_contents.lock().unwrap(), indexing_step)
+            },
         )?;
 
-        for content_uuid in contents.into_iter() {
+        if let Some(patched_documents) = patched_documents {
+            *indexed_contents.lock().unwrap() = patched_contents.clone();
+            builder.add_documents(patched_documents)?;
+        }
+
+        // All the update files are added to the same indexer, so that the index is only updated
+        // once for the whole batch. The next file is only pulled from `contents` once the
+        // previous one is added, so that the caller can stop an in-flight addition between two
+        // files.
+        let mut added_contents = patched_contents;
+        for content_uuid in contents {
+            *indexed_contents.lock().unwrap() = vec![content_uuid];
             let content_file = file_store.get_update(content_uuid)?;
             let reader = DocumentBatchReader::from_reader(content_file)?;
             builder.add_documents(reader)?;
+            added_contents.push(content_uuid);
         }
 
-        let addition = builder.execute()?;
+        *indexed_contents.lock().unwrap() = added_contents;
+        let DocumentAdditionResult {
+            indexed_documents, ..
+        } = builder.execute()?;
+
+        let addition = DocumentAdditionResult {
+            indexed_documents,
+            number_of_documents: self.number_of_documents(&txn)?,
+        };
 
         txn.commit()?;
 
@@ -303,22 +600,152 @@ impl Index {
         Ok(addition)
     }
 
-    pub fn update_settings(&self, settings: &Settings<Checked>) -> Result<()> {
-        // We must use the write transaction of the update here.
-        let mut txn = self.write_txn()?;
-        let mut builder =
-            milli::update::Settings::new(&mut txn, self, self.indexer_config.as_ref());
+    /// Applies the documents of the update files as JSON merge patches of the stored documents,
+    /// and returns the patched documents in the order of their first patch. The patches of a
+    /// document are applied in order, even when they are in the same update file.
+    ///
+    /// The patches are sorted by external id in temporary files, so that the patches of a
+    /// document are applied together without keeping all the documents in memory.
+    fn merge_patch_documents(
+        &self,
+        txn: &RoTxn,
+        primary_key: Option<&str>,
+        file_store: &UpdateFileStore,
+        contents: impl IntoIterator<Item = Uuid>,
+    ) -> Result<DocumentBatchReader<File>> {
+        let fields_ids_map = self.fields_ids_map(txn)?;
+        let all_fields: Vec<_> = fields_ids_map.iter().map(|(id, _)| id).collect();
+        let external_documents_ids = self.external_documents_ids(txn)?;
+
+        let mut primary_key = primary_key.map(String::from);
+        // The patches by external id, along with their position among all the patches.
+        let mut patches = grenad::SorterBuilder::new(concat_merge).build();
+        // The patched documents by position of their first patch. The documents without a valid
+        // id are left at their position for the indexer, which rejects them.
+        let mut documents = grenad::SorterBuilder::new(concat_merge).build();
+        let mut position = 0u64;
+        for content_uuid in contents {
+            let content_file = file_store.get_update(content_uuid)?;
+            let mut reader = DocumentBatchReader::from_reader(content_file)?;
+            while let Some((fields_index, obkv)) = reader.next_document_with_index()? {
+                let mut patch = Document::new();
+                for (field_id, content) in obkv.iter() {
+                    if let Some(name) = fields_index.name(field_id) {
+                        patch.insert(name.to_string(), serde_json::from_slice(content)?);
+                    }
+                }
+
+                // Like the indexer does, an index without primary key gets the first field
+                // containing `id`.
+                if primary_key.is_none() {
+                    primary_key = patch
+                        .keys()
+                        .find(|key| key.to_lowercase().contains("id"))
+                        .cloned();
+                }
+                let patch_bytes = serde_json::to_vec(&patch)?;
+                match primary_key.as_ref().and_then(|key| patch.get(key)) {
+                    Some(Value::String(id)) => {
+                        patches.insert(id, encode_patch(position, &patch_bytes))?
+                    }
+                    Some(Value::Number(id)) => {
+                        patches.insert(id.to_string(), encode_patch(position, &patch_bytes))?
+                    }
+                    _ => documents.insert(position.to_be_bytes(), patch_bytes)?,
+                }
+                position += 1;
+            }
+        }
+
+        let mut patches = patches.into_stream_merger_iter()?;
+        while let Some((external_id, document_patches)) = patches.next()? {
+            let mut document_patches = decode_patches(document_patches);
+            document_patches.sort_unstable_by_key(|(position, _)| *position);
+
+            let docid = external_documents_ids.get(external_id);
+            let stored = match docid {
+                Some(docid) => self.documents(txn, std::iter::once(docid))?.pop(),
+                None => None,
+            };
+            let mut document = match stored {
+                Some((_, obkv)) => obkv_to_json(&all_fields, &fields_ids_map, obkv)?,
+                None => Document::new(),
+            };
+            for (_, patch) in &document_patches {
+                let patch: Document = serde_json::from_slice(patch)?;
+                permissive_json_pointer::merge_patch(&mut document, &patch);
+            }
+
+            let (first_position, _) = document_patches[0];
+            documents.insert(first_position.to_be_bytes(), serde_json::to_vec(&document)?)?;
+        }
+
+        let mut patched_file = tempfile::tempfile()?;
+        let mut builder = DocumentBatchBuilder::new(&mut patched_file)?;
+        let mut documents = documents.into_stream_merger_iter()?;
+        while let Some((_, document)) = documents.next()? {
+            builder.extend_from_json(Cursor::new(document))?;
+        }
+        builder.finish()?;
+
+        patched_file.seek(SeekFrom::Start(0))?;
+        Ok(DocumentBatchReader::from_reader(patched_file)?)
+    }
+
+    fn update_settings_txn<'a, 'b>(
+        &'a self,
+        txn: &mut milli::heed::RwTxn<'a, 'b>,
+        settings: &Settings<Checked>,
+        progress: &(impl Fn(UpdateIndexingStep) + Sync),
+    ) -> Result<()> {
+        let mut builder = milli::update::Settings::new(txn, self, self.indexer_config.as_ref());
 
         apply_settings_to_builder(settings, &mut builder);
 
-        builder.execute(|indexing_step| debug!("update: {:?}", indexing_step))?;
+        builder.execute(|indexing_step| {
+            debug!("update: {:?}", indexing_step);
+            progress(indexing_step)
+        })?;
 
-        txn.commit()?;
+        apply_meilisearch_settings(self, txn, settings)?;
 
         Ok(())
     }
 }
 
+/// Concatenates the values of the same key. The positions of the patches are kept in their
+/// encoding, so the order in which the values are concatenated doesn't matter.
+fn concat_merge<'a>(
+    _key: &[u8],
+    values: &[Cow<'a, [u8]>],
+) -> std::result::Result<Cow<'a, [u8]>, Infallible> {
+    Ok(values.concat().into())
+}
+
+/// Encodes a patch along with its position, as the big-endian position and length of the patch,
+/// followed by the patch.
+fn encode_patch(position: u64, patch: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(12 + patch.len());
+    bytes.extend_from_slice(&position.to_be_bytes());
+    bytes.extend_from_slice(&(patch.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(patch);
+    bytes
+}
+
+/// Decodes the patches encoded by `encode_patch` and concatenated by `concat_merge`.
+fn decode_patches(mut bytes: &[u8]) -> Vec<(u64, &[u8])> {
+    let mut patches = Vec::new();
+    while bytes.len() >= 12 {
+        let (header, rest) = bytes.split_at(12);
+        let position = u64::from_be_bytes(header[..8].try_into().unwrap());
+        let len = u32::from_be_bytes(header[8..].try_into().unwrap()) as usize;
+        let (patch, rest) = rest.split_at(len);
+        patches.push((position, patch));
+        bytes = rest;
+    }
+    patches
+}
+
 pub fn apply_settings_to_builder(
     settings: &Settings<Checked>,
     builder: &mut milli::update::Settings,
@@ -429,11 +856,122 @@ pub fn apply_settings_to_builder(
     }
 }
 
+/// Applies the settings that milli doesn't know about, and that Meilisearch stores in the main
+/// database of the index itself.
+pub fn apply_meilisearch_settings(
+    index: &milli::Index,
+    txn: &mut RwTxn,
+    settings: &Settings<Checked>,
+) -> Result<()> {
+    let max_total_hits = match settings.pagination {
+        Setting::Set(ref pagination) => pagination.max_total_hits.clone(),
+        Setting::Reset => Setting::Reset,
+        Setting::NotSet => Setting::NotSet,
+    };
+
+    match max_total_hits {
+        Setting::Set(max_total_hits) => index.main.put::<_, Str, OwnedType<u64>>(
+            txn,
+            MAX_TOTAL_HITS_KEY,
+            &(max_total_hits as u64),
+        )?,
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, MAX_TOTAL_HITS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    let (max_values_per_facet, sort_facet_values_by) = match settings.faceting {
+        Setting::Set(ref faceting) => (
+            faceting.max_values_per_facet.clone(),
+            faceting.sort_facet_values_by.clone(),
+        ),
+        Setting::Reset => (Setting::Reset, Setting::Reset),
+        Setting::NotSet => (Setting::NotSet, Setting::NotSet),
+    };
+
+    match max_values_per_facet {
+        Setting::Set(max_values_per_facet) => index.main.put::<_, Str, OwnedType<u64>>(
+            txn,
+            MAX_VALUES_PER_FACET_KEY,
+            &(max_values_per_facet as u64),
+        )?,
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, MAX_VALUES_PER_FACET_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match sort_facet_values_by {
+        Setting::Set(sort_facet_values_by) => index.main.put::<_, Str, SerdeJson<
+            BTreeMap<String, FacetValuesSort>,
+        >>(
+            txn,
+            SORT_FACET_VALUES_BY_KEY,
+            &sort_facet_values_by,
+        )?,
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SORT_FACET_VALUES_BY_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    match settings.search_cutoff_ms {
+        Setting::Set(search_cutoff_ms) => index.main.put::<_, Str, OwnedType<u64>>(
+            txn,
+            SEARCH_CUTOFF_MS_KEY,
+            &search_cutoff_ms,
+        )?,
+        Setting::Reset => {
+            index.main.delete::<_, Str>(txn, SEARCH_CUTOFF_MS_KEY)?;
+        }
+        Setting::NotSet => (),
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 pub(crate) mod test {
+    use std::sync::Arc;
+
+    use milli::update::IndexerConfig;
     use proptest::prelude::*;
+    use serde_json::json;
+    use tempfile::TempDir;
 
     use super::*;
+    use crate::document_formats::read_json;
+
+    /// Opens an index in a temporary directory, and indexes the documents in it.
+    pub(crate) fn index_with_documents(documents: Value) -> (TempDir, Index) {
+        let tmp = tempfile::tempdir().unwrap();
+        let index = Index::open(
+            tmp.path().join("index"),
+            4096 * 100000,
+            Uuid::new_v4(),
+            Arc::new(IndexerConfig::default()),
+        )
+        .unwrap();
+
+        let file_store = UpdateFileStore::new(tmp.path()).unwrap();
+        let (content_uuid, mut update_file) = file_store.new_update().unwrap();
+        read_json(Cursor::new(documents.to_string()), &mut *update_file).unwrap();
+        update_file.persist().unwrap();
+
+        index
+            .update_documents(
+                IndexDocumentsMethod::ReplaceDocuments,
+                false,
+                Some("id".to_string()),
+                file_store,
+                [content_uuid],
+                |_, _| (),
+            )
+            .unwrap();
+
+        (tmp, index)
+    }
 
     pub(super) fn setting_strategy<T: Arbitrary + Clone>() -> impl Strategy<Value = Setting<T>> {
         prop_oneof![
@@ -456,6 +994,9 @@ pub(crate) mod test {
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
+            pagination: Setting::NotSet,
+            faceting: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -478,6 +1019,9 @@ pub(crate) mod test {
             synonyms: Setting::NotSet,
             distinct_attribute: Setting::NotSet,
             typo_tolerance: Setting::NotSet,
+            pagination: Setting::NotSet,
+            faceting: Setting::NotSet,
+            search_cutoff_ms: Setting::NotSet,
             _kind: PhantomData::<Unchecked>,
         };
 
@@ -485,4 +1029,60 @@ pub(crate) mod test {
         assert_eq!(checked.displayed_attributes, Setting::Reset);
         assert_eq!(checked.searchable_attributes, Setting::Reset);
     }
+
+    #[test]
+    fn test_apply_updates_rolls_back_failed_update() {
+        let (_tmp, index) = index_with_documents(json!([
+            { "id": 1, "title": "hello" },
+            { "id": 2, "title": "world" },
+            { "id": 3, "title": 42 },
+        ]));
+
+        let settings = Settings {
+            filterable_attributes: Setting::Set(BTreeSet::from(["title".to_string()])),
+            ..Default::default()
+        };
+        let results = index
+            .apply_updates(
+                [
+                    BatchedUpdate::DeleteDocuments(vec!["1".to_string()]),
+                    // fails on the third document, whose title isn't a string.
+                    BatchedUpdate::EditDocuments {
+                        filter: None,
+                        function: "doc.title = upper(doc.title)".to_string(),
+                    },
+                    BatchedUpdate::UpdateSettings(settings.check()),
+                ],
+                |_| (),
+            )
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &0);
+
+        let (total, documents) = index
+            .retrieve_documents::<String>(0, 10, None, None, None)
+            .unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(documents[0]["title"], json!("world"));
+        assert_eq!(documents[1]["title"], json!(42));
+        assert_eq!(
+            index.settings().unwrap().filterable_attributes,
+            Setting::Set(BTreeSet::from(["title".to_string()]))
+        );
+    }
+
+    #[test]
+    fn test_concatenated_patches() {
+        let first = encode_patch(3, br#"{"id":1}"#);
+        let second = encode_patch(1, br#"{"id":1,"a":2}"#);
+        let values = [Cow::Borrowed(&second[..]), Cow::Owned(first.clone())];
+        let merged = concat_merge(b"1", &values).unwrap();
+        assert_eq!(
+            decode_patches(&merged),
+            [(1, &br#"{"id":1,"a":2}"#[..]), (3, &br#"{"id":1}"#[..])]
+        );
+    }
 }
@@ -1,7 +1,7 @@
 use meilisearch_auth::SearchRules;
 use std::collections::BTreeMap;
 use std::fmt;
-use std::io::Cursor;
+use std::io::{self, BufWriter, Cursor, Write};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,23 +12,30 @@ use futures::Stream;
 use futures::StreamExt;
 use milli::update::IndexDocumentsMethod;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, RwLock};
 use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use crate::document_formats::{read_csv, read_json, read_ndjson};
 use crate::dump::{self, load_dump, DumpHandler};
+use crate::index::error::IndexError;
 use crate::index::{
-    Checked, Document, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
+    validate_filter, Checked, Document, DocumentExportFormat, EditionFunction, FacetSearchQuery,
+    FacetSearchResult, IndexMeta, IndexStats, SearchQuery, SearchResult, Settings, Unchecked,
 };
 use crate::options::{IndexerOpts, SchedulerConfig};
 use crate::snapshot::{load_snapshot, SnapshotService};
+use crate::tasks::batch::BatchId;
 use crate::tasks::error::TaskError;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId};
+use crate::tasks::retention::{RetentionPolicy, RetentionService};
+use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskId, TaskPriority, TaskProgress};
+use crate::tasks::webhooks::{Webhook, WebhookPayload, WebhookService, WebhookStore};
 use crate::tasks::{
-    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskFilter, TaskStore,
+    BatchHandler, EmptyBatchHandler, Scheduler, SnapshotHandler, TaskCancelation, TaskFilter,
+    TaskStore,
 };
 use error::Result;
 
@@ -76,6 +83,7 @@ pub struct IndexController<U, I> {
     pub index_resolver: Arc<IndexResolver<U, I>>,
     scheduler: Arc<RwLock<Scheduler>>,
     task_store: TaskStore,
+    webhook_store: WebhookStore,
     pub update_file_store: UpdateFileStore,
 }
 
@@ -87,6 +95,7 @@ impl<U, I> Clone for IndexController<U, I> {
             scheduler: self.scheduler.clone(),
             update_file_store: self.update_file_store.clone(),
             task_store: self.task_store.clone(),
+            webhook_store: self.webhook_store.clone(),
         }
     }
 }
@@ -108,6 +117,26 @@ impl fmt::Display for DocumentAdditionFormat {
     }
 }
 
+/// The size of the chunks of a documents export.
+const EXPORT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Sends what is written to it as the chunks of a documents export.
+struct ExportChunkWriter(mpsc::Sender<Result<Bytes>>);
+
+impl Write for ExportChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The receiver is dropped when the client stops reading the export.
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the export was interrupted"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[derive(Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Stats {
@@ -122,6 +151,14 @@ pub struct Stats {
 #[derivative(Debug)]
 pub enum Update {
     DeleteDocuments(Vec<String>),
+    /// Deletes the documents matching a search filter.
+    DeleteDocumentsByFilter(Value),
+    /// Edits the documents matching the filter, or all the documents, with a document edition
+    /// function.
+    EditDocuments {
+        filter: Option<Value>,
+        function: String,
+    },
     ClearDocuments,
     Settings {
         settings: Settings<Unchecked>,
@@ -134,6 +171,8 @@ pub enum Update {
         payload: Payload,
         primary_key: Option<String>,
         method: IndexDocumentsMethod,
+        /// Whether the documents are JSON merge patches of the stored documents.
+        merge_patch: bool,
         format: DocumentAdditionFormat,
         allow_index_creation: bool,
     },
@@ -160,6 +199,8 @@ pub struct IndexControllerBuilder {
     dump_dst: Option<PathBuf>,
     ignore_dump_if_db_exists: bool,
     ignore_missing_dump: bool,
+    task_retention_policy: RetentionPolicy,
+    webhook_payload: Option<WebhookPayload>,
 }
 
 impl IndexControllerBuilder {
@@ -204,6 +245,12 @@ impl IndexControllerBuilder {
 
         std::fs::create_dir_all(db_path.as_ref())?;
 
+        // The batches processed concurrently share the indexing memory.
+        let mut indexer_options = indexer_options;
+        indexer_options.max_indexing_memory = indexer_options
+            .max_indexing_memory
+            .split(scheduler_config.concurrent_batches());
+
         let meta_env = Arc::new(open_meta_env(db_path.as_ref(), task_store_size)?);
 
         let update_file_store = UpdateFileStore::new(&db_path)?;
@@ -231,7 +278,8 @@ impl IndexControllerBuilder {
             meta_env.clone(),
             index_resolver.clone(),
         ));
-        let task_store = TaskStore::new(meta_env)?;
+        let task_store = TaskStore::new(meta_env.clone())?;
+        let webhook_store = WebhookStore::new(meta_env)?;
 
         // register all the batch handlers for use with the scheduler.
         let handlers: Vec<Arc<dyn BatchHandler + Sync + Send + 'static>> = vec![
@@ -263,11 +311,31 @@ impl IndexControllerBuilder {
             tokio::task::spawn_local(snapshot_service.run());
         }
 
+        if self.task_retention_policy.is_enabled() {
+            let retention_service = RetentionService {
+                policy: self.task_retention_policy,
+                task_store: task_store.clone(),
+                update_file_store: update_file_store.clone(),
+            };
+
+            tokio::task::spawn_local(retention_service.run());
+        }
+
+        let webhook_service = WebhookService {
+            store: webhook_store.clone(),
+            scheduler: scheduler.clone(),
+            payload: self
+                .webhook_payload
+                .unwrap_or(|task| serde_json::to_value(task).unwrap_or_default()),
+        };
+        tokio::task::spawn_local(webhook_service.run());
+
         Ok(IndexController {
             index_resolver,
             scheduler,
             update_file_store,
             task_store,
+            webhook_store,
         })
     }
 
@@ -344,6 +412,29 @@ impl IndexControllerBuilder {
         self.ignore_missing_dump = ignore_missing_dump;
         self
     }
+
+    /// Set the period after which the finished tasks are deleted.
+    pub fn set_task_retention_period(&mut self, retention_period: Duration) -> &mut Self {
+        self.task_retention_policy
+            .retention_period
+            .replace(retention_period);
+        self
+    }
+
+    /// Set the maximum number of finished tasks kept in the task store.
+    pub fn set_max_finished_tasks(&mut self, max_finished_tasks: usize) -> &mut Self {
+        self.task_retention_policy
+            .max_finished_tasks
+            .replace(max_finished_tasks);
+        self
+    }
+
+    /// Set the function building the body sent to the webhooks from a finished task. Defaults to
+    /// the task serialized as is.
+    pub fn set_webhook_payload(&mut self, payload: WebhookPayload) -> &mut Self {
+        self.webhook_payload.replace(payload);
+        self
+    }
 }
 
 impl<U, I> IndexController<U, I>
@@ -355,16 +446,45 @@ where
         IndexControllerBuilder::default()
     }
 
-    pub async fn register_update(&self, uid: String, update: Update) -> Result<Task> {
+    pub async fn register_update(
+        &self,
+        uid: String,
+        update: Update,
+        priority: TaskPriority,
+    ) -> Result<Task> {
         let index_uid = IndexUid::new(uid)?;
         let content = match update {
             Update::DeleteDocuments(ids) => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Ids(ids),
+                priority,
             },
+            Update::DeleteDocumentsByFilter(filter) => {
+                // The syntax errors are returned right away, instead of failing the task.
+                validate_filter(&filter)?;
+                TaskContent::DocumentDeletion {
+                    index_uid,
+                    deletion: DocumentDeletion::Filter(filter),
+                    priority,
+                }
+            }
+            Update::EditDocuments { filter, function } => {
+                // The syntax errors are returned right away, instead of failing the task.
+                if let Some(ref filter) = filter {
+                    validate_filter(filter)?;
+                }
+                EditionFunction::parse(&function).map_err(IndexError::from)?;
+                TaskContent::DocumentEdition {
+                    index_uid,
+                    filter,
+                    function,
+                    priority,
+                }
+            }
             Update::ClearDocuments => TaskContent::DocumentDeletion {
                 index_uid,
                 deletion: DocumentDeletion::Clear,
+                priority,
             },
             Update::Settings {
                 settings,
@@ -375,12 +495,14 @@ where
                 is_deletion,
                 allow_index_creation,
                 index_uid,
+                priority,
             },
             Update::DocumentAddition {
                 mut payload,
                 primary_key,
                 format,
                 method,
+                merge_patch,
                 allow_index_creation,
             } => {
                 let mut buffer = Vec::new();
@@ -411,20 +533,27 @@ where
                 TaskContent::DocumentAddition {
                     content_uuid,
                     merge_strategy: method,
+                    merge_patch,
                     primary_key,
                     documents_count,
                     allow_index_creation,
                     index_uid,
+                    priority,
                 }
             }
-            Update::DeleteIndex => TaskContent::IndexDeletion { index_uid },
+            Update::DeleteIndex => TaskContent::IndexDeletion {
+                index_uid,
+                priority,
+            },
             Update::CreateIndex { primary_key } => TaskContent::IndexCreation {
                 primary_key,
                 index_uid,
+                priority,
             },
             Update::UpdateIndex { primary_key } => TaskContent::IndexUpdate {
                 primary_key,
                 index_uid,
+                priority,
             },
         };
 
@@ -447,6 +576,11 @@ where
         Ok(task)
     }
 
+    /// Returns the indexing progress of a task, if it is being processed.
+    pub async fn get_task_progress(&self, id: TaskId) -> Option<TaskProgress> {
+        self.scheduler.read().await.task_progress(id)
+    }
+
     pub async fn get_index_task(&self, index_uid: String, task_id: TaskId) -> Result<Task> {
         let creation_task_id = self
             .index_resolver
@@ -484,6 +618,91 @@ where
         Ok(tasks)
     }
 
+    /// Subscribes to the state changes of the tasks, as they are recorded by the update loop.
+    pub async fn subscribe_tasks(&self) -> broadcast::Receiver<Task> {
+        self.scheduler.read().await.subscribe()
+    }
+
+    /// Cancels the unfinished tasks matching the filter.
+    pub async fn cancel_tasks(&self, filter: TaskFilter) -> Result<TaskCancelation> {
+        let cancelation = self.scheduler.write().await.cancel_tasks(filter).await?;
+
+        // The content of the canceled tasks will never be processed.
+        for content_uuid in cancelation
+            .canceled
+            .iter()
+            .filter_map(Task::get_content_uuid)
+        {
+            if let Err(e) = self.update_file_store.delete(content_uuid).await {
+                log::error!("error deleting update file: {}", e);
+            }
+        }
+
+        Ok(cancelation)
+    }
+
+    /// Deletes the finished tasks matching the filter. Returns the deleted tasks.
+    pub async fn delete_tasks(&self, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+        let tasks = self.task_store.delete_tasks(filter).await?;
+        Ok(tasks)
+    }
+
+    /// Returns the tasks of a batch. The batch is not found if none of its tasks match the filter.
+    pub async fn get_batch(&self, id: BatchId, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+        let tasks = self.task_store.get_batch(id, filter).await?;
+        Ok(tasks)
+    }
+
+    /// Lists the batches from the most recent to the oldest, along with their tasks.
+    pub async fn list_batches(
+        &self,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+        offset: Option<BatchId>,
+    ) -> Result<Vec<(BatchId, Vec<Task>)>> {
+        let batches = self.task_store.list_batches(offset, filter, limit).await?;
+        Ok(batches)
+    }
+
+    /// Registers a webhook called with every finished task, or with the finished tasks of
+    /// `index_uid` only.
+    pub async fn register_webhook(
+        &self,
+        url: String,
+        index_uid: Option<String>,
+    ) -> Result<Webhook> {
+        let index_uid = index_uid.map(IndexUid::new).transpose()?;
+        let webhook = Webhook::new(url, index_uid.map(IndexUid::into_inner))?;
+
+        let store = self.webhook_store.clone();
+        let webhook =
+            spawn_blocking(move || store.put_webhook(&webhook).map(|()| webhook)).await??;
+
+        Ok(webhook)
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>> {
+        let store = self.webhook_store.clone();
+        let webhooks = spawn_blocking(move || store.webhooks()).await??;
+        Ok(webhooks)
+    }
+
+    pub async fn get_webhook(&self, uid: Uuid) -> Result<Webhook> {
+        let store = self.webhook_store.clone();
+        let webhook = spawn_blocking(move || store.webhook(uid)).await??;
+        webhook.ok_or_else(|| TaskError::UnexistingWebhook(uid).into())
+    }
+
+    /// Deletes a webhook, dropping the deliveries that are still pending.
+    pub async fn delete_webhook(&self, uid: Uuid) -> Result<()> {
+        let store = self.webhook_store.clone();
+        if spawn_blocking(move || store.delete_webhook(uid)).await?? {
+            Ok(())
+        } else {
+            Err(TaskError::UnexistingWebhook(uid).into())
+        }
+    }
+
     pub async fn list_index_task(
         &self,
         index_uid: String,
@@ -534,21 +753,57 @@ where
         Ok(settings)
     }
 
-    /// Return the total number of documents contained in the index + the selected documents.
+    /// Return the total number of documents matching the filter + the selected documents, sorted
+    /// by `sort` when it is specified.
     pub async fn documents(
         &self,
         uid: String,
         offset: usize,
         limit: usize,
         attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<Value>,
+        sort: Option<Vec<String>>,
     ) -> Result<(u64, Vec<Document>)> {
         let index = self.index_resolver.get_index(uid).await?;
-        let result =
-            spawn_blocking(move || index.retrieve_documents(offset, limit, attributes_to_retrieve))
-                .await??;
+        let result = spawn_blocking(move || {
+            index.retrieve_documents(offset, limit, attributes_to_retrieve, filter, sort)
+        })
+        .await??;
         Ok(result)
     }
 
+    /// Streams all the documents matching the filter, read from a single read transaction.
+    ///
+    /// The errors that happen before anything is exported, like an invalid filter, are returned
+    /// instead of the stream. The later ones end the stream.
+    pub async fn export_documents(
+        &self,
+        uid: String,
+        format: DocumentExportFormat,
+        attributes_to_retrieve: Option<Vec<String>>,
+        filter: Option<Value>,
+    ) -> Result<impl Stream<Item = Result<Bytes>>> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let (sender, mut receiver) = mpsc::channel(1);
+        spawn_blocking(move || {
+            let writer =
+                BufWriter::with_capacity(EXPORT_CHUNK_SIZE, ExportChunkWriter(sender.clone()));
+            if let Err(e) = index.export_documents(format, attributes_to_retrieve, filter, writer) {
+                let _ = sender.blocking_send(Err(e.into()));
+            }
+        });
+
+        let first = match receiver.recv().await {
+            Some(Err(e)) => return Err(e),
+            first => first,
+        };
+        let rest = futures::stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|chunk| (chunk, receiver))
+        });
+
+        Ok(futures::stream::iter(first).chain(rest))
+    }
+
     pub async fn document(
         &self,
         uid: String,
@@ -568,6 +823,16 @@ where
         Ok(result)
     }
 
+    pub async fn facet_search(
+        &self,
+        uid: String,
+        query: FacetSearchQuery,
+    ) -> Result<FacetSearchResult> {
+        let index = self.index_resolver.get_index(uid).await?;
+        let result = spawn_blocking(move || index.perform_facet_search(query)).await??;
+        Ok(result)
+    }
+
     pub async fn get_index(&self, uid: String) -> Result<IndexMetadata> {
         let index = self.index_resolver.get_index(uid.clone()).await?;
         let uuid = index.uuid();
@@ -652,7 +917,8 @@ mod test {
     use crate::index::error::Result as IndexResult;
     use crate::index::Index;
     use crate::index::{
-        DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG, DEFAULT_HIGHLIGHT_PRE_TAG,
+        HitsInfo, MatchingStrategy, DEFAULT_CROP_MARKER, DEFAULT_HIGHLIGHT_POST_TAG,
+        DEFAULT_HIGHLIGHT_PRE_TAG,
     };
     use crate::index_resolver::index_store::MockIndexStore;
     use crate::index_resolver::meta_store::MockIndexMetaStore;
@@ -666,12 +932,14 @@ mod test {
             task_store: TaskStore,
             update_file_store: UpdateFileStore,
             scheduler: Arc<RwLock<Scheduler>>,
+            webhook_store: WebhookStore,
         ) -> Self {
             IndexController {
                 index_resolver,
                 task_store,
                 update_file_store,
                 scheduler,
+                webhook_store,
             }
         }
     }
@@ -684,6 +952,8 @@ mod test {
             q: Some(String::from("hello world")),
             offset: Some(10),
             limit: 0,
+            page: None,
+            hits_per_page: None,
             attributes_to_retrieve: Some(vec!["string".to_owned()].into_iter().collect()),
             attributes_to_crop: None,
             crop_length: 18,
@@ -695,16 +965,24 @@ mod test {
             highlight_pre_tag: DEFAULT_HIGHLIGHT_PRE_TAG(),
             highlight_post_tag: DEFAULT_HIGHLIGHT_POST_TAG(),
             crop_marker: DEFAULT_CROP_MARKER(),
+            show_ranking_score: false,
+            show_ranking_score_details: false,
+            search_cutoff_ms: None,
+            matching_strategy: MatchingStrategy::default(),
         };
 
         let result = SearchResult {
             hits: vec![],
-            estimated_total_hits: 29,
             query: "hello world".to_string(),
-            limit: 24,
-            offset: 0,
             processing_time_ms: 50,
+            hits_info: HitsInfo::OffsetLimit {
+                limit: 24,
+                offset: 0,
+                estimated_total_hits: 29,
+            },
             facet_distribution: None,
+            facet_stats: None,
+            degraded: false,
         };
 
         let mut uuid_store = MockIndexMetaStore::new();
@@ -757,8 +1035,16 @@ mod test {
             SchedulerConfig::default(),
         )
         .unwrap();
-        let index_controller =
-            IndexController::mock(index_resolver, task_store, update_file_store, scheduler);
+        let tmp = tempfile::tempdir().unwrap();
+        let meta_env = Arc::new(open_meta_env(tmp.path(), 4096 * 100000).unwrap());
+        let webhook_store = WebhookStore::new(meta_env).unwrap();
+        let index_controller = IndexController::mock(
+            index_resolver,
+            task_store,
+            update_file_store,
+            scheduler,
+            webhook_store,
+        );
 
         let r = index_controller
             .search(index_uid.to_owned(), query.clone())
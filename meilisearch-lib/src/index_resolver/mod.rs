@@ -2,25 +2,29 @@ pub mod error;
 pub mod index_store;
 pub mod meta_store;
 
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::path::Path;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use error::{IndexResolverError, Result};
 use index_store::{IndexStore, MapIndexStore};
 use meilisearch_error::ResponseError;
 use meta_store::{HeedMetaStore, IndexMetaStore};
 use milli::heed::Env;
-use milli::update::{DocumentDeletionResult, IndexerConfig};
+use milli::update::{IndexerConfig, UpdateIndexingStep};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tokio::task::spawn_blocking;
 use uuid::Uuid;
 
-use crate::index::{error::Result as IndexResult, Index};
+use crate::index::{error::Result as IndexResult, BatchedUpdate, Index};
 use crate::options::IndexerOpts;
-use crate::tasks::task::{DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskResult};
+use crate::tasks::batch::{BatchProgress, CanceledTasks};
+use crate::tasks::task::{
+    DocumentDeletion, Task, TaskContent, TaskEvent, TaskId, TaskProgress, TaskResult,
+};
 use crate::update_file_store::UpdateFileStore;
 
 use self::meta_store::IndexMeta;
@@ -135,7 +139,12 @@ where
         }
     }
 
-    pub async fn process_document_addition_batch(&self, mut tasks: Vec<Task>) -> Vec<Task> {
+    pub async fn process_document_addition_batch(
+        &self,
+        mut tasks: Vec<Task>,
+        canceled: CanceledTasks,
+        progress: BatchProgress,
+    ) -> Vec<Task> {
         fn get_content_uuid(task: &Task) -> Uuid {
             match task {
                 Task {
@@ -146,7 +155,35 @@ where
             }
         }
 
-        let content_uuids = tasks.iter().map(get_content_uuid).collect::<Vec<_>>();
+        // The tasks that were canceled before we started processing the batch are not indexed at
+        // all.
+        for task in tasks.iter_mut().filter(|t| canceled.contains(t.id)) {
+            task.events.push(TaskEvent::canceled());
+        }
+
+        let contents = tasks
+            .iter()
+            .filter(|t| !t.is_finished())
+            .map(|t| (t.id, get_content_uuid(t)))
+            .collect::<Vec<_>>();
+
+        // The tasks of each update file, with the number of documents they were registered with.
+        let documents_counts = tasks
+            .iter()
+            .filter(|t| !t.is_finished())
+            .map(|t| match t.content {
+                TaskContent::DocumentAddition {
+                    content_uuid,
+                    documents_count,
+                    ..
+                } => (content_uuid, (t.id, documents_count)),
+                _ => panic!("unexpected task in the document addition batch"),
+            })
+            .collect::<HashMap<_, _>>();
+
+        if contents.is_empty() {
+            return tasks;
+        }
 
         match tasks.first() {
             Some(Task {
@@ -154,6 +191,7 @@ where
                 content:
                     TaskContent::DocumentAddition {
                         merge_strategy,
+                        merge_patch,
                         primary_key,
                         allow_index_creation,
                         index_uid,
@@ -163,6 +201,7 @@ where
             }) => {
                 let primary_key = primary_key.clone();
                 let method = *merge_strategy;
+                let merge_patch = *merge_patch;
 
                 let index = if *allow_index_creation {
                     self.get_or_create_index(index_uid.clone(), *id).await
@@ -188,13 +227,34 @@ where
                 };
 
                 let file_store = self.file_store.clone();
-                let result = spawn_blocking(move || {
-                    index.update_documents(
-                        method,
-                        primary_key,
-                        file_store,
-                        content_uuids.into_iter(),
-                    )
+                let skipped = Arc::new(Mutex::new(HashSet::new()));
+                let result = spawn_blocking({
+                    let skipped = skipped.clone();
+                    move || {
+                        // The content of each task is lazily pulled by the indexer, once the
+                        // previous one is indexed, so we can skip the tasks that were canceled in
+                        // the meantime.
+                        let content_uuids =
+                            contents.into_iter().filter_map(move |(id, content_uuid)| {
+                                if canceled.contains(id) {
+                                    skipped.lock().unwrap().insert(id);
+                                    None
+                                } else {
+                                    Some(content_uuid)
+                                }
+                            });
+
+                        let indexing_callback = documents_progress(documents_counts, progress);
+
+                        index.update_documents(
+                            method,
+                            merge_patch,
+                            primary_key,
+                            file_store,
+                            content_uuids,
+                            indexing_callback,
+                        )
+                    }
                 })
                 .await;
 
@@ -215,8 +275,13 @@ where
                     },
                 };
 
-                for task in tasks.iter_mut() {
-                    task.events.push(event.clone());
+                let skipped = skipped.lock().unwrap();
+                for task in tasks.iter_mut().filter(|t| !t.is_finished()) {
+                    if skipped.contains(&task.id) {
+                        task.events.push(TaskEvent::canceled());
+                    } else {
+                        task.events.push(event.clone());
+                    }
                 }
 
                 tasks
@@ -225,54 +290,194 @@ where
         }
     }
 
-    pub async fn process_task(&self, task: &Task) -> Result<TaskResult> {
-        match &task.content {
-            TaskContent::DocumentAddition { .. } => panic!("updates should be handled by batch"),
-            TaskContent::DocumentDeletion {
-                deletion: DocumentDeletion::Ids(ids),
-                index_uid,
-            } => {
-                let ids = ids.clone();
-                let index = self.get_index(index_uid.clone().into_inner()).await?;
+    pub async fn process_index_updates_batch(
+        &self,
+        mut tasks: Vec<Task>,
+        canceled: CanceledTasks,
+        progress: BatchProgress,
+    ) -> Vec<Task> {
+        fn get_update(task: &Task) -> BatchedUpdate {
+            match &task.content {
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Ids(ids),
+                    ..
+                } => BatchedUpdate::DeleteDocuments(ids.clone()),
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Filter(filter),
+                    ..
+                } => BatchedUpdate::DeleteDocumentsByFilter(filter.clone()),
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Clear,
+                    ..
+                } => BatchedUpdate::ClearDocuments,
+                TaskContent::DocumentEdition {
+                    filter, function, ..
+                } => BatchedUpdate::EditDocuments {
+                    filter: filter.clone(),
+                    function: function.clone(),
+                },
+                TaskContent::SettingsUpdate { settings, .. } => {
+                    BatchedUpdate::UpdateSettings(settings.clone().check())
+                }
+                _ => panic!("unexpected task in the index updates batch"),
+            }
+        }
+
+        fn get_result(task: &Task, count: u64) -> TaskResult {
+            match task.content {
+                TaskContent::DocumentDeletion {
+                    deletion: DocumentDeletion::Clear,
+                    ..
+                } => TaskResult::ClearAll {
+                    deleted_documents: count,
+                },
+                TaskContent::DocumentDeletion { .. } => TaskResult::DocumentDeletion {
+                    deleted_documents: count,
+                },
+                TaskContent::DocumentEdition { .. } => TaskResult::DocumentEdition {
+                    edited_documents: count,
+                },
+                _ => TaskResult::Other,
+            }
+        }
+
+        fn creates_index(task: &Task) -> bool {
+            matches!(
+                task.content,
+                TaskContent::SettingsUpdate {
+                    is_deletion: false,
+                    allow_index_creation: true,
+                    ..
+                }
+            )
+        }
+
+        // The tasks that were canceled before we started processing the batch are not applied at
+        // all.
+        for task in tasks.iter_mut().filter(|t| canceled.contains(t.id)) {
+            task.events.push(TaskEvent::canceled());
+        }
 
-                let DocumentDeletionResult {
-                    deleted_documents, ..
-                } = spawn_blocking(move || index.delete_documents(&ids)).await??;
+        let index_uid = match tasks
+            .iter()
+            .find(|t| !t.is_finished())
+            .and_then(|t| t.index_uid())
+        {
+            Some(index_uid) => index_uid.to_string(),
+            None => return tasks,
+        };
 
-                Ok(TaskResult::DocumentDeletion { deleted_documents })
+        // If the index doesn't exist, the tasks preceding the first settings update allowed to
+        // create it fail, and the index is created for the following ones.
+        let index = match self.get_index(index_uid.clone()).await {
+            Err(IndexResolverError::UnexistingIndex(uid)) => {
+                let error = ResponseError::from(IndexResolverError::UnexistingIndex(uid));
+                let creator = tasks
+                    .iter()
+                    .position(|t| !t.is_finished() && creates_index(t));
+                let end = creator.unwrap_or(tasks.len());
+                for task in tasks[..end].iter_mut().filter(|t| !t.is_finished()) {
+                    task.events.push(TaskEvent::failed(error.clone()));
+                }
+                match creator {
+                    Some(creator) => {
+                        let task_id = tasks[creator].id;
+                        self.get_or_create_index(IndexUid::new_unchecked(index_uid), task_id)
+                            .await
+                    }
+                    None => return tasks,
+                }
             }
-            TaskContent::DocumentDeletion {
-                deletion: DocumentDeletion::Clear,
-                index_uid,
-            } => {
-                let index = self.get_index(index_uid.clone().into_inner()).await?;
-                let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
-                    let number_documents = index.stats()?.number_of_documents;
-                    index.clear_documents()?;
-                    Ok(number_documents)
-                })
-                .await??;
+            index => index,
+        };
 
-                Ok(TaskResult::ClearAll { deleted_documents })
+        let index = match index {
+            Ok(index) => index,
+            Err(e) => {
+                let error = ResponseError::from(e);
+                for task in tasks.iter_mut().filter(|t| !t.is_finished()) {
+                    task.events.push(TaskEvent::failed(error.clone()));
+                }
+                return tasks;
             }
-            TaskContent::SettingsUpdate {
-                settings,
-                is_deletion,
-                allow_index_creation,
-                index_uid,
-            } => {
-                let index = if *is_deletion || !*allow_index_creation {
-                    self.get_index(index_uid.clone().into_inner()).await?
-                } else {
-                    self.get_or_create_index(index_uid.clone(), task.id).await?
+        };
+
+        let updates = tasks
+            .iter()
+            .filter(|t| !t.is_finished())
+            .map(|t| (t.id, get_update(t)))
+            .collect::<Vec<_>>();
+
+        let skipped = Arc::new(Mutex::new(HashSet::new()));
+        let result = spawn_blocking({
+            let skipped = skipped.clone();
+            move || {
+                let current = Arc::new(Mutex::new(None));
+                let updates = uncanceled_updates(updates, canceled, skipped, current.clone());
+
+                // The number of documents reindexed by an update is only known once milli
+                // reports it, so the last reported one is kept for the following steps.
+                let indexing_callback = move |step| {
+                    if let Some(id) = *current.lock().unwrap() {
+                        let total_documents = progress.get(id).map_or(0, |p| p.total_documents);
+                        progress.update(id, TaskProgress::new(step, total_documents));
+                    }
                 };
 
-                let settings = settings.clone();
-                spawn_blocking(move || index.update_settings(&settings.check())).await??;
+                index.apply_updates(updates, indexing_callback)
+            }
+        })
+        .await;
 
-                Ok(TaskResult::Other)
+        let skipped = skipped.lock().unwrap();
+        let mut results = match result {
+            Ok(Ok(results)) => results.into_iter(),
+            Ok(Err(e)) => {
+                let error = ResponseError::from(e);
+                for task in tasks.iter_mut().filter(|t| !t.is_finished()) {
+                    if skipped.contains(&task.id) {
+                        task.events.push(TaskEvent::canceled());
+                    } else {
+                        task.events.push(TaskEvent::failed(error.clone()));
+                    }
+                }
+                return tasks;
             }
-            TaskContent::IndexDeletion { index_uid } => {
+            Err(e) => {
+                let error = ResponseError::from(IndexResolverError::from(e));
+                for task in tasks.iter_mut().filter(|t| !t.is_finished()) {
+                    task.events.push(TaskEvent::failed(error.clone()));
+                }
+                return tasks;
+            }
+        };
+
+        for task in tasks.iter_mut().filter(|t| !t.is_finished()) {
+            if skipped.contains(&task.id) {
+                task.events.push(TaskEvent::canceled());
+                continue;
+            }
+
+            match results.next() {
+                Some(Ok(count)) => {
+                    let result = get_result(task, count);
+                    task.events.push(TaskEvent::succeeded(result));
+                }
+                Some(Err(e)) => task.events.push(TaskEvent::failed(e.into())),
+                None => break,
+            }
+        }
+
+        tasks
+    }
+
+    pub async fn process_task(&self, task: &Task) -> Result<TaskResult> {
+        match &task.content {
+            TaskContent::DocumentAddition { .. }
+            | TaskContent::DocumentDeletion { .. }
+            | TaskContent::DocumentEdition { .. }
+            | TaskContent::SettingsUpdate { .. } => panic!("updates should be handled by batch"),
+            TaskContent::IndexDeletion { index_uid, .. } => {
                 let index = self.delete_index(index_uid.clone().into_inner()).await?;
 
                 let deleted_documents = spawn_blocking(move || -> IndexResult<u64> {
@@ -285,6 +490,7 @@ where
             TaskContent::IndexCreation {
                 primary_key,
                 index_uid,
+                ..
             } => {
                 let index = self.create_index(index_uid.clone(), task.id).await?;
 
@@ -298,6 +504,7 @@ where
             TaskContent::IndexUpdate {
                 primary_key,
                 index_uid,
+                ..
             } => {
                 let index = self.get_index(index_uid.clone().into_inner()).await?;
 
@@ -419,8 +626,59 @@ where
     }
 }
 
+/// Returns the indexing callback of a document addition batch. The progress is reported to the
+/// scheduler through memory only, for each of the tasks whose update files are being indexed.
+///
+/// `documents_counts` maps the update files to their task, along with the number of documents the
+/// task was registered with.
+fn documents_progress(
+    documents_counts: HashMap<Uuid, (TaskId, usize)>,
+    progress: BatchProgress,
+) -> impl Fn(&[Uuid], UpdateIndexingStep) + Sync {
+    move |content_uuids: &[Uuid], step| {
+        let tasks = content_uuids
+            .iter()
+            .filter_map(|content_uuid| documents_counts.get(content_uuid));
+        let total_documents = tasks.clone().map(|(_, count)| count).sum();
+        let task_progress = TaskProgress::new(step, total_documents);
+        for (id, _) in tasks {
+            progress.update(*id, task_progress);
+        }
+    }
+}
+
+/// Lazily pulls the updates of the tasks, like the document additions: the next update is only
+/// pulled once the previous one is applied, so that the tasks canceled in the meantime are
+/// skipped. The skipped tasks are recorded in `skipped`, and the task of the last pulled update in
+/// `current`.
+fn uncanceled_updates<T>(
+    updates: impl IntoIterator<Item = (TaskId, T)>,
+    canceled: CanceledTasks,
+    skipped: Arc<Mutex<HashSet<TaskId>>>,
+    current: Arc<Mutex<Option<TaskId>>>,
+) -> impl Iterator<Item = T> {
+    updates.into_iter().filter_map(move |(id, update)| {
+        if canceled.contains(id) {
+            skipped.lock().unwrap().insert(id);
+            None
+        } else {
+            current.lock().unwrap().replace(id);
+            Some(update)
+        }
+    })
+}
+
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeSet;
+
+    use milli::update::Setting;
+
+    use super::*;
+    use crate::index::Settings;
+    use crate::index_controller::open_meta_env;
+    use crate::tasks::task::{IndexingStep, TaskStatus};
+
     // use std::{collections::BTreeMap, vec::IntoIter};
     //
     // use super::*;
@@ -610,4 +868,193 @@ mod test {
     //         });
     //     }
     // }
+
+    #[test]
+    fn documents_progress_is_reported_for_the_indexed_tasks() {
+        let (first, second, unknown) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let documents_counts = HashMap::from([(first, (1, 10)), (second, (2, 20))]);
+        let progress = BatchProgress::default();
+        let callback = documents_progress(documents_counts, progress.clone());
+
+        // Until milli knows the number of documents, the registered ones are used.
+        callback(
+            &[first],
+            UpdateIndexingStep::RemapDocumentAddition { documents_seen: 4 },
+        );
+        assert_eq!(
+            progress.get(1),
+            Some(TaskProgress {
+                step: IndexingStep::RemapDocumentAddition,
+                documents_processed: 4,
+                total_documents: 10,
+            })
+        );
+        assert_eq!(progress.get(2), None);
+
+        // The tasks indexed together share the same progress.
+        callback(
+            &[first, second, unknown],
+            UpdateIndexingStep::IndexDocuments {
+                documents_seen: 12,
+                total_documents: 25,
+            },
+        );
+        let expected = TaskProgress {
+            step: IndexingStep::IndexDocuments,
+            documents_processed: 12,
+            total_documents: 25,
+        };
+        assert_eq!(progress.get(1), Some(expected));
+        assert_eq!(progress.get(2), Some(expected));
+
+        callback(
+            &[first, second],
+            UpdateIndexingStep::MergeDataIntoFinalDatabase {
+                databases_seen: 1,
+                total_databases: 4,
+            },
+        );
+        let expected = TaskProgress {
+            step: IndexingStep::MergeDataIntoFinalDatabase,
+            documents_processed: 30,
+            total_documents: 30,
+        };
+        assert_eq!(progress.get(1), Some(expected));
+        assert_eq!(progress.get(2), Some(expected));
+    }
+
+    fn settings_task(id: TaskId, attribute: &str, filterable: bool) -> Task {
+        let attributes = Setting::Set(BTreeSet::from([attribute.to_string()]));
+        let settings = if filterable {
+            Settings {
+                filterable_attributes: attributes,
+                ..Default::default()
+            }
+        } else {
+            Settings {
+                sortable_attributes: attributes,
+                ..Default::default()
+            }
+        };
+
+        Task {
+            id,
+            content: TaskContent::SettingsUpdate {
+                index_uid: IndexUid::new_unchecked("test"),
+                settings,
+                is_deletion: false,
+                allow_index_creation: true,
+                priority: Default::default(),
+            },
+            events: Vec::new(),
+        }
+    }
+
+    fn deletion_task(id: TaskId, deletion: DocumentDeletion) -> Task {
+        Task {
+            id,
+            content: TaskContent::DocumentDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+                deletion,
+                priority: Default::default(),
+            },
+            events: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn index_updates_canceled_mid_batch_are_skipped() {
+        let tmp = tempfile::tempdir().unwrap();
+        let indexer_config = IndexerConfig::try_from(&IndexerOpts::default()).unwrap();
+        let index = Index::open(
+            tmp.path(),
+            4096 * 100000,
+            Uuid::new_v4(),
+            Arc::new(indexer_config),
+        )
+        .unwrap();
+
+        let updates = [
+            (1, BatchedUpdate::UpdateSettings(Settings::cleared())),
+            (2, BatchedUpdate::ClearDocuments),
+            (3, BatchedUpdate::ClearDocuments),
+        ];
+        let canceled = CanceledTasks::default();
+        let skipped = Arc::new(Mutex::new(HashSet::new()));
+        let current = Arc::new(Mutex::new(None));
+
+        // The second task is canceled while the first update is being applied.
+        let cancel = canceled.clone();
+        let updates = updates.into_iter().inspect(move |(id, _)| {
+            if *id == 1 {
+                cancel.insert(2);
+            }
+        });
+        let updates = uncanceled_updates(updates, canceled, skipped.clone(), current.clone());
+
+        let results = index.apply_updates(updates, |_| ()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(*skipped.lock().unwrap(), HashSet::from([2]));
+        assert_eq!(*current.lock().unwrap(), Some(3));
+    }
+
+    #[actix_rt::test]
+    async fn index_created_by_settings_update_in_batch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let meta_env = Arc::new(open_meta_env(tmp.path(), 4096 * 100000).unwrap());
+        let file_store = UpdateFileStore::new(tmp.path()).unwrap();
+        let index_resolver = create_index_resolver(
+            tmp.path(),
+            4096 * 100000,
+            &IndexerOpts::default(),
+            meta_env,
+            file_store,
+        )
+        .unwrap();
+
+        let tasks = vec![
+            deletion_task(0, DocumentDeletion::Clear),
+            settings_task(1, "color", true),
+            deletion_task(2, DocumentDeletion::Clear),
+            settings_task(3, "price", false),
+        ];
+        let tasks = index_resolver
+            .process_index_updates_batch(tasks, CanceledTasks::default(), BatchProgress::default())
+            .await;
+
+        // The tasks preceding the settings update fail since the index doesn't exist yet.
+        let statuses: Vec<_> = tasks.iter().map(Task::status).collect();
+        assert_eq!(
+            statuses,
+            [
+                TaskStatus::Failed,
+                TaskStatus::Succeeded,
+                TaskStatus::Succeeded,
+                TaskStatus::Succeeded,
+            ]
+        );
+
+        assert_eq!(
+            index_resolver
+                .get_index_creation_task_id("test".to_string())
+                .await
+                .unwrap(),
+            1
+        );
+        let settings = index_resolver
+            .get_index("test".to_string())
+            .await
+            .unwrap()
+            .settings()
+            .unwrap();
+        assert_eq!(
+            settings.filterable_attributes,
+            Setting::Set(BTreeSet::from(["color".to_string()]))
+        );
+        assert_eq!(
+            settings.sortable_attributes,
+            Setting::Set(BTreeSet::from(["price".to_string()]))
+        );
+    }
 }
@@ -41,27 +41,53 @@ pub struct IndexerOpts {
 
 #[derive(Debug, Clone, Parser, Default, Serialize)]
 pub struct SchedulerConfig {
-    /// enable the autobatching experimental feature
-    #[clap(long, hide = true)]
+    /// Enables autobatching.
+    ///
+    /// Consecutive document additions, document deletions or settings updates of the same index
+    /// are then processed together, in a single batch.
+    #[clap(long, env = "MEILI_ENABLE_AUTO_BATCHING")]
     pub enable_auto_batching: bool,
 
-    // The maximum number of updates of the same type that can be batched together.
-    // If unspecified, this is unlimited. A value of 0 is interpreted as 1.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    /// The maximum number of tasks of the same type that can be batched together.
+    ///
+    /// If unspecified, this is unlimited. A value of 0 is interpreted as 1.
+    #[clap(long, env = "MEILI_MAX_BATCH_SIZE", requires = "enable-auto-batching")]
     pub max_batch_size: Option<usize>,
 
-    // The maximum number of documents in a document batch. Since batches must contain at least one
-    // update for the scheduler to make progress, the number of documents in a batch will be at
-    // least the number of documents of its first update.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    /// The maximum number of documents in a document batch.
+    ///
+    /// Since batches must contain at least one task for the scheduler to make progress, the number
+    /// of documents in a batch will be at least the number of documents of its first task.
+    #[clap(
+        long,
+        env = "MEILI_MAX_DOCUMENTS_PER_BATCH",
+        requires = "enable-auto-batching"
+    )]
     pub max_documents_per_batch: Option<usize>,
 
     /// Debounce duration in seconds
     ///
     /// When a new task is enqueued, the scheduler waits for `debounce_duration_sec` seconds for new updates before
     /// starting to process a batch of updates.
-    #[clap(long, requires = "enable-auto-batching", hide = true)]
+    #[clap(
+        long,
+        env = "MEILI_DEBOUNCE_DURATION_SEC",
+        requires = "enable-auto-batching"
+    )]
     pub debounce_duration_sec: Option<u64>,
+
+    /// The maximum number of batches processed at the same time.
+    ///
+    /// Only batches on different indexes are processed concurrently, and they share the indexing
+    /// threads and memory. A value of 0 is interpreted as 1.
+    #[clap(long, env = "MEILI_MAX_CONCURRENT_BATCHES", default_value = "1")]
+    pub max_concurrent_batches: usize,
+}
+
+impl SchedulerConfig {
+    pub fn concurrent_batches(&self) -> usize {
+        self.max_concurrent_batches.max(1)
+    }
 }
 
 impl TryFrom<&IndexerOpts> for IndexerConfig {
@@ -137,6 +163,14 @@ impl MaxMemory {
     pub fn unlimited() -> Self {
         Self(None)
     }
+
+    /// Returns the share of the memory of one of `parts` indexing processes running concurrently.
+    pub fn split(self, parts: usize) -> Self {
+        Self(
+            self.0
+                .map(|bytes| Byte::from_bytes(bytes.get_bytes() / parts.max(1) as u64)),
+        )
+    }
 }
 
 /// Returns the total amount of bytes available or `None` if this system isn't supported.
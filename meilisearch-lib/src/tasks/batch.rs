@@ -1,8 +1,12 @@
+use std::collections::{HashMap, HashSet};
+use std::slice;
+use std::sync::{Arc, RwLock};
+
 use time::OffsetDateTime;
 
 use crate::snapshot::SnapshotJob;
 
-use super::task::{Task, TaskEvent};
+use super::task::{Task, TaskEvent, TaskId, TaskProgress};
 
 pub type BatchId = u32;
 
@@ -10,6 +14,9 @@ pub type BatchId = u32;
 pub enum BatchContent {
     DocumentsAdditionBatch(Vec<Task>),
     IndexUpdate(Task),
+    /// Consecutive document deletions or settings updates of an index, processed one after the
+    /// other.
+    IndexUpdates(Vec<Task>),
     Dump(Task),
     Snapshot(SnapshotJob),
     // Symbolizes a empty batch. This can occur when we were woken, but there wasn't any work to do.
@@ -19,15 +26,23 @@ pub enum BatchContent {
 impl BatchContent {
     pub fn first(&self) -> Option<&Task> {
         match self {
-            BatchContent::DocumentsAdditionBatch(ts) => ts.first(),
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::IndexUpdates(ts) => ts.first(),
             BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => Some(t),
             BatchContent::Snapshot(_) | BatchContent::Empty => None,
         }
     }
 
+    pub fn tasks(&self) -> &[Task] {
+        match self {
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::IndexUpdates(ts) => ts,
+            BatchContent::Dump(t) | BatchContent::IndexUpdate(t) => slice::from_ref(t),
+            BatchContent::Snapshot(_) | BatchContent::Empty => &[],
+        }
+    }
+
     pub fn push_event(&mut self, event: TaskEvent) {
         match self {
-            BatchContent::DocumentsAdditionBatch(ts) => {
+            BatchContent::DocumentsAdditionBatch(ts) | BatchContent::IndexUpdates(ts) => {
                 ts.iter_mut().for_each(|t| t.events.push(event.clone()))
             }
             BatchContent::IndexUpdate(t) | BatchContent::Dump(t) => t.events.push(event),
@@ -36,6 +51,40 @@ impl BatchContent {
     }
 }
 
+/// The ids of the tasks of a batch that were canceled while the batch was being processed.
+///
+/// This set is shared between the scheduler and the batch handlers, so that the handlers can skip
+/// the canceled tasks that they haven't started processing yet.
+#[derive(Debug, Clone, Default)]
+pub struct CanceledTasks(Arc<RwLock<HashSet<TaskId>>>);
+
+impl CanceledTasks {
+    pub fn insert(&self, id: TaskId) {
+        self.0.write().unwrap().insert(id);
+    }
+
+    pub fn contains(&self, id: TaskId) -> bool {
+        self.0.read().unwrap().contains(&id)
+    }
+}
+
+/// The progress of the tasks of the batch being processed, as reported by the batch handler.
+///
+/// Like `CanceledTasks`, it is shared between the scheduler and the batch handlers. It is only kept
+/// in memory: the tasks aren't written to the store on every indexing step.
+#[derive(Debug, Clone, Default)]
+pub struct BatchProgress(Arc<RwLock<HashMap<TaskId, TaskProgress>>>);
+
+impl BatchProgress {
+    pub fn update(&self, id: TaskId, progress: TaskProgress) {
+        self.0.write().unwrap().insert(id, progress);
+    }
+
+    pub fn get(&self, id: TaskId) -> Option<TaskProgress> {
+        self.0.read().unwrap().get(&id).copied()
+    }
+}
+
 #[derive(Debug)]
 pub struct Batch {
     // Only batches that contains a persistant tasks are given an id. Snapshot batches don't have
@@ -43,6 +92,8 @@ pub struct Batch {
     pub id: Option<BatchId>,
     pub created_at: OffsetDateTime,
     pub content: BatchContent,
+    pub canceled: CanceledTasks,
+    pub progress: BatchProgress,
 }
 
 impl Batch {
@@ -51,11 +102,15 @@ impl Batch {
             id,
             created_at: OffsetDateTime::now_utc(),
             content,
+            canceled: CanceledTasks::default(),
+            progress: BatchProgress::default(),
         }
     }
     pub fn len(&self) -> usize {
         match self.content {
-            BatchContent::DocumentsAdditionBatch(ref ts) => ts.len(),
+            BatchContent::DocumentsAdditionBatch(ref ts) | BatchContent::IndexUpdates(ref ts) => {
+                ts.len()
+            }
             BatchContent::IndexUpdate(_) | BatchContent::Dump(_) | BatchContent::Snapshot(_) => 1,
             BatchContent::Empty => 0,
         }
@@ -70,6 +125,8 @@ impl Batch {
             id: None,
             created_at: OffsetDateTime::now_utc(),
             content: BatchContent::Empty,
+            canceled: CanceledTasks::default(),
+            progress: BatchProgress::default(),
         }
     }
 }
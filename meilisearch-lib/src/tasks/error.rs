@@ -1,8 +1,10 @@
 use meilisearch_error::{internal_error, Code, ErrorCode};
 use tokio::task::JoinError;
+use uuid::Uuid;
 
 use crate::update_file_store::UpdateFileStoreError;
 
+use super::batch::BatchId;
 use super::task::TaskId;
 
 pub type Result<T> = std::result::Result<T, TaskError>;
@@ -11,6 +13,12 @@ pub type Result<T> = std::result::Result<T, TaskError>;
 pub enum TaskError {
     #[error("Task `{0}` not found.")]
     UnexistingTask(TaskId),
+    #[error("Batch `{0}` not found.")]
+    UnexistingBatch(BatchId),
+    #[error("Webhook `{0}` not found.")]
+    UnexistingWebhook(Uuid),
+    #[error("`{0}` is not a valid webhook url. It must be an absolute `http` or `https` url.")]
+    InvalidWebhookUrl(String),
     #[error("Internal error: {0}")]
     Internal(Box<dyn std::error::Error + Send + Sync + 'static>),
 }
@@ -27,6 +35,9 @@ impl ErrorCode for TaskError {
     fn error_code(&self) -> Code {
         match self {
             TaskError::UnexistingTask(_) => Code::TaskNotFound,
+            TaskError::UnexistingBatch(_) => Code::BatchNotFound,
+            TaskError::UnexistingWebhook(_) => Code::WebhookNotFound,
+            TaskError::InvalidWebhookUrl(_) => Code::InvalidWebhookUrl,
             TaskError::Internal(_) => Code::Internal,
         }
     }
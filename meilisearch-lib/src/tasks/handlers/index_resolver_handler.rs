@@ -13,7 +13,9 @@ where
     fn accept(&self, batch: &Batch) -> bool {
         matches!(
             batch.content,
-            BatchContent::DocumentsAdditionBatch(_) | BatchContent::IndexUpdate(_)
+            BatchContent::DocumentsAdditionBatch(_)
+                | BatchContent::IndexUpdate(_)
+                | BatchContent::IndexUpdates(_)
         )
     }
 
@@ -21,13 +23,26 @@ where
         match batch.content {
             BatchContent::DocumentsAdditionBatch(ref mut tasks) => {
                 *tasks = self
-                    .process_document_addition_batch(std::mem::take(tasks))
+                    .process_document_addition_batch(
+                        std::mem::take(tasks),
+                        batch.canceled.clone(),
+                        batch.progress.clone(),
+                    )
                     .await;
             }
             BatchContent::IndexUpdate(ref mut task) => match self.process_task(task).await {
                 Ok(success) => task.events.push(TaskEvent::succeeded(success)),
                 Err(err) => task.events.push(TaskEvent::failed(err.into())),
             },
+            BatchContent::IndexUpdates(ref mut tasks) => {
+                *tasks = self
+                    .process_index_updates_batch(
+                        std::mem::take(tasks),
+                        batch.canceled.clone(),
+                        batch.progress.clone(),
+                    )
+                    .await;
+            }
             _ => unreachable!(),
         }
 
@@ -78,7 +93,8 @@ mod test {
 
             match batch.content {
                 BatchContent::DocumentsAdditionBatch(_)
-                    | BatchContent::IndexUpdate(_) => assert!(index_resolver.accept(&batch)),
+                    | BatchContent::IndexUpdate(_)
+                    | BatchContent::IndexUpdates(_) => assert!(index_resolver.accept(&batch)),
                 BatchContent::Dump(_)
                     | BatchContent::Snapshot(_)
                     | BatchContent::Empty => assert!(!index_resolver.accept(&batch)),
@@ -107,10 +123,12 @@ mod test {
             content: TaskContent::DocumentAddition {
                 content_uuid,
                 merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+                merge_patch: false,
                 primary_key: None,
                 documents_count: 100,
                 allow_index_creation: true,
                 index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
             },
             events: Vec::new(),
         };
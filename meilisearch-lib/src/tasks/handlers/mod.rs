@@ -18,8 +18,9 @@ mod test {
                 BatchContent::DocumentsAdditionBatch(vec![task])
             }
             TaskContent::DocumentDeletion { .. }
-            | TaskContent::SettingsUpdate { .. }
-            | TaskContent::IndexDeletion { .. }
+            | TaskContent::DocumentEdition { .. }
+            | TaskContent::SettingsUpdate { .. } => BatchContent::IndexUpdates(vec![task]),
+            TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
             | TaskContent::IndexUpdate { .. } => BatchContent::IndexUpdate(task),
             TaskContent::Dump { .. } => BatchContent::Dump(task),
@@ -29,6 +30,8 @@ mod test {
             id: Some(1),
             created_at: OffsetDateTime::now_utc(),
             content,
+            canceled: Default::default(),
+            progress: Default::default(),
         }
     }
 }
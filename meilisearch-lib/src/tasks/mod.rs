@@ -2,7 +2,7 @@ use async_trait::async_trait;
 
 pub use handlers::empty_handler::EmptyBatchHandler;
 pub use handlers::snapshot_handler::SnapshotHandler;
-pub use scheduler::Scheduler;
+pub use scheduler::{Scheduler, TaskCancelation};
 pub use task_store::TaskFilter;
 
 #[cfg(test)]
@@ -16,10 +16,12 @@ use error::Result;
 pub mod batch;
 pub mod error;
 mod handlers;
+pub mod retention;
 mod scheduler;
 pub mod task;
 mod task_store;
 pub mod update_loop;
+pub mod webhooks;
 
 #[cfg_attr(test, mockall::automock(type Error=test::DebugError;))]
 #[async_trait]
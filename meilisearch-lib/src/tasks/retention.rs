@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime};
+
+use log::{error, info};
+use tokio::time::sleep;
+
+use crate::update_file_store::UpdateFileStore;
+
+use super::task::Task;
+use super::TaskStore;
+
+/// Interval between two enforcements of the retention policy.
+const RETENTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// An update file is only considered orphaned when it was left untouched for that long. This leaves
+/// time to the task referring to a freshly persisted update file to be registered.
+const ORPHAN_UPDATE_FILE_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// Defines which finished tasks are kept in the task store.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RetentionPolicy {
+    /// Finished tasks are deleted once they were finished for longer than this period.
+    pub retention_period: Option<Duration>,
+    /// Only this number of the most recent finished tasks are kept.
+    pub max_finished_tasks: Option<usize>,
+}
+
+impl RetentionPolicy {
+    pub fn is_enabled(&self) -> bool {
+        self.retention_period.is_some() || self.max_finished_tasks.is_some()
+    }
+}
+
+pub struct RetentionService {
+    pub(crate) policy: RetentionPolicy,
+    pub(crate) task_store: TaskStore,
+    pub(crate) update_file_store: UpdateFileStore,
+}
+
+impl RetentionService {
+    pub async fn run(self) {
+        info!(
+            "Task retention policy enforced every {}s.",
+            RETENTION_INTERVAL.as_secs()
+        );
+        loop {
+            if let Err(e) = self.enforce().await {
+                error!("error while enforcing the task retention policy: {}", e);
+            }
+            sleep(RETENTION_INTERVAL).await;
+        }
+    }
+
+    async fn enforce(&self) -> anyhow::Result<()> {
+        let pruned = self.task_store.prune(self.policy).await?;
+        if !pruned.is_empty() {
+            info!("Deleted {} tasks from the task store.", pruned.len());
+        }
+
+        // The update files of the finished tasks are deleted once the tasks are processed, but they
+        // may be left behind when meilisearch was stopped in the meantime.
+        let pending_updates = self
+            .task_store
+            .fetch_unfinished_tasks(None)
+            .await?
+            .iter()
+            .filter_map(Task::get_content_uuid)
+            .collect::<HashSet<_>>();
+
+        let modified_before = SystemTime::now() - ORPHAN_UPDATE_FILE_DELAY;
+        for uuid in self.update_file_store.list_updates(modified_before)? {
+            if !pending_updates.contains(&uuid) {
+                if let Err(e) = self.update_file_store.delete(uuid).await {
+                    error!("error deleting orphaned update file: {}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
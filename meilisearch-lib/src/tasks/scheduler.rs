@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::{hash_map::Entry, BinaryHeap, HashMap, VecDeque};
+use std::collections::{hash_map::Entry, BinaryHeap, HashMap, HashSet, VecDeque};
 use std::ops::{Deref, DerefMut};
 use std::slice;
 use std::sync::Arc;
@@ -8,21 +8,29 @@ use std::time::Duration;
 use atomic_refcell::AtomicRefCell;
 use milli::update::IndexDocumentsMethod;
 use time::OffsetDateTime;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 
 use crate::options::SchedulerConfig;
 use crate::snapshot::SnapshotJob;
 
-use super::batch::{Batch, BatchContent};
+use super::batch::{Batch, BatchContent, BatchId, BatchProgress, CanceledTasks};
 use super::error::Result;
-use super::task::{Task, TaskContent, TaskEvent, TaskId};
+use super::task::{Task, TaskContent, TaskEvent, TaskId, TaskPriority, TaskProgress};
 use super::update_loop::UpdateLoop;
 use super::{BatchHandler, TaskFilter, TaskStore};
 
+/// The number of task state changes that can be buffered for a subscriber before it starts
+/// missing some.
+const TASK_EVENTS_CAPACITY: usize = 1024;
+
 #[derive(Eq, Debug, Clone, Copy)]
 enum TaskType {
     DocumentAddition { number: usize },
     DocumentUpdate { number: usize },
+    DocumentMergePatch { number: usize },
+    DocumentDeletion,
+    DocumentEdition,
+    SettingsUpdate,
     IndexUpdate,
     Dump,
 }
@@ -34,6 +42,13 @@ impl PartialEq for TaskType {
             (self, other),
             (Self::DocumentAddition { .. }, Self::DocumentAddition { .. })
                 | (Self::DocumentUpdate { .. }, Self::DocumentUpdate { .. })
+                | (
+                    Self::DocumentMergePatch { .. },
+                    Self::DocumentMergePatch { .. }
+                )
+                | (Self::DocumentDeletion, Self::DocumentDeletion)
+                | (Self::DocumentEdition, Self::DocumentEdition)
+                | (Self::SettingsUpdate, Self::SettingsUpdate)
         )
     }
 }
@@ -42,6 +57,7 @@ impl PartialEq for TaskType {
 struct PendingTask {
     kind: TaskType,
     id: TaskId,
+    priority: TaskPriority,
 }
 
 impl PartialEq for PendingTask {
@@ -107,7 +123,11 @@ impl Ord for TaskList {
                     (None, None) => Ordering::Equal,
                     (None, Some(_)) => Ordering::Less,
                     (Some(_), None) => Ordering::Greater,
-                    (Some(lhs), Some(rhs)) => lhs.cmp(rhs),
+                    // The index whose next task has the highest priority goes first, and the
+                    // oldest task breaks the ties.
+                    (Some(lhs), Some(rhs)) => {
+                        lhs.priority.cmp(&rhs.priority).then_with(|| lhs.cmp(rhs))
+                    }
                 }
             }
             (TaskListIdentifier::Index(_), TaskListIdentifier::Dump) => Ordering::Less,
@@ -136,8 +156,9 @@ impl From<&Task> for TaskListIdentifier {
         match &task.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
-            | TaskContent::IndexDeletion { index_uid }
+            | TaskContent::IndexDeletion { index_uid, .. }
             | TaskContent::IndexCreation { index_uid, .. }
             | TaskContent::IndexUpdate { index_uid, .. } => {
                 TaskListIdentifier::Index(index_uid.as_str().to_string())
@@ -151,14 +172,18 @@ impl From<&Task> for TaskListIdentifier {
 struct TaskQueue {
     /// Maps index uids to their TaskList, for quick access
     index_tasks: HashMap<TaskListIdentifier, Arc<AtomicRefCell<TaskList>>>,
-    /// A queue that orders TaskList by the priority of their fist update
+    /// A queue that orders TaskList by the priority of their fist update, then by its id
     queue: BinaryHeap<Arc<AtomicRefCell<TaskList>>>,
+    /// The TaskLists a batch is being processed from. They are kept out of the queue until the
+    /// batch is finished, so that the tasks of an index are processed in order.
+    locked: HashSet<TaskListIdentifier>,
 }
 
 impl TaskQueue {
     fn insert(&mut self, task: Task) {
         let id = task.id;
         let uid = TaskListIdentifier::from(&task);
+        let priority = task.content.priority();
 
         let kind = match task.content {
             TaskContent::DocumentAddition {
@@ -168,6 +193,14 @@ impl TaskQueue {
             } => TaskType::DocumentAddition {
                 number: documents_count,
             },
+            TaskContent::DocumentAddition {
+                documents_count,
+                merge_strategy: IndexDocumentsMethod::UpdateDocuments,
+                merge_patch: true,
+                ..
+            } => TaskType::DocumentMergePatch {
+                number: documents_count,
+            },
             TaskContent::DocumentAddition {
                 documents_count,
                 merge_strategy: IndexDocumentsMethod::UpdateDocuments,
@@ -176,14 +209,15 @@ impl TaskQueue {
                 number: documents_count,
             },
             TaskContent::Dump { .. } => TaskType::Dump,
-            TaskContent::DocumentDeletion { .. }
-            | TaskContent::SettingsUpdate { .. }
-            | TaskContent::IndexDeletion { .. }
+            TaskContent::DocumentDeletion { .. } => TaskType::DocumentDeletion,
+            TaskContent::DocumentEdition { .. } => TaskType::DocumentEdition,
+            TaskContent::SettingsUpdate { .. } => TaskType::SettingsUpdate,
+            TaskContent::IndexDeletion { .. }
             | TaskContent::IndexCreation { .. }
             | TaskContent::IndexUpdate { .. } => TaskType::IndexUpdate,
             _ => unreachable!("unhandled task type"),
         };
-        let task = PendingTask { kind, id };
+        let task = PendingTask { kind, id, priority };
 
         match self.index_tasks.entry(uid) {
             Entry::Occupied(entry) => {
@@ -203,45 +237,150 @@ impl TaskQueue {
                 task_list.push(task);
                 let task_list = Arc::new(AtomicRefCell::new(task_list));
                 entry.insert(task_list.clone());
-                self.queue.push(task_list);
+                if !self.locked.contains(&task_list.borrow().id) {
+                    self.queue.push(task_list);
+                }
             }
         }
     }
 
     /// Passes a context with a view to the task list of the next index to schedule. It is
-    /// guaranteed that the first id from task list will be the lowest pending task id.
+    /// guaranteed that the first task from the task list has the highest priority among the next
+    /// tasks of every index, and the lowest id among those of the same priority.
     fn head_mut<R>(&mut self, mut f: impl FnMut(&mut TaskList) -> R) -> Option<R> {
         let head = self.queue.pop()?;
         let result = {
             let mut ref_head = head.borrow_mut();
             f(&mut *ref_head)
         };
-        if !head.borrow().tasks.is_empty() {
+        if head.borrow().tasks.is_empty() {
+            self.index_tasks.remove(&head.borrow().id);
+        } else if !self.locked.contains(&head.borrow().id) {
             // After being mutated, the head is reinserted to the correct position.
             self.queue.push(head);
-        } else {
-            self.index_tasks.remove(&head.borrow().id);
         }
 
         Some(result)
     }
 
+    /// Returns the identifier of the task list that will be scheduled next.
+    fn peek_head(&self) -> Option<TaskListIdentifier> {
+        self.queue.peek().map(|list| list.borrow().id.clone())
+    }
+
+    /// Prevents a task list from being scheduled until it is unlocked.
+    fn lock(&mut self, id: TaskListIdentifier) {
+        if self.locked.insert(id.clone()) {
+            let queue = std::mem::take(&mut self.queue);
+            self.queue = queue
+                .into_iter()
+                .filter(|list| list.borrow().id != id)
+                .collect();
+        }
+    }
+
+    /// Allows a locked task list to be scheduled again.
+    fn unlock(&mut self, id: &TaskListIdentifier) {
+        if self.locked.remove(id) {
+            if let Some(list) = self.index_tasks.get(id) {
+                self.queue.push(list.clone());
+            }
+        }
+    }
+
+    /// Removes the pending task with the given id from the queue. Returns whether the task was
+    /// found.
+    fn remove(&mut self, id: TaskId) -> bool {
+        let mut found = false;
+        for list in self.index_tasks.values() {
+            let mut list = list.borrow_mut();
+            if list.iter().any(|pending| pending.id == id) {
+                let tasks = std::mem::take(&mut list.tasks);
+                list.tasks = tasks
+                    .into_iter()
+                    .filter(|pending| pending.id != id)
+                    .collect();
+                found = true;
+                break;
+            }
+        }
+
+        if found {
+            // The head of a list may have changed, so the queue must be rebuilt to keep the lists
+            // ordered. Lists that are now empty are dropped.
+            self.index_tasks.retain(|_, list| !list.borrow().is_empty());
+            self.queue = self
+                .index_tasks
+                .values()
+                .filter(|list| !self.locked.contains(&list.borrow().id))
+                .cloned()
+                .collect();
+        }
+
+        found
+    }
+
+    /// Returns the ids of the tasks waiting in the queue, including those of the locked task
+    /// lists.
+    fn pending_ids(&self) -> impl Iterator<Item = TaskId> + '_ {
+        self.index_tasks.values().flat_map(|list| {
+            list.borrow()
+                .iter()
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>()
+        })
+    }
+
     pub fn is_empty(&self) -> bool {
         self.queue.is_empty() && self.index_tasks.is_empty()
     }
 }
 
+/// A batch handed to the update loop, that isn't finished yet.
+#[derive(Debug)]
+struct ProcessingBatch {
+    id: Option<BatchId>,
+    /// The task list the batch was made from, or `None` for the snapshots.
+    list: Option<TaskListIdentifier>,
+    tasks: Processing,
+    /// The tasks of the batch that were canceled while it was processed.
+    canceled: CanceledTasks,
+    /// The indexing progress of the batch, updated by its handler.
+    progress: BatchProgress,
+}
+
+impl ProcessingBatch {
+    /// Dumps and snapshots must be processed alone, since they read all the indexes.
+    fn is_exclusive(&self) -> bool {
+        !matches!(self.list, Some(TaskListIdentifier::Index(_)))
+    }
+}
+
+/// The outcome of a task cancelation.
+#[derive(Debug, Default)]
+pub struct TaskCancelation {
+    /// The enqueued tasks, that were canceled.
+    pub canceled: Vec<Task>,
+    /// The ids of the processing tasks that will be canceled if they weren't processed yet.
+    pub processing: Vec<TaskId>,
+    /// The ids of the processing tasks that can't be canceled, like the tasks of a dump.
+    pub not_cancelable: Vec<TaskId>,
+}
+
 pub struct Scheduler {
     // TODO: currently snapshots are non persistent tasks, and are treated differently.
     snapshots: VecDeque<SnapshotJob>,
     tasks: TaskQueue,
 
     store: TaskStore,
-    processing: Processing,
+    /// The batches being processed. Batches on different indexes are processed concurrently.
+    processing: Vec<ProcessingBatch>,
     next_fetched_task_id: TaskId,
     config: SchedulerConfig,
     /// Notifies the update loop that a new task was received
     notifier: watch::Sender<()>,
+    /// Broadcasts the tasks whose state was changed by the update loop.
+    events: broadcast::Sender<Task>,
 }
 
 impl Scheduler {
@@ -251,6 +390,7 @@ impl Scheduler {
         mut config: SchedulerConfig,
     ) -> Result<Arc<RwLock<Self>>> {
         let (notifier, rcv) = watch::channel(());
+        let (events, _) = broadcast::channel(TASK_EVENTS_CAPACITY);
 
         let debounce_time = config.debounce_duration_sec;
 
@@ -264,10 +404,11 @@ impl Scheduler {
             tasks: TaskQueue::default(),
 
             store,
-            processing: Processing::Nothing,
+            processing: Vec::new(),
             next_fetched_task_id: 0,
             config,
             notifier,
+            events: events.clone(),
         };
 
         // Notify update loop to start processing pending updates immediately after startup.
@@ -280,6 +421,7 @@ impl Scheduler {
             performers,
             debounce_time.filter(|&v| v > 0).map(Duration::from_secs),
             rcv,
+            events,
         );
 
         tokio::task::spawn_local(update_loop.run());
@@ -292,15 +434,29 @@ impl Scheduler {
         self.tasks.insert(task);
     }
 
-    /// Clears the processing list, this method should be called when the processing of a batch is finished.
-    pub fn finish(&mut self) {
-        self.processing = Processing::Nothing;
+    /// Removes a batch from the processing list, this method should be called when the processing
+    /// of a batch is finished. The index it was made from can then be scheduled again.
+    pub fn finish(&mut self, batch_id: Option<BatchId>) {
+        if let Some(position) = self.processing.iter().position(|b| b.id == batch_id) {
+            let batch = self.processing.remove(position);
+            if let Some(list) = batch.list {
+                self.tasks.unlock(&list);
+            }
+        }
+
+        // A slot was freed, or the barrier of a dump or a snapshot was lifted.
+        self.notify_if_not_empty();
     }
 
     pub fn notify(&self) {
         let _ = self.notifier.send(());
     }
 
+    /// Subscribes to the state changes of the tasks, as they are recorded by the update loop.
+    pub fn subscribe(&self) -> broadcast::Receiver<Task> {
+        self.events.subscribe()
+    }
+
     fn notify_if_not_empty(&self) {
         if !self.snapshots.is_empty() || !self.tasks.is_empty() {
             self.notify();
@@ -317,6 +473,10 @@ impl Scheduler {
                 let mut tasks = self.store.update_tasks(vec![t]).await?;
                 Ok(BatchContent::IndexUpdate(tasks.remove(0)))
             }
+            BatchContent::IndexUpdates(tasks) => {
+                let tasks = self.store.update_tasks(tasks).await?;
+                Ok(BatchContent::IndexUpdates(tasks))
+            }
             BatchContent::Dump(t) => {
                 let mut tasks = self.store.update_tasks(vec![t]).await?;
                 Ok(BatchContent::Dump(tasks.remove(0)))
@@ -341,7 +501,7 @@ impl Scheduler {
     pub async fn get_processing_tasks(&self) -> Result<Vec<Task>> {
         let mut tasks = Vec::new();
 
-        for id in self.processing.ids() {
+        for id in self.processing.iter().flat_map(|batch| batch.tasks.ids()) {
             let task = self.store.get_task(id, None).await?;
             tasks.push(task);
         }
@@ -349,6 +509,71 @@ impl Scheduler {
         Ok(tasks)
     }
 
+    /// Returns the progress of a processing task, if its handler reported any.
+    pub fn task_progress(&self, id: TaskId) -> Option<TaskProgress> {
+        self.processing
+            .iter()
+            .find(|batch| batch.tasks.ids().any(|task_id| task_id == id))
+            .and_then(|batch| batch.progress.get(id))
+    }
+
+    /// Cancels the unfinished tasks matching the filter.
+    ///
+    /// Enqueued tasks are removed from the queue and marked as canceled right away. Tasks that
+    /// belong to a document addition, document deletion or settings update batch being processed
+    /// can't be canceled synchronously: they are flagged instead, and the batch handler skips them
+    /// if it hasn't started processing them yet. The tasks of the other processing batches can't
+    /// be canceled at all.
+    pub async fn cancel_tasks(&mut self, mut filter: TaskFilter) -> Result<TaskCancelation> {
+        // Make sure all the enqueued tasks are in the queue before removing them from it.
+        self.fetch_pending_tasks().await?;
+
+        // Only the tasks known to the scheduler can be canceled, so there is no need to go through
+        // the finished ones.
+        let unfinished = self
+            .tasks
+            .pending_ids()
+            .chain(self.processing.iter().flat_map(|batch| batch.tasks.ids()))
+            .collect();
+        filter.restrict_uids(unfinished);
+
+        let tasks = self.store.list_tasks(None, Some(filter), None).await?;
+
+        let mut cancelation = TaskCancelation::default();
+        let mut canceled = Vec::new();
+        for mut task in tasks.into_iter().filter(|t| !t.is_finished()) {
+            let batch = self
+                .processing
+                .iter()
+                .find(|batch| batch.tasks.ids().any(|id| id == task.id));
+            match batch {
+                Some(batch)
+                    if matches!(
+                        batch.tasks,
+                        Processing::DocumentAdditions(_) | Processing::IndexUpdates(_)
+                    ) =>
+                {
+                    batch.canceled.insert(task.id);
+                    cancelation.processing.push(task.id);
+                }
+                Some(_) => cancelation.not_cancelable.push(task.id),
+                None => {
+                    if self.tasks.remove(task.id) {
+                        task.events.push(TaskEvent::canceled());
+                        canceled.push(task);
+                    }
+                }
+            }
+        }
+
+        cancelation.canceled = self.store.update_tasks(canceled).await?;
+        for task in &cancelation.canceled {
+            let _ = self.events.send(task.clone());
+        }
+
+        Ok(cancelation)
+    }
+
     pub fn schedule_snapshot(&mut self, job: SnapshotJob) {
         self.snapshots.push_back(job);
         self.notify();
@@ -367,10 +592,32 @@ impl Scheduler {
         Ok(())
     }
 
-    /// Prepare the next batch, and set `processing` to the ids in that batch.
+    /// Prepare the next batch, and add the ids in that batch to `processing`.
+    ///
+    /// An empty batch is returned when there is nothing to do, when all the processing slots are
+    /// taken, or when the next batch must wait for the processing ones to finish. `finish` notifies
+    /// the update loop once the batch can be prepared.
     pub async fn prepare(&mut self) -> Result<Batch> {
-        // If there is a job to process, do it first.
-        if let Some(job) = self.snapshots.pop_front() {
+        if self.processing.len() >= self.config.concurrent_batches()
+            || self.processing.iter().any(ProcessingBatch::is_exclusive)
+        {
+            return Ok(Batch::empty());
+        }
+
+        // If there is a job to process, do it first, once the processing batches are finished.
+        if !self.snapshots.is_empty() {
+            if !self.processing.is_empty() {
+                return Ok(Batch::empty());
+            }
+
+            let job = self.snapshots.pop_front().unwrap();
+            self.processing.push(ProcessingBatch {
+                id: None,
+                list: None,
+                tasks: Processing::Nothing,
+                canceled: CanceledTasks::default(),
+                progress: BatchProgress::default(),
+            });
             // There is more work to do, notify the update loop
             self.notify_if_not_empty();
             let batch = Batch::new(None, BatchContent::Snapshot(job));
@@ -380,15 +627,19 @@ impl Scheduler {
         // Try to fill the queue with pending tasks.
         self.fetch_pending_tasks().await?;
 
-        self.processing = make_batch(&mut self.tasks, &self.config);
+        // A dump waits for the processing batches to finish. Since the dumps are scheduled first,
+        // no other batch is started in the meantime.
+        let list = self.tasks.peek_head();
+        if list == Some(TaskListIdentifier::Dump) && !self.processing.is_empty() {
+            return Ok(Batch::empty());
+        }
+
+        let processing = make_batch(&mut self.tasks, &self.config);
 
-        log::debug!("prepared batch with {} tasks", self.processing.len());
+        log::debug!("prepared batch with {} tasks", processing.len());
 
-        if !self.processing.is_nothing() {
-            let (processing, mut content) = self
-                .store
-                .get_processing_tasks(std::mem::take(&mut self.processing))
-                .await?;
+        if !processing.is_nothing() {
+            let (processing, mut content) = self.store.get_processing_tasks(processing).await?;
 
             // The batch id is the id of the first update it contains. At this point we must have a
             // valid batch that contains at least 1 task.
@@ -402,9 +653,19 @@ impl Scheduler {
                 timestamp: OffsetDateTime::now_utc(),
             });
 
-            self.processing = processing;
+            // The tasks of an index must be processed in order, so its task list isn't scheduled
+            // again until this batch is finished.
+            let list = list.expect("a batch was made from an empty queue");
+            self.tasks.lock(list.clone());
 
             let batch = Batch::new(Some(id), content);
+            self.processing.push(ProcessingBatch {
+                id: Some(id),
+                list: Some(list),
+                tasks: processing,
+                canceled: batch.canceled.clone(),
+                progress: batch.progress.clone(),
+            });
 
             // There is more work to do, notify the update loop
             self.notify_if_not_empty();
@@ -420,6 +681,8 @@ impl Scheduler {
 pub enum Processing {
     DocumentAdditions(Vec<TaskId>),
     IndexUpdate(TaskId),
+    /// Consecutive document deletions or settings updates of the same kind.
+    IndexUpdates(Vec<TaskId>),
     Dump(TaskId),
     /// Variant used when there is nothing to process.
     Nothing,
@@ -454,7 +717,9 @@ impl Processing {
 
     pub fn ids(&self) -> impl Iterator<Item = TaskId> + '_ {
         match self {
-            Processing::DocumentAdditions(v) => ProcessingIter::Many(v.iter()),
+            Processing::DocumentAdditions(v) | Processing::IndexUpdates(v) => {
+                ProcessingIter::Many(v.iter())
+            }
             Processing::IndexUpdate(id) | Processing::Dump(id) => ProcessingIter::Single(Some(*id)),
             Processing::Nothing => ProcessingIter::Single(None),
         }
@@ -462,7 +727,7 @@ impl Processing {
 
     pub fn len(&self) -> usize {
         match self {
-            Processing::DocumentAdditions(v) => v.len(),
+            Processing::DocumentAdditions(v) | Processing::IndexUpdates(v) => v.len(),
             Processing::IndexUpdate(_) | Processing::Dump(_) => 1,
             Processing::Nothing => 0,
         }
@@ -480,6 +745,7 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             Some(PendingTask {
                 kind: TaskType::IndexUpdate,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::IndexUpdate(id)
@@ -487,6 +753,7 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
             Some(PendingTask {
                 kind: TaskType::Dump,
                 id,
+                ..
             }) => {
                 list.pop();
                 Processing::Dump(id)
@@ -510,7 +777,8 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
                             // We check that bound only after adding the current task to the batch, so that a batch contains at least one task.
                             match pending.kind {
                                 TaskType::DocumentUpdate { number }
-                                | TaskType::DocumentAddition { number } => {
+                                | TaskType::DocumentAddition { number }
+                                | TaskType::DocumentMergePatch { number } => {
                                     doc_count += number;
 
                                     if doc_count
@@ -525,7 +793,14 @@ fn make_batch(tasks: &mut TaskQueue, config: &SchedulerConfig) -> Processing {
                         _ => break,
                     }
                 }
-                Processing::DocumentAdditions(task_list)
+                match kind {
+                    TaskType::DocumentAddition { .. }
+                    | TaskType::DocumentUpdate { .. }
+                    | TaskType::DocumentMergePatch { .. } => {
+                        Processing::DocumentAdditions(task_list)
+                    }
+                    _ => Processing::IndexUpdates(task_list),
+                }
             }
             None => Processing::Nothing,
         })
@@ -537,7 +812,10 @@ mod test {
     use milli::update::IndexDocumentsMethod;
     use uuid::Uuid;
 
-    use crate::{index_resolver::IndexUid, tasks::task::TaskContent};
+    use crate::{
+        index_resolver::IndexUid,
+        tasks::task::{DocumentDeletion, TaskContent},
+    };
 
     use super::*;
 
@@ -553,13 +831,13 @@ mod test {
     #[rustfmt::skip]
     fn register_updates_multiples_indexes() {
         let mut queue = TaskQueue::default();
-        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
-        queue.insert(gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
-        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
-        queue.insert(gen_task(3, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
-        queue.insert(gen_task(4, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
-        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1") }));
-        queue.insert(gen_task(6, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2") }));
+        queue.insert(gen_task(0, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1"), priority: Default::default() }));
+        queue.insert(gen_task(1, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default() }));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default() }));
+        queue.insert(gen_task(3, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default() }));
+        queue.insert(gen_task(4, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1"), priority: Default::default() }));
+        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1"), priority: Default::default() }));
+        queue.insert(gen_task(6, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default() }));
 
         let test1_tasks = queue
             .head_mut(|tasks| tasks.drain().map(|t| t.id).collect::<Vec<_>>())
@@ -581,10 +859,12 @@ mod test {
         TaskContent::DocumentAddition {
             content_uuid: Uuid::new_v4(),
             merge_strategy: IndexDocumentsMethod::ReplaceDocuments,
+            merge_patch: false,
             primary_key: Some("test".to_string()),
             documents_count: 0,
             allow_index_creation: true,
             index_uid: IndexUid::new_unchecked(index_uid),
+            priority: Default::default(),
         }
     }
 
@@ -594,10 +874,10 @@ mod test {
         let mut queue = TaskQueue::default();
         queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
         queue.insert(gen_task(1, gen_doc_addition_task_content("test2")));
-        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2")}));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default()}));
         queue.insert(gen_task(3, gen_doc_addition_task_content("test2")));
         queue.insert(gen_task(4, gen_doc_addition_task_content("test1")));
-        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1")}));
+        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1"), priority: Default::default()}));
         queue.insert(gen_task(6, gen_doc_addition_task_content("test2")));
         queue.insert(gen_task(7, gen_doc_addition_task_content("test1")));
         queue.insert(gen_task(8, TaskContent::Dump { uid: "adump".to_owned() }));
@@ -628,4 +908,167 @@ mod test {
 
         assert!(queue.is_empty());
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_index_updates() {
+        let deletion = |index_uid: &str| TaskContent::DocumentDeletion {
+            index_uid: IndexUid::new_unchecked(index_uid),
+            deletion: DocumentDeletion::Clear,
+            priority: Default::default(),
+        };
+        let settings = |index_uid: &str| TaskContent::SettingsUpdate {
+            index_uid: IndexUid::new_unchecked(index_uid),
+            settings: Default::default(),
+            is_deletion: false,
+            allow_index_creation: true,
+            priority: Default::default(),
+        };
+
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, deletion("test1")));
+        queue.insert(gen_task(1, deletion("test1")));
+        queue.insert(gen_task(2, settings("test1")));
+        queue.insert(gen_task(3, settings("test1")));
+        queue.insert(gen_task(4, settings("test1")));
+        queue.insert(gen_task(5, deletion("test1")));
+        queue.insert(gen_task(6, gen_doc_addition_task_content("test1")));
+
+        let config = SchedulerConfig { max_batch_size: Some(2), ..Default::default() };
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdates(vec![0, 1]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdates(vec![2, 3]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdates(vec![4]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdates(vec![5]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![6]));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_make_batch_priorities() {
+        let with_priority = |index_uid: &str, priority| TaskContent::IndexDeletion {
+            index_uid: IndexUid::new_unchecked(index_uid),
+            priority,
+        };
+
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, with_priority("test2", TaskPriority::Low)));
+        queue.insert(gen_task(2, with_priority("test3", TaskPriority::High)));
+        queue.insert(gen_task(3, with_priority("test1", TaskPriority::High)));
+        queue.insert(gen_task(4, with_priority("test2", TaskPriority::High)));
+        queue.insert(gen_task(5, with_priority("test4", TaskPriority::Normal)));
+
+        let config = SchedulerConfig::default();
+
+        // The high priority index goes first.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(2));
+
+        // The tasks of an index are processed in order, whatever their priority.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(3));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(5));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(1));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(4));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn locked_task_lists_are_not_scheduled() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(2, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test1"), priority: Default::default() }));
+
+        let config = SchedulerConfig::default();
+        let test1 = TaskListIdentifier::Index("test1".to_string());
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0]));
+        queue.lock(test1.clone());
+
+        // While the batch of test1 is processed, only test2 can be scheduled.
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![1]));
+        queue.lock(TaskListIdentifier::Index("test2".to_string()));
+
+        // A task registered on a locked index that had no more pending tasks waits as well.
+        queue.insert(gen_task(3, gen_doc_addition_task_content("test2")));
+        assert_eq!(make_batch(&mut queue, &config), Processing::Nothing);
+        assert!(!queue.is_empty());
+
+        queue.unlock(&test1);
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(2));
+
+        queue.unlock(&TaskListIdentifier::Index("test2".to_string()));
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![3]));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn remove_pending_tasks() {
+        let mut queue = TaskQueue::default();
+        queue.insert(gen_task(0, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(1, gen_doc_addition_task_content("test2")));
+        queue.insert(gen_task(2, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(3, gen_doc_addition_task_content("test1")));
+
+        assert!(queue.remove(2));
+        assert!(queue.remove(1));
+        assert!(!queue.remove(1));
+        assert!(!queue.remove(42));
+
+        let mut pending: Vec<_> = queue.pending_ids().collect();
+        pending.sort_unstable();
+        assert_eq!(pending, vec![0, 3]);
+
+        let config = SchedulerConfig::default();
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![0, 3]));
+
+        assert!(queue.is_empty());
+
+        queue.insert(gen_task(4, gen_doc_addition_task_content("test1")));
+        queue.insert(gen_task(5, TaskContent::IndexDeletion { index_uid: IndexUid::new_unchecked("test2"), priority: Default::default() }));
+        queue.insert(gen_task(6, gen_doc_addition_task_content("test1")));
+
+        // Removing the head of a list must reorder the queue.
+        assert!(queue.remove(4));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::IndexUpdate(5));
+
+        let batch = make_batch(&mut queue, &config);
+        assert_eq!(batch, Processing::DocumentAdditions(vec![6]));
+
+        assert!(queue.is_empty());
+    }
 }
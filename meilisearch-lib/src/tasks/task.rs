@@ -1,6 +1,7 @@
 use meilisearch_error::ResponseError;
-use milli::update::{DocumentAdditionResult, IndexDocumentsMethod};
+use milli::update::{DocumentAdditionResult, IndexDocumentsMethod, UpdateIndexingStep};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
@@ -15,6 +16,7 @@ pub type TaskId = u32;
 pub enum TaskResult {
     DocumentAddition { indexed_documents: u64 },
     DocumentDeletion { deleted_documents: u64 },
+    DocumentEdition { edited_documents: u64 },
     ClearAll { deleted_documents: u64 },
     Other,
 }
@@ -58,6 +60,11 @@ pub enum TaskEvent {
         #[serde(with = "time::serde::rfc3339")]
         timestamp: OffsetDateTime,
     },
+    Canceled(
+        #[cfg_attr(test, proptest(strategy = "test::datetime_strategy()"))]
+        #[serde(with = "time::serde::rfc3339")]
+        OffsetDateTime,
+    ),
 }
 
 impl TaskEvent {
@@ -74,6 +81,138 @@ impl TaskEvent {
             timestamp: OffsetDateTime::now_utc(),
         }
     }
+
+    pub fn canceled() -> Self {
+        Self::Canceled(OffsetDateTime::now_utc())
+    }
+}
+
+/// The status of a task, derived from its last event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Enqueued => "enqueued",
+            Self::Processing => "processing",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Canceled => "canceled",
+        }
+    }
+}
+
+/// The kind of a task, derived from its content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TaskKind {
+    DocumentAddition,
+    DocumentDeletion,
+    DocumentEdition,
+    SettingsUpdate,
+    IndexDeletion,
+    IndexCreation,
+    IndexUpdate,
+    Dump,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::DocumentAddition => "documentAddition",
+            Self::DocumentDeletion => "documentDeletion",
+            Self::DocumentEdition => "documentEdition",
+            Self::SettingsUpdate => "settingsUpdate",
+            Self::IndexDeletion => "indexDeletion",
+            Self::IndexCreation => "indexCreation",
+            Self::IndexUpdate => "indexUpdate",
+            Self::Dump => "dump",
+        }
+    }
+}
+
+/// The lane a task is scheduled in. The scheduler processes the indexes whose next task has the
+/// highest priority first, while the tasks of a same index are always processed in order.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(test, derive(proptest_derive::Arbitrary))]
+#[serde(rename_all = "camelCase")]
+pub enum TaskPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Default for TaskPriority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+/// The indexing steps of a document addition batch, in the order milli goes through them.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IndexingStep {
+    RemapDocumentAddition,
+    ComputeIdsAndMergeDocuments,
+    IndexDocuments,
+    MergeDataIntoFinalDatabase,
+}
+
+/// The progress of a processing task. It is only kept in memory while the task is processed.
+#[derive(Clone, Copy, Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgress {
+    pub step: IndexingStep,
+    pub documents_processed: usize,
+    pub total_documents: usize,
+}
+
+impl TaskProgress {
+    /// Converts an indexing step reported by milli. `total_documents` is the number of documents
+    /// received by the task, used until milli knows the number of documents to index.
+    pub fn new(step: UpdateIndexingStep, total_documents: usize) -> Self {
+        let (step, documents_processed, total_documents) = match step {
+            UpdateIndexingStep::RemapDocumentAddition { documents_seen } => (
+                IndexingStep::RemapDocumentAddition,
+                documents_seen,
+                total_documents,
+            ),
+            UpdateIndexingStep::ComputeIdsAndMergeDocuments {
+                documents_seen,
+                total_documents,
+            } => (
+                IndexingStep::ComputeIdsAndMergeDocuments,
+                documents_seen,
+                total_documents,
+            ),
+            UpdateIndexingStep::IndexDocuments {
+                documents_seen,
+                total_documents,
+            } => (
+                IndexingStep::IndexDocuments,
+                documents_seen,
+                total_documents,
+            ),
+            // All the documents were indexed, they are being written to the index databases.
+            UpdateIndexingStep::MergeDataIntoFinalDatabase { .. } => (
+                IndexingStep::MergeDataIntoFinalDatabase,
+                total_documents,
+                total_documents,
+            ),
+        };
+
+        Self {
+            step,
+            documents_processed,
+            total_documents,
+        }
+    }
 }
 
 /// A task represents an operation that Meilisearch must do.
@@ -94,16 +233,74 @@ pub struct Task {
 
 impl Task {
     /// Return true when a task is finished.
-    /// A task is finished when its last state is either `Succeeded` or `Failed`.
+    /// A task is finished when its last state is either `Succeeded`, `Failed` or `Canceled`.
     pub fn is_finished(&self) -> bool {
         self.events.last().map_or(false, |event| {
             matches!(
                 event,
-                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. }
+                TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. } | TaskEvent::Canceled(_)
             )
         })
     }
 
+    /// Return the status of the task. A task without any event is considered enqueued.
+    pub fn status(&self) -> TaskStatus {
+        match self.events.last() {
+            None | Some(TaskEvent::Created(_)) => TaskStatus::Enqueued,
+            Some(TaskEvent::Batched { .. } | TaskEvent::Processing(_)) => TaskStatus::Processing,
+            Some(TaskEvent::Succeeded { .. }) => TaskStatus::Succeeded,
+            Some(TaskEvent::Failed { .. }) => TaskStatus::Failed,
+            Some(TaskEvent::Canceled(_)) => TaskStatus::Canceled,
+        }
+    }
+
+    /// Return the date of the last state change of the task.
+    pub fn updated_at(&self) -> Option<OffsetDateTime> {
+        match self.events.last()? {
+            TaskEvent::Created(timestamp)
+            | TaskEvent::Processing(timestamp)
+            | TaskEvent::Canceled(timestamp) => Some(*timestamp),
+            TaskEvent::Batched { timestamp, .. }
+            | TaskEvent::Succeeded { timestamp, .. }
+            | TaskEvent::Failed { timestamp, .. } => Some(*timestamp),
+        }
+    }
+
+    /// Return the date at which the task was enqueued.
+    pub fn enqueued_at(&self) -> Option<OffsetDateTime> {
+        match self.events.first()? {
+            TaskEvent::Created(timestamp) => Some(*timestamp),
+            _ => None,
+        }
+    }
+
+    /// Return the date at which the task started being processed, if it was.
+    pub fn started_at(&self) -> Option<OffsetDateTime> {
+        self.events.iter().find_map(|event| match event {
+            TaskEvent::Processing(timestamp) => Some(*timestamp),
+            _ => None,
+        })
+    }
+
+    /// Return the date at which the task was finished, if it is finished.
+    pub fn finished_at(&self) -> Option<OffsetDateTime> {
+        match self.events.last()? {
+            TaskEvent::Succeeded { timestamp, .. } | TaskEvent::Failed { timestamp, .. } => {
+                Some(*timestamp)
+            }
+            TaskEvent::Canceled(timestamp) => Some(*timestamp),
+            _ => None,
+        }
+    }
+
+    /// Return the id of the batch the task was processed in, if it was batched.
+    pub fn batch_id(&self) -> Option<BatchId> {
+        self.events.iter().find_map(|event| match event {
+            TaskEvent::Batched { batch_id, .. } => Some(*batch_id),
+            _ => None,
+        })
+    }
+
     /// Return the content_uuid of the `Task` if there is one.
     pub fn get_content_uuid(&self) -> Option<Uuid> {
         match self {
@@ -119,8 +316,9 @@ impl Task {
         match &self.content {
             TaskContent::DocumentAddition { index_uid, .. }
             | TaskContent::DocumentDeletion { index_uid, .. }
+            | TaskContent::DocumentEdition { index_uid, .. }
             | TaskContent::SettingsUpdate { index_uid, .. }
-            | TaskContent::IndexDeletion { index_uid }
+            | TaskContent::IndexDeletion { index_uid, .. }
             | TaskContent::IndexCreation { index_uid, .. }
             | TaskContent::IndexUpdate { index_uid, .. } => Some(index_uid.as_str()),
             TaskContent::Dump { .. } => None,
@@ -133,6 +331,8 @@ impl Task {
 pub enum DocumentDeletion {
     Clear,
     Ids(Vec<String>),
+    /// The documents matching a search filter, resolved when the task is processed.
+    Filter(#[cfg_attr(test, proptest(strategy = "test::filter_strategy()"))] Value),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -145,13 +345,34 @@ pub enum TaskContent {
         content_uuid: Uuid,
         #[cfg_attr(test, proptest(strategy = "test::index_document_method_strategy()"))]
         merge_strategy: IndexDocumentsMethod,
+        /// Whether the documents are JSON merge patches of the stored documents, which only
+        /// happens with the `UpdateDocuments` strategy.
+        #[serde(default)]
+        merge_patch: bool,
         primary_key: Option<String>,
         documents_count: usize,
         allow_index_creation: bool,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     DocumentDeletion {
         index_uid: IndexUid,
         deletion: DocumentDeletion,
+        #[serde(default)]
+        priority: TaskPriority,
+    },
+    /// Applies a document edition function to the documents matching the filter, or to all the
+    /// documents of the index.
+    DocumentEdition {
+        index_uid: IndexUid,
+        #[cfg_attr(
+            test,
+            proptest(strategy = "proptest::option::of(test::filter_strategy())")
+        )]
+        filter: Option<Value>,
+        function: String,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     SettingsUpdate {
         index_uid: IndexUid,
@@ -159,23 +380,61 @@ pub enum TaskContent {
         /// Indicates whether the task was a deletion
         is_deletion: bool,
         allow_index_creation: bool,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     IndexDeletion {
         index_uid: IndexUid,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     IndexCreation {
         index_uid: IndexUid,
         primary_key: Option<String>,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     IndexUpdate {
         index_uid: IndexUid,
         primary_key: Option<String>,
+        #[serde(default)]
+        priority: TaskPriority,
     },
     Dump {
         uid: String,
     },
 }
 
+impl TaskContent {
+    pub fn kind(&self) -> TaskKind {
+        match self {
+            TaskContent::DocumentAddition { .. } => TaskKind::DocumentAddition,
+            TaskContent::DocumentDeletion { .. } => TaskKind::DocumentDeletion,
+            TaskContent::DocumentEdition { .. } => TaskKind::DocumentEdition,
+            TaskContent::SettingsUpdate { .. } => TaskKind::SettingsUpdate,
+            TaskContent::IndexDeletion { .. } => TaskKind::IndexDeletion,
+            TaskContent::IndexCreation { .. } => TaskKind::IndexCreation,
+            TaskContent::IndexUpdate { .. } => TaskKind::IndexUpdate,
+            TaskContent::Dump { .. } => TaskKind::Dump,
+        }
+    }
+
+    /// Returns the priority the task was registered with. Dumps are not scheduled with the index
+    /// tasks, and always have the normal priority.
+    pub fn priority(&self) -> TaskPriority {
+        match self {
+            TaskContent::DocumentAddition { priority, .. }
+            | TaskContent::DocumentDeletion { priority, .. }
+            | TaskContent::DocumentEdition { priority, .. }
+            | TaskContent::SettingsUpdate { priority, .. }
+            | TaskContent::IndexDeletion { priority, .. }
+            | TaskContent::IndexCreation { priority, .. }
+            | TaskContent::IndexUpdate { priority, .. } => *priority,
+            TaskContent::Dump { .. } => TaskPriority::Normal,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use proptest::prelude::*;
@@ -192,4 +451,48 @@ mod test {
     pub(super) fn datetime_strategy() -> impl Strategy<Value = OffsetDateTime> {
         Just(OffsetDateTime::now_utc())
     }
+
+    pub(super) fn filter_strategy() -> impl Strategy<Value = serde_json::Value> {
+        any::<String>().prop_map(serde_json::Value::String)
+    }
+
+    #[test]
+    fn task_progress_from_indexing_steps() {
+        let progress = TaskProgress::new(
+            UpdateIndexingStep::RemapDocumentAddition { documents_seen: 10 },
+            100,
+        );
+        assert_eq!(progress.step, IndexingStep::RemapDocumentAddition);
+        assert_eq!(
+            (progress.documents_processed, progress.total_documents),
+            (10, 100)
+        );
+
+        // Once milli knows the number of documents, its total is used.
+        let progress = TaskProgress::new(
+            UpdateIndexingStep::IndexDocuments {
+                documents_seen: 20,
+                total_documents: 80,
+            },
+            100,
+        );
+        assert_eq!(progress.step, IndexingStep::IndexDocuments);
+        assert_eq!(
+            (progress.documents_processed, progress.total_documents),
+            (20, 80)
+        );
+
+        let progress = TaskProgress::new(
+            UpdateIndexingStep::MergeDataIntoFinalDatabase {
+                databases_seen: 1,
+                total_databases: 10,
+            },
+            100,
+        );
+        assert_eq!(progress.step, IndexingStep::MergeDataIntoFinalDatabase);
+        assert_eq!(
+            (progress.documents_processed, progress.total_documents),
+            (100, 100)
+        );
+    }
 }
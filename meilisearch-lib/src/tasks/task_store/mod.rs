@@ -7,12 +7,14 @@ use std::sync::Arc;
 
 use log::debug;
 use milli::heed::{Env, RwTxn};
+use roaring::RoaringBitmap;
 use time::OffsetDateTime;
 
-use super::batch::BatchContent;
+use super::batch::{BatchContent, BatchId};
 use super::error::TaskError;
+use super::retention::RetentionPolicy;
 use super::scheduler::Processing;
-use super::task::{Task, TaskContent, TaskId};
+use super::task::{Task, TaskContent, TaskId, TaskKind, TaskStatus};
 use super::Result;
 use crate::tasks::task::TaskEvent;
 use crate::update_file_store::UpdateFileStore;
@@ -22,10 +24,40 @@ pub use store::test::MockStore as Store;
 #[cfg(not(test))]
 pub use store::Store;
 
+/// The maximum number of tasks deleted in a single write transaction when pruning the store.
+const PRUNE_CHUNK_SIZE: usize = 1000;
+
+/// A range of dates, both bounds are exclusive.
+#[derive(Default, Clone, Copy)]
+struct DateRange {
+    after: Option<OffsetDateTime>,
+    before: Option<OffsetDateTime>,
+}
+
+impl DateRange {
+    fn contains(&self, date: Option<OffsetDateTime>) -> bool {
+        if self.after.is_none() && self.before.is_none() {
+            return true;
+        }
+
+        date.map_or(false, |date| {
+            self.after.map_or(true, |after| date > after)
+                && self.before.map_or(true, |before| date < before)
+        })
+    }
+}
+
 /// Defines constraints to be applied when querying for Tasks from the store.
 #[derive(Default)]
 pub struct TaskFilter {
     indexes: Option<HashSet<String>>,
+    statuses: Option<HashSet<TaskStatus>>,
+    kinds: Option<HashSet<TaskKind>>,
+    uids: Option<RoaringBitmap>,
+    after_uid: Option<TaskId>,
+    enqueued_at: DateRange,
+    started_at: DateRange,
+    finished_at: DateRange,
     filter_fn: Option<Box<dyn Fn(&Task) -> bool + Sync + Send + 'static>>,
 }
 
@@ -40,10 +72,77 @@ impl TaskFilter {
         }
     }
 
+    /// Returns whether the task matches the constraints that are not enforced by the store
+    /// candidates.
+    fn matches(&self, task: &Task) -> bool {
+        self.after_uid.map_or(true, |uid| task.id > uid)
+            && self.enqueued_at.contains(task.enqueued_at())
+            && self.started_at.contains(task.started_at())
+            && self.finished_at.contains(task.finished_at())
+            && self.filter_fn.as_ref().map_or(true, |f| f(task))
+    }
+
+    /// Returns whether the task matches all the constraints of the filter.
+    pub fn accepts(&self, task: &Task) -> bool {
+        let index = self.indexes.as_ref().map_or(true, |indexes| {
+            task.index_uid()
+                .map_or(false, |index_uid| indexes.contains(index_uid))
+        });
+        let status = self
+            .statuses
+            .as_ref()
+            .map_or(true, |statuses| statuses.contains(&task.status()));
+        let kind = self
+            .kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(&task.content.kind()));
+        let uid = self
+            .uids
+            .as_ref()
+            .map_or(true, |uids| uids.contains(task.id));
+
+        index && status && kind && uid && self.matches(task)
+    }
+
     fn filtered_indexes(&self) -> Option<&HashSet<String>> {
         self.indexes.as_ref()
     }
 
+    fn filtered_statuses(&self) -> Option<&HashSet<TaskStatus>> {
+        self.statuses.as_ref()
+    }
+
+    fn filtered_kinds(&self) -> Option<&HashSet<TaskKind>> {
+        self.kinds.as_ref()
+    }
+
+    fn filtered_uids(&self) -> Option<&RoaringBitmap> {
+        self.uids.as_ref()
+    }
+
+    /// Returns the uid strictly after which the tasks must have been enqueued.
+    fn filtered_after_uid(&self) -> Option<TaskId> {
+        self.after_uid
+    }
+
+    /// Returns the date strictly after which the tasks must have been enqueued.
+    fn enqueued_after(&self) -> Option<OffsetDateTime> {
+        self.enqueued_at.after
+    }
+
+    /// Returns the date strictly before which the tasks must have been enqueued. A task can't be
+    /// started or finished before being enqueued, so the other dates constrain it as well.
+    fn enqueued_before(&self) -> Option<OffsetDateTime> {
+        [
+            self.enqueued_at.before,
+            self.started_at.before,
+            self.finished_at.before,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
     /// Adds an index to the filter, so the filter must match this index.
     pub fn filter_index(&mut self, index: String) {
         self.indexes
@@ -51,6 +150,67 @@ impl TaskFilter {
             .insert(index);
     }
 
+    /// Adds a status to the filter, so the filter must match this status.
+    pub fn filter_status(&mut self, status: TaskStatus) {
+        self.statuses
+            .get_or_insert_with(Default::default)
+            .insert(status);
+    }
+
+    /// Adds a task kind to the filter, so the filter must match this kind.
+    pub fn filter_kind(&mut self, kind: TaskKind) {
+        self.kinds.get_or_insert_with(Default::default).insert(kind);
+    }
+
+    /// Adds a task uid to the filter, so the filter must match this uid.
+    pub fn filter_uid(&mut self, uid: TaskId) {
+        self.uids.get_or_insert_with(Default::default).insert(uid);
+    }
+
+    /// Restricts the filter to the given task uids, on top of the uids it already filters.
+    pub fn restrict_uids(&mut self, uids: RoaringBitmap) {
+        let uids = match self.uids.take() {
+            Some(filtered) => filtered & uids,
+            None => uids,
+        };
+        self.uids = Some(uids);
+    }
+
+    /// Restricts the filter to the tasks that were enqueued after the task with the given uid.
+    pub fn filter_after_uid(&mut self, uid: TaskId) {
+        self.after_uid.replace(uid);
+    }
+
+    /// Restricts the filter to the tasks that were enqueued after the given date.
+    pub fn filter_enqueued_after(&mut self, date: OffsetDateTime) {
+        self.enqueued_at.after.replace(date);
+    }
+
+    /// Restricts the filter to the tasks that were enqueued before the given date.
+    pub fn filter_enqueued_before(&mut self, date: OffsetDateTime) {
+        self.enqueued_at.before.replace(date);
+    }
+
+    /// Restricts the filter to the tasks that were started after the given date.
+    pub fn filter_started_after(&mut self, date: OffsetDateTime) {
+        self.started_at.after.replace(date);
+    }
+
+    /// Restricts the filter to the tasks that were started before the given date.
+    pub fn filter_started_before(&mut self, date: OffsetDateTime) {
+        self.started_at.before.replace(date);
+    }
+
+    /// Restricts the filter to the tasks that were finished after the given date.
+    pub fn filter_finished_after(&mut self, date: OffsetDateTime) {
+        self.finished_at.after.replace(date);
+    }
+
+    /// Restricts the filter to the tasks that were finished before the given date.
+    pub fn filter_finished_before(&mut self, date: OffsetDateTime) {
+        self.finished_at.before.replace(date);
+    }
+
     pub fn filter_fn(&mut self, f: impl Fn(&Task) -> bool + Sync + Send + 'static) {
         self.filter_fn.replace(Box::new(f));
     }
@@ -135,7 +295,7 @@ impl TaskStore {
             let txn = store.rtxn()?;
 
             let content = match processing {
-                Processing::DocumentAdditions(ref ids) => {
+                Processing::DocumentAdditions(ref ids) | Processing::IndexUpdates(ref ids) => {
                     let mut tasks = Vec::new();
 
                     for id in ids.iter() {
@@ -144,7 +304,13 @@ impl TaskStore {
                             .ok_or(TaskError::UnexistingTask(*id))?;
                         tasks.push(task);
                     }
-                    BatchContent::DocumentsAdditionBatch(tasks)
+
+                    match processing {
+                        Processing::DocumentAdditions(_) => {
+                            BatchContent::DocumentsAdditionBatch(tasks)
+                        }
+                        _ => BatchContent::IndexUpdates(tasks),
+                    }
                 }
                 Processing::IndexUpdate(id) => {
                     let task = store.get(&txn, id)?.ok_or(TaskError::UnexistingTask(id))?;
@@ -211,6 +377,120 @@ impl TaskStore {
         .await?
     }
 
+    pub async fn get_batch(&self, id: BatchId, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+        let tasks = tokio::task::spawn_blocking(move || -> Result<_> {
+            let txn = store.rtxn()?;
+            let tasks = store.get_batch(&txn, id)?;
+            Ok(tasks)
+        })
+        .await??
+        .ok_or(TaskError::UnexistingBatch(id))?;
+
+        match filter {
+            Some(filter) if !tasks.iter().any(|task| filter.pass(task)) => {
+                Err(TaskError::UnexistingBatch(id))
+            }
+            _ => Ok(tasks),
+        }
+    }
+
+    pub async fn list_batches(
+        &self,
+        offset: Option<BatchId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(BatchId, Vec<Task>)>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let txn = store.rtxn()?;
+            let batches = store.list_batches(&txn, offset, filter, limit)?;
+            Ok(batches)
+        })
+        .await?
+    }
+
+    /// Deletes the finished tasks matching the filter. Returns the deleted tasks.
+    pub async fn delete_tasks(&self, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut txn = store.wtxn()?;
+            let tasks = store
+                .list_tasks(&txn, None, filter, None)?
+                .into_iter()
+                .filter(Task::is_finished)
+                .collect::<Vec<_>>();
+
+            for task in &tasks {
+                store.delete(&mut txn, task)?;
+            }
+
+            txn.commit()?;
+
+            Ok(tasks)
+        })
+        .await?
+    }
+
+    /// Deletes the finished tasks that the retention policy doesn't keep anymore. Returns the
+    /// deleted tasks.
+    pub async fn prune(&self, policy: RetentionPolicy) -> Result<Vec<Task>> {
+        let store = self.store.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let expired_before = policy
+                .retention_period
+                .map(|period| OffsetDateTime::now_utc() - period);
+
+            let txn = store.rtxn()?;
+            let finished = store.task_ids_by_statuses(
+                &txn,
+                &[
+                    TaskStatus::Succeeded,
+                    TaskStatus::Failed,
+                    TaskStatus::Canceled,
+                ],
+            )?;
+
+            // The tasks are ranked from the most recent to the oldest, the tasks beyond the
+            // maximum are deleted without being read.
+            let mut pruned_ids = Vec::new();
+            for (rank, id) in finished.iter().rev().enumerate() {
+                let too_many = policy.max_finished_tasks.map_or(false, |max| rank >= max);
+                let expired = match expired_before {
+                    Some(expired_before) if !too_many => store
+                        .get(&txn, id)?
+                        .and_then(|task| task.finished_at())
+                        .map_or(false, |finished_at| finished_at < expired_before),
+                    _ => false,
+                };
+                if too_many || expired {
+                    pruned_ids.push(id);
+                }
+            }
+            drop(txn);
+
+            // The tasks are deleted in small write transactions, so that the registration of
+            // new tasks isn't blocked for the whole pruning.
+            let mut tasks = Vec::with_capacity(pruned_ids.len());
+            for ids in pruned_ids.chunks(PRUNE_CHUNK_SIZE) {
+                let mut txn = store.wtxn()?;
+                for id in ids {
+                    if let Some(task) = store.get(&txn, *id)? {
+                        store.delete(&mut txn, &task)?;
+                        tasks.push(task);
+                    }
+                }
+                txn.commit()?;
+            }
+
+            Ok(tasks)
+        })
+        .await?
+    }
+
     pub async fn dump(
         env: Arc<Env>,
         dir_path: impl AsRef<Path>,
@@ -268,7 +548,7 @@ impl TaskStore {
 #[cfg(test)]
 pub mod test {
     use crate::{
-        tasks::{scheduler::Processing, task_store::store::test::tmp_env},
+        tasks::{scheduler::Processing, task::TaskResult, task_store::store::test::tmp_env},
         IndexUid,
     };
 
@@ -356,6 +636,43 @@ pub mod test {
             }
         }
 
+        pub async fn get_batch(
+            &self,
+            id: BatchId,
+            filter: Option<TaskFilter>,
+        ) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.get_batch(id, filter).await,
+                Self::Mock(m) => unsafe { m.get("get_batch").call((id, filter)) },
+            }
+        }
+
+        pub async fn list_batches(
+            &self,
+            from: Option<BatchId>,
+            filter: Option<TaskFilter>,
+            limit: Option<usize>,
+        ) -> Result<Vec<(BatchId, Vec<Task>)>> {
+            match self {
+                Self::Real(s) => s.list_batches(from, filter, limit).await,
+                Self::Mock(m) => unsafe { m.get("list_batches").call((from, filter, limit)) },
+            }
+        }
+
+        pub async fn delete_tasks(&self, filter: Option<TaskFilter>) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.delete_tasks(filter).await,
+                Self::Mock(m) => unsafe { m.get("delete_tasks").call(filter) },
+            }
+        }
+
+        pub async fn prune(&self, policy: RetentionPolicy) -> Result<Vec<Task>> {
+            match self {
+                Self::Real(s) => s.prune(policy).await,
+                Self::Mock(m) => unsafe { m.get("prune").call(policy) },
+            }
+        }
+
         pub async fn register(&self, content: TaskContent) -> Result<Task> {
             match self {
                 Self::Real(s) => s.register(content).await,
@@ -389,6 +706,7 @@ pub mod test {
             content: TaskContent::IndexCreation {
                 primary_key: None,
                 index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
             },
             events: Vec::new(),
         };
@@ -417,4 +735,98 @@ pub mod test {
             })
             .unwrap();
     }
+
+    #[test]
+    fn test_filter_accepts() {
+        let task = Task {
+            id: 4,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
+            },
+            events: vec![
+                TaskEvent::Created(OffsetDateTime::now_utc()),
+                TaskEvent::succeeded(TaskResult::Other),
+            ],
+        };
+
+        assert!(TaskFilter::default().accepts(&task));
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".to_string());
+        filter.filter_status(TaskStatus::Succeeded);
+        filter.filter_kind(TaskKind::IndexDeletion);
+        filter.filter_after_uid(3);
+        assert!(filter.accepts(&task));
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("other".to_string());
+        assert!(!filter.accepts(&task));
+
+        let mut filter = TaskFilter::default();
+        filter.filter_status(TaskStatus::Failed);
+        assert!(!filter.accepts(&task));
+
+        let mut filter = TaskFilter::default();
+        filter.filter_uid(3);
+        assert!(!filter.accepts(&task));
+
+        let mut filter = TaskFilter::default();
+        filter.filter_after_uid(4);
+        assert!(!filter.accepts(&task));
+    }
+
+    #[actix_rt::test]
+    async fn test_prune_finished_tasks() {
+        let tmp = tmp_env();
+        let store = TaskStore::new(tmp.env()).unwrap();
+
+        let now = OffsetDateTime::now_utc();
+        let gen_task = |id: TaskId, finished_days_ago: Option<i64>| {
+            let mut events = vec![TaskEvent::Created(now)];
+            if let Some(days) = finished_days_ago {
+                events.push(TaskEvent::Succeeded {
+                    result: TaskResult::Other,
+                    timestamp: now - time::Duration::days(days),
+                });
+            }
+            Task {
+                id,
+                content: TaskContent::IndexCreation {
+                    primary_key: None,
+                    index_uid: IndexUid::new_unchecked("test"),
+                    priority: Default::default(),
+                },
+                events,
+            }
+        };
+
+        let tasks = vec![
+            gen_task(0, Some(10)),
+            gen_task(1, Some(5)),
+            gen_task(2, Some(3)),
+            gen_task(3, None),
+            gen_task(4, Some(1)),
+            gen_task(5, Some(0)),
+        ];
+        store.update_tasks(tasks).await.unwrap();
+
+        let policy = RetentionPolicy {
+            retention_period: Some(std::time::Duration::from_secs(7 * 24 * 60 * 60)),
+            max_finished_tasks: None,
+        };
+        let pruned = store.prune(policy).await.unwrap();
+        assert_eq!(pruned.iter().map(|t| t.id).collect::<Vec<_>>(), &[0]);
+
+        let policy = RetentionPolicy {
+            retention_period: None,
+            max_finished_tasks: Some(2),
+        };
+        let pruned = store.prune(policy).await.unwrap();
+        assert_eq!(pruned.iter().map(|t| t.id).collect::<Vec<_>>(), &[2, 1]);
+
+        // unfinished tasks are never pruned.
+        let tasks = store.list_tasks(None, None, None).await.unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[5, 4, 3]);
+    }
 }
@@ -1,12 +1,27 @@
 #[allow(clippy::upper_case_acronyms)]
 
 type BEU32 = milli::heed::zerocopy::U32<milli::heed::byteorder::BE>;
+#[allow(clippy::upper_case_acronyms)]
+type BEU64 = milli::heed::zerocopy::U64<milli::heed::byteorder::BE>;
 
 const INDEX_UIDS_TASK_IDS: &str = "index-uids-task-ids";
+const STATUSES_TASK_IDS: &str = "statuses-task-ids";
+const KINDS_TASK_IDS: &str = "kinds-task-ids";
+const ENQUEUED_AT_TASK_IDS: &str = "enqueued-at-task-ids";
+const BATCH_UIDS_TASK_IDS: &str = "batch-uids-task-ids";
 const TASKS: &str = "tasks";
+const MAIN: &str = "task-store-main";
+
+const NEXT_TASK_ID_KEY: &str = "next-task-id";
+const INDEXES_VERSION_KEY: &str = "indexes-version";
 
-use std::collections::HashSet;
-use std::ops::Bound::{Excluded, Unbounded};
+/// The version of the secondary indexes of the tasks. It must be bumped every time an index is
+/// added or changed, so that the stores created by a previous version are re-indexed on load.
+const INDEXES_VERSION: u32 = 2;
+
+use std::collections::HashMap;
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::ops::RangeInclusive;
 use std::result::Result as StdResult;
 use std::sync::Arc;
 
@@ -14,8 +29,10 @@ use milli::heed::types::{OwnedType, SerdeJson, Str};
 use milli::heed::{Database, Env, RoTxn, RwTxn};
 use milli::heed_codec::RoaringBitmapCodec;
 use roaring::RoaringBitmap;
+use time::OffsetDateTime;
 
-use crate::tasks::task::{Task, TaskId};
+use crate::tasks::batch::BatchId;
+use crate::tasks::task::{Task, TaskId, TaskStatus};
 
 use super::super::Result;
 use super::TaskFilter;
@@ -24,7 +41,18 @@ pub struct Store {
     env: Arc<Env>,
     /// Maps an index uid to the set of tasks ids associated to it.
     index_uid_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Maps a task status to the set of tasks ids currently in this status.
+    status_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Maps a task kind to the set of tasks ids of this kind.
+    kind_task_ids: Database<Str, RoaringBitmapCodec>,
+    /// Maps an enqueue date, in nanoseconds since the unix epoch, to the set of tasks ids enqueued
+    /// at this date.
+    enqueued_at_task_ids: Database<OwnedType<BEU64>, RoaringBitmapCodec>,
+    /// Maps a batch id to the set of tasks ids that were processed in this batch.
+    batch_uid_task_ids: Database<OwnedType<BEU32>, RoaringBitmapCodec>,
     tasks: Database<OwnedType<BEU32>, SerdeJson<Task>>,
+    /// Store metadata about the tasks, such as the next task id once the last tasks were deleted.
+    main: Database<Str, OwnedType<BEU32>>,
 }
 
 impl Drop for Store {
@@ -35,6 +63,12 @@ impl Drop for Store {
     }
 }
 
+/// Returns the key of a date in the enqueued-at database.
+fn enqueued_at_key(date: OffsetDateTime) -> BEU64 {
+    let nanos = date.unix_timestamp_nanos().clamp(0, u64::MAX as i128);
+    BEU64::new(nanos as u64)
+}
+
 impl Store {
     /// Create a new store from the specified `Path`.
     /// Be really cautious when calling this function, the returned `Store` may
@@ -43,13 +77,27 @@ impl Store {
     /// queue with the `reset_and_return_unfinished_update` method.
     pub fn new(env: Arc<milli::heed::Env>) -> Result<Self> {
         let index_uid_task_ids = env.create_database(Some(INDEX_UIDS_TASK_IDS))?;
+        let status_task_ids = env.create_database(Some(STATUSES_TASK_IDS))?;
+        let kind_task_ids = env.create_database(Some(KINDS_TASK_IDS))?;
+        let enqueued_at_task_ids = env.create_database(Some(ENQUEUED_AT_TASK_IDS))?;
+        let batch_uid_task_ids = env.create_database(Some(BATCH_UIDS_TASK_IDS))?;
         let tasks = env.create_database(Some(TASKS))?;
+        let main = env.create_database(Some(MAIN))?;
 
-        Ok(Self {
+        let store = Self {
             env,
             index_uid_task_ids,
+            status_task_ids,
+            kind_task_ids,
+            enqueued_at_task_ids,
+            batch_uid_task_ids,
             tasks,
-        })
+            main,
+        };
+
+        store.rebuild_indexes_if_needed()?;
+
+        Ok(store)
     }
 
     pub fn wtxn(&self) -> Result<RwTxn> {
@@ -60,6 +108,83 @@ impl Store {
         Ok(self.env.read_txn()?)
     }
 
+    /// Rebuilds the secondary indexes of the tasks from scratch if they were built by an older
+    /// version of the store, or if the store predates them.
+    fn rebuild_indexes_if_needed(&self) -> Result<()> {
+        let mut txn = self.wtxn()?;
+        let version = self
+            .main
+            .get(&txn, INDEXES_VERSION_KEY)?
+            .map_or(0, |version| version.get());
+
+        if version >= INDEXES_VERSION {
+            return Ok(());
+        }
+
+        let mut index_uids: HashMap<String, RoaringBitmap> = HashMap::new();
+        let mut statuses: HashMap<&str, RoaringBitmap> = HashMap::new();
+        let mut kinds: HashMap<&str, RoaringBitmap> = HashMap::new();
+        let mut enqueued_at: HashMap<u64, RoaringBitmap> = HashMap::new();
+        let mut batch_uids: HashMap<BatchId, RoaringBitmap> = HashMap::new();
+
+        for result in self.tasks.iter(&txn)? {
+            let (_, task) = result?;
+            if let Some(index_uid) = task.index_uid() {
+                index_uids
+                    .entry(index_uid.to_string())
+                    .or_default()
+                    .insert(task.id);
+            }
+            statuses
+                .entry(task.status().as_str())
+                .or_default()
+                .insert(task.id);
+            kinds
+                .entry(task.content.kind().as_str())
+                .or_default()
+                .insert(task.id);
+            if let Some(date) = task.enqueued_at() {
+                enqueued_at
+                    .entry(enqueued_at_key(date).get())
+                    .or_default()
+                    .insert(task.id);
+            }
+            if let Some(batch_id) = task.batch_id() {
+                batch_uids.entry(batch_id).or_default().insert(task.id);
+            }
+        }
+
+        self.index_uid_task_ids.clear(&mut txn)?;
+        self.status_task_ids.clear(&mut txn)?;
+        self.kind_task_ids.clear(&mut txn)?;
+        self.enqueued_at_task_ids.clear(&mut txn)?;
+        self.batch_uid_task_ids.clear(&mut txn)?;
+
+        for (index_uid, ids) in index_uids {
+            self.index_uid_task_ids.put(&mut txn, &index_uid, &ids)?;
+        }
+        for (status, ids) in statuses {
+            self.status_task_ids.put(&mut txn, status, &ids)?;
+        }
+        for (kind, ids) in kinds {
+            self.kind_task_ids.put(&mut txn, kind, &ids)?;
+        }
+        for (date, ids) in enqueued_at {
+            self.enqueued_at_task_ids
+                .put(&mut txn, &BEU64::new(date), &ids)?;
+        }
+        for (batch_id, ids) in batch_uids {
+            self.batch_uid_task_ids
+                .put(&mut txn, &BEU32::new(batch_id), &ids)?;
+        }
+
+        self.main
+            .put(&mut txn, INDEXES_VERSION_KEY, &BEU32::new(INDEXES_VERSION))?;
+        txn.commit()?;
+
+        Ok(())
+    }
+
     /// Returns the id for the next task.
     ///
     /// The required `mut txn` acts as a reservation system. It guarantees that as long as you commit
@@ -71,31 +196,147 @@ impl Store {
             .last(txn)?
             .map(|(id, _)| id.get() + 1)
             .unwrap_or(0);
-        Ok(id)
+        // The last tasks may have been deleted, we must never reuse their ids.
+        let deleted_id = self
+            .main
+            .get(txn, NEXT_TASK_ID_KEY)?
+            .map_or(0, |id| id.get());
+        Ok(id.max(deleted_id))
     }
 
     pub fn put(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        let previous = self.tasks.get(txn, &BEU32::new(task.id))?;
         self.tasks.put(txn, &BEU32::new(task.id), task)?;
+
+        match previous {
+            // Once a task is registered, only its status is expected to change.
+            Some(previous)
+                if previous.content == task.content
+                    && previous.enqueued_at() == task.enqueued_at() =>
+            {
+                if previous.status() != task.status() {
+                    let (previous, status) = (previous.status(), task.status());
+                    remove_from_set(txn, self.status_task_ids, previous.as_str(), task.id)?;
+                    add_to_set(txn, self.status_task_ids, status.as_str(), task.id)?;
+                }
+                if previous.batch_id() != task.batch_id() {
+                    self.unindex_batch(txn, &previous)?;
+                    self.index_batch(txn, task)?;
+                }
+            }
+            Some(previous) => {
+                self.unindex_task(txn, &previous)?;
+                self.index_task(txn, task)?;
+            }
+            None => self.index_task(txn, task)?,
+        }
+
+        Ok(())
+    }
+
+    /// Removes the task from the store.
+    pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        let next_task_id = self.next_task_id(txn)?;
+        self.main
+            .put(txn, NEXT_TASK_ID_KEY, &BEU32::new(next_task_id))?;
+
+        self.tasks.delete(txn, &BEU32::new(task.id))?;
+        self.unindex_task(txn, task)?;
+
+        Ok(())
+    }
+
+    /// Adds the task to the secondary indexes.
+    fn index_task(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
         // only add the task to the indexes index if it has an index_uid
         if let Some(index_uid) = task.index_uid() {
+            add_to_set(txn, self.index_uid_task_ids, index_uid, task.id)?;
+        }
+        let (status, kind) = (task.status(), task.content.kind());
+        add_to_set(txn, self.status_task_ids, status.as_str(), task.id)?;
+        add_to_set(txn, self.kind_task_ids, kind.as_str(), task.id)?;
+
+        if let Some(date) = task.enqueued_at() {
+            let key = enqueued_at_key(date);
             let mut tasks_set = self
-                .index_uid_task_ids
-                .get(txn, index_uid)?
+                .enqueued_at_task_ids
+                .get(txn, &key)?
                 .unwrap_or_default();
+            tasks_set.insert(task.id);
+            self.enqueued_at_task_ids.put(txn, &key, &tasks_set)?;
+        }
 
+        self.index_batch(txn, task)
+    }
+
+    /// Adds the task to the set of tasks of its batch, if it was batched.
+    fn index_batch(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        if let Some(batch_id) = task.batch_id() {
+            let key = BEU32::new(batch_id);
+            let mut tasks_set = self.batch_uid_task_ids.get(txn, &key)?.unwrap_or_default();
             tasks_set.insert(task.id);
+            self.batch_uid_task_ids.put(txn, &key, &tasks_set)?;
+        }
 
-            self.index_uid_task_ids.put(txn, index_uid, &tasks_set)?;
+        Ok(())
+    }
+
+    /// Removes the task from the set of tasks of its batch, and removes the batch once it's empty.
+    fn unindex_batch(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        if let Some(batch_id) = task.batch_id() {
+            let key = BEU32::new(batch_id);
+            if let Some(mut tasks_set) = self.batch_uid_task_ids.get(txn, &key)? {
+                tasks_set.remove(task.id);
+                if tasks_set.is_empty() {
+                    self.batch_uid_task_ids.delete(txn, &key)?;
+                } else {
+                    self.batch_uid_task_ids.put(txn, &key, &tasks_set)?;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Removes the task from the secondary indexes.
+    fn unindex_task(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+        if let Some(index_uid) = task.index_uid() {
+            remove_from_set(txn, self.index_uid_task_ids, index_uid, task.id)?;
+        }
+        let (status, kind) = (task.status(), task.content.kind());
+        remove_from_set(txn, self.status_task_ids, status.as_str(), task.id)?;
+        remove_from_set(txn, self.kind_task_ids, kind.as_str(), task.id)?;
+
+        if let Some(date) = task.enqueued_at() {
+            let key = enqueued_at_key(date);
+            if let Some(mut tasks_set) = self.enqueued_at_task_ids.get(txn, &key)? {
+                tasks_set.remove(task.id);
+                if tasks_set.is_empty() {
+                    self.enqueued_at_task_ids.delete(txn, &key)?;
+                } else {
+                    self.enqueued_at_task_ids.put(txn, &key, &tasks_set)?;
+                }
+            }
+        }
+
+        self.unindex_batch(txn, task)
+    }
+
     pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
         let task = self.tasks.get(txn, &BEU32::new(id))?;
         Ok(task)
     }
 
+    /// Returns the ids of the tasks having one of the statuses.
+    pub fn task_ids_by_statuses(
+        &self,
+        txn: &RoTxn,
+        statuses: &[TaskStatus],
+    ) -> Result<RoaringBitmap> {
+        let keys = statuses.iter().map(|status| status.as_str());
+        union_of_sets(txn, self.status_task_ids, keys)
+    }
+
     /// Returns the unfinished tasks starting from the given taskId in ascending order.
     pub fn fetch_unfinished_tasks(&self, txn: &RoTxn, from: Option<TaskId>) -> Result<Vec<Task>> {
         // We must NEVER re-enqueue an already processed task! It's content uuid would point to an unexisting file.
@@ -126,22 +367,35 @@ impl Store {
             None => self.tasks.last(txn)?.map_or(0, |(id, _)| id.get()),
         };
 
-        let filter_fn = |task: &Task| {
-            filter
-                .as_ref()
-                .and_then(|f| f.filter_fn.as_ref())
-                .map_or(true, |f| f(task))
+        let range = match filter.as_ref() {
+            Some(filter) => match self.compute_range(txn, filter, from)? {
+                Some(range) => range,
+                None => return Ok(Vec::new()),
+            },
+            None => 0..=from,
+        };
+
+        let filter_fn = |task: &Task| filter.as_ref().map_or(true, |f| f.matches(task));
+
+        let candidates = match filter.as_ref() {
+            Some(filter) => self.compute_candidates(txn, filter, range.clone())?,
+            None => None,
         };
 
-        let result: Result<Vec<_>> = match filter.as_ref().and_then(|f| f.filtered_indexes()) {
-            Some(indexes) => self
-                .compute_candidates(txn, indexes, from)?
+        let result: Result<Vec<_>> = match candidates {
+            Some(candidates) => candidates
+                .into_iter()
+                .rev()
+                .filter_map(|id| self.get(txn, id).transpose())
                 .filter(|result| result.as_ref().map_or(true, filter_fn))
                 .take(limit.unwrap_or(usize::MAX))
                 .collect(),
             None => self
                 .tasks
-                .rev_range(txn, &(..=BEU32::new(from)))?
+                .rev_range(
+                    txn,
+                    &(BEU32::new(*range.start())..=BEU32::new(*range.end())),
+                )?
                 .map(|r| r.map(|(_, t)| t).map_err(Into::into))
                 .filter(|result| result.as_ref().map_or(true, filter_fn))
                 .take(limit.unwrap_or(usize::MAX))
@@ -151,40 +405,214 @@ impl Store {
         result.map_err(Into::into)
     }
 
-    fn compute_candidates<'a>(
-        &'a self,
-        txn: &'a RoTxn,
-        indexes: &HashSet<String>,
+    /// Returns the tasks of the batch, in ascending order, or `None` if the batch doesn't exist.
+    pub fn get_batch(&self, txn: &RoTxn, id: BatchId) -> Result<Option<Vec<Task>>> {
+        match self.batch_uid_task_ids.get(txn, &BEU32::new(id))? {
+            Some(ids) => {
+                let tasks: Result<Vec<_>> = ids
+                    .into_iter()
+                    .filter_map(|id| self.get(txn, id).transpose())
+                    .collect();
+                tasks.map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the batches starting from the given batch id and going in descending order, along
+    /// with their tasks. Only the batches with at least one task passing the filter are returned.
+    pub fn list_batches(
+        &self,
+        txn: &RoTxn,
+        from: Option<BatchId>,
+        filter: Option<TaskFilter>,
+        limit: Option<usize>,
+    ) -> Result<Vec<(BatchId, Vec<Task>)>> {
+        let range = (
+            Unbounded,
+            Included(BEU32::new(from.unwrap_or(BatchId::MAX))),
+        );
+        let mut batches = Vec::new();
+
+        // The batches that don't contain any task of the filtered indexes are skipped without
+        // decoding their tasks.
+        let candidates = match filter.as_ref().and_then(|filter| filter.filtered_indexes()) {
+            Some(indexes) => {
+                let keys = indexes.iter().map(String::as_str);
+                Some(union_of_sets(txn, self.index_uid_task_ids, keys)?)
+            }
+            None => None,
+        };
+
+        for result in self.batch_uid_task_ids.rev_range(txn, &range)? {
+            if batches.len() >= limit.unwrap_or(usize::MAX) {
+                break;
+            }
+
+            let (id, ids) = result?;
+            if candidates
+                .as_ref()
+                .map_or(false, |candidates| ids.is_disjoint(candidates))
+            {
+                continue;
+            }
+
+            let tasks: Result<Vec<_>> = ids
+                .into_iter()
+                .filter_map(|id| self.get(txn, id).transpose())
+                .collect();
+            let tasks = tasks?;
+
+            if filter
+                .as_ref()
+                .map_or(true, |filter| tasks.iter().any(|task| filter.pass(task)))
+            {
+                batches.push((id.get(), tasks));
+            }
+        }
+
+        Ok(batches)
+    }
+
+    /// Computes the range of task ids that can match the filter, or `None` if no task can match
+    /// it. Since task ids are attributed in the order the tasks are enqueued, the enqueued-at
+    /// constraints of the filter are translated into bounds on the task ids.
+    fn compute_range(
+        &self,
+        txn: &RoTxn,
+        filter: &TaskFilter,
         from: TaskId,
-    ) -> Result<impl Iterator<Item = Result<Task>> + 'a> {
-        let mut candidates = RoaringBitmap::new();
+    ) -> Result<Option<RangeInclusive<TaskId>>> {
+        let start = match filter.enqueued_after() {
+            Some(after) => {
+                let range = (Excluded(enqueued_at_key(after)), Unbounded);
+                match self.enqueued_at_task_ids.range(txn, &range)?.next() {
+                    Some(result) => result?.1.min().unwrap_or_default(),
+                    None => return Ok(None),
+                }
+            }
+            None => 0,
+        };
+        let start = match filter.filtered_after_uid() {
+            Some(uid) => match uid.checked_add(1) {
+                Some(uid) => start.max(uid),
+                None => return Ok(None),
+            },
+            None => start,
+        };
 
-        for index_uid in indexes {
-            if let Some(tasks_set) = self.index_uid_task_ids.get(txn, index_uid)? {
-                candidates |= tasks_set;
+        let end = match filter.enqueued_before() {
+            Some(before) => {
+                let range = (Unbounded, Excluded(enqueued_at_key(before)));
+                match self.enqueued_at_task_ids.rev_range(txn, &range)?.next() {
+                    Some(result) => result?.1.max().unwrap_or_default().min(from),
+                    None => return Ok(None),
+                }
             }
+            None => from,
+        };
+
+        Ok((start <= end).then(|| start..=end))
+    }
+
+    /// Computes the ids of the tasks matching the index, status, kind and uid constraints of the
+    /// filter, within the given range. Returns `None` if the filter doesn't have any of these
+    /// constraints.
+    fn compute_candidates(
+        &self,
+        txn: &RoTxn,
+        filter: &TaskFilter,
+        range: RangeInclusive<TaskId>,
+    ) -> Result<Option<RoaringBitmap>> {
+        let mut sets = Vec::new();
+
+        if let Some(indexes) = filter.filtered_indexes() {
+            let keys = indexes.iter().map(String::as_str);
+            sets.push(union_of_sets(txn, self.index_uid_task_ids, keys)?);
+        }
+        if let Some(statuses) = filter.filtered_statuses() {
+            let keys = statuses.iter().map(|status| status.as_str());
+            sets.push(union_of_sets(txn, self.status_task_ids, keys)?);
+        }
+        if let Some(kinds) = filter.filtered_kinds() {
+            let keys = kinds.iter().map(|kind| kind.as_str());
+            sets.push(union_of_sets(txn, self.kind_task_ids, keys)?);
+        }
+        if let Some(uids) = filter.filtered_uids() {
+            sets.push(uids.clone());
         }
 
-        candidates.remove_range((Excluded(from), Unbounded));
+        let mut candidates = match sets.into_iter().reduce(|acc, set| acc & set) {
+            Some(candidates) => candidates,
+            None => return Ok(None),
+        };
 
-        let iter = candidates
-            .into_iter()
-            .rev()
-            .filter_map(|id| self.get(txn, id).transpose());
+        candidates.remove_range((Unbounded, Excluded(*range.start())));
+        candidates.remove_range((Excluded(*range.end()), Unbounded));
 
-        Ok(iter)
+        Ok(Some(candidates))
     }
 }
 
+/// Adds the task id to the set stored under the key.
+fn add_to_set(
+    txn: &mut RwTxn,
+    db: Database<Str, RoaringBitmapCodec>,
+    key: &str,
+    id: TaskId,
+) -> Result<()> {
+    let mut tasks_set = db.get(txn, key)?.unwrap_or_default();
+    tasks_set.insert(id);
+    db.put(txn, key, &tasks_set)?;
+
+    Ok(())
+}
+
+/// Removes the task id from the set stored under the key, and removes the set once it's empty.
+fn remove_from_set(
+    txn: &mut RwTxn,
+    db: Database<Str, RoaringBitmapCodec>,
+    key: &str,
+    id: TaskId,
+) -> Result<()> {
+    if let Some(mut tasks_set) = db.get(txn, key)? {
+        tasks_set.remove(id);
+        if tasks_set.is_empty() {
+            db.delete(txn, key)?;
+        } else {
+            db.put(txn, key, &tasks_set)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the union of the sets stored under the keys.
+fn union_of_sets<'a>(
+    txn: &RoTxn,
+    db: Database<Str, RoaringBitmapCodec>,
+    keys: impl Iterator<Item = &'a str>,
+) -> Result<RoaringBitmap> {
+    let mut union = RoaringBitmap::new();
+    for key in keys {
+        if let Some(tasks_set) = db.get(txn, key)? {
+            union |= tasks_set;
+        }
+    }
+
+    Ok(union)
+}
+
 #[cfg(test)]
 pub mod test {
     use itertools::Itertools;
     use milli::heed::EnvOpenOptions;
     use nelson::Mocker;
     use tempfile::TempDir;
+    use time::OffsetDateTime;
 
     use crate::index_resolver::IndexUid;
-    use crate::tasks::task::TaskContent;
+    use crate::tasks::task::{TaskContent, TaskEvent, TaskKind, TaskResult};
 
     use super::*;
 
@@ -247,6 +675,13 @@ pub mod test {
             }
         }
 
+        pub fn delete(&self, txn: &mut RwTxn, task: &Task) -> Result<()> {
+            match self {
+                MockStore::Real(index) => index.delete(txn, task),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn get(&self, txn: &RoTxn, id: TaskId) -> Result<Option<Task>> {
             match self {
                 MockStore::Real(index) => index.get(txn, id),
@@ -254,6 +689,17 @@ pub mod test {
             }
         }
 
+        pub fn task_ids_by_statuses(
+            &self,
+            txn: &RoTxn,
+            statuses: &[TaskStatus],
+        ) -> Result<RoaringBitmap> {
+            match self {
+                MockStore::Real(index) => index.task_ids_by_statuses(txn, statuses),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
         pub fn fetch_unfinished_tasks(
             &self,
             txn: &RoTxn,
@@ -277,6 +723,26 @@ pub mod test {
                 MockStore::Fake(_) => todo!(),
             }
         }
+
+        pub fn get_batch(&self, txn: &RoTxn, id: BatchId) -> Result<Option<Vec<Task>>> {
+            match self {
+                MockStore::Real(index) => index.get_batch(txn, id),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
+
+        pub fn list_batches(
+            &self,
+            txn: &RoTxn,
+            from: Option<BatchId>,
+            filter: Option<TaskFilter>,
+            limit: Option<usize>,
+        ) -> Result<Vec<(BatchId, Vec<Task>)>> {
+            match self {
+                MockStore::Real(index) => index.list_batches(txn, from, filter, limit),
+                MockStore::Fake(_) => todo!(),
+            }
+        }
     }
 
     #[test]
@@ -289,6 +755,7 @@ pub mod test {
                 id: rand::random(),
                 content: TaskContent::IndexDeletion {
                     index_uid: IndexUid::new_unchecked("test"),
+                    priority: Default::default(),
                 },
                 events: vec![],
             })
@@ -321,6 +788,7 @@ pub mod test {
             id: 1,
             content: TaskContent::IndexDeletion {
                 index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
             },
             events: vec![],
         };
@@ -329,6 +797,7 @@ pub mod test {
             id: 0,
             content: TaskContent::IndexDeletion {
                 index_uid: IndexUid::new_unchecked("test1"),
+                priority: Default::default(),
             },
             events: vec![],
         };
@@ -351,6 +820,7 @@ pub mod test {
             id: 0,
             content: TaskContent::IndexDeletion {
                 index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
             },
             events: vec![],
         };
@@ -358,6 +828,7 @@ pub mod test {
             id: 1,
             content: TaskContent::IndexDeletion {
                 index_uid: IndexUid::new_unchecked("test1"),
+                priority: Default::default(),
             },
             events: vec![],
         };
@@ -377,4 +848,236 @@ pub mod test {
             "test"
         );
     }
+
+    #[test]
+    fn test_list_tasks_enqueued_range() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let date = |secs: i64| OffsetDateTime::from_unix_timestamp(secs).unwrap();
+        let gen_task = |id: TaskId| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
+            },
+            events: vec![TaskEvent::Created(date(id as i64 * 10))],
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..10 {
+            store.put(&mut txn, &gen_task(id)).unwrap();
+        }
+        store.delete(&mut txn, &gen_task(4)).unwrap();
+
+        let mut filter = TaskFilter::default();
+        filter.filter_enqueued_after(date(25));
+        filter.filter_enqueued_before(date(70));
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[6, 5, 3]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_enqueued_after(date(25));
+        filter.filter_uid(1);
+        filter.filter_uid(8);
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[8]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_enqueued_after(date(100));
+        assert!(store
+            .list_tasks(&txn, None, Some(filter), None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_status_and_kind_indexes() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut task = Task {
+            id: 0,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked("test"),
+                priority: Default::default(),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+        };
+        let dump = Task {
+            id: 1,
+            content: TaskContent::Dump {
+                uid: "dump".to_string(),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &task).unwrap();
+        store.put(&mut txn, &dump).unwrap();
+
+        task.events.push(TaskEvent::succeeded(TaskResult::Other));
+        store.put(&mut txn, &task).unwrap();
+
+        let list = |status: Option<TaskStatus>, kind: Option<TaskKind>| {
+            let mut filter = TaskFilter::default();
+            if let Some(status) = status {
+                filter.filter_status(status);
+            }
+            if let Some(kind) = kind {
+                filter.filter_kind(kind);
+            }
+            store
+                .list_tasks(&txn, None, Some(filter), None)
+                .unwrap()
+                .iter()
+                .map(|t| t.id)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(list(Some(TaskStatus::Succeeded), None), &[0]);
+        assert_eq!(list(Some(TaskStatus::Enqueued), None), &[1]);
+        assert_eq!(list(None, Some(TaskKind::Dump)), &[1]);
+        assert!(list(Some(TaskStatus::Enqueued), Some(TaskKind::IndexDeletion)).is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_indexes() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..10 {
+            let task = Task {
+                id,
+                content: TaskContent::IndexDeletion {
+                    index_uid: IndexUid::new_unchecked("test"),
+                    priority: Default::default(),
+                },
+                events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+            };
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        // Simulate a store created before the secondary indexes existed.
+        store.index_uid_task_ids.clear(&mut txn).unwrap();
+        store.status_task_ids.clear(&mut txn).unwrap();
+        store.kind_task_ids.clear(&mut txn).unwrap();
+        store.enqueued_at_task_ids.clear(&mut txn).unwrap();
+        store.main.delete(&mut txn, INDEXES_VERSION_KEY).unwrap();
+        txn.commit().unwrap();
+        drop(store);
+
+        let store = Store::new(tmp.env()).unwrap();
+        let txn = store.rtxn().unwrap();
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        filter.filter_status(TaskStatus::Enqueued);
+        filter.filter_kind(TaskKind::IndexDeletion);
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.len(), 10);
+        assert!(!store.enqueued_at_task_ids.is_empty(&txn).unwrap());
+    }
+
+    #[test]
+    fn test_batch_index() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id: TaskId, index_uid: &str| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked(index_uid),
+                priority: Default::default(),
+            },
+            events: vec![TaskEvent::Created(OffsetDateTime::now_utc())],
+        };
+        let batched = |mut task: Task, batch_id: BatchId| {
+            task.events.push(TaskEvent::Batched {
+                timestamp: OffsetDateTime::now_utc(),
+                batch_id,
+            });
+            task
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        for id in 0..4 {
+            store.put(&mut txn, &gen_task(id, "test")).unwrap();
+        }
+        store.put(&mut txn, &gen_task(4, "other")).unwrap();
+
+        // The batch index is updated when a task gets batched.
+        for (id, index_uid, batch_id) in [
+            (0, "test", 0),
+            (1, "test", 0),
+            (2, "test", 2),
+            (4, "other", 4),
+        ] {
+            let task = batched(gen_task(id, index_uid), batch_id);
+            store.put(&mut txn, &task).unwrap();
+        }
+
+        let ids = |tasks: &[Task]| tasks.iter().map(|t| t.id).collect::<Vec<_>>();
+        let batch_ids = |batches: Vec<(BatchId, Vec<Task>)>| {
+            batches.into_iter().map(|(id, _)| id).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ids(&store.get_batch(&txn, 0).unwrap().unwrap()), &[0, 1]);
+        assert!(store.get_batch(&txn, 3).unwrap().is_none());
+
+        let batches = store.list_batches(&txn, None, None, None).unwrap();
+        assert_eq!(batch_ids(batches), &[4, 2, 0]);
+
+        let batches = store.list_batches(&txn, Some(3), None, Some(1)).unwrap();
+        assert_eq!(batch_ids(batches), &[2]);
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let batches = store.list_batches(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(batch_ids(batches), &[2, 0]);
+
+        // A batch disappears with its last task.
+        let task = store.get(&txn, 2).unwrap().unwrap();
+        store.delete(&mut txn, &task).unwrap();
+        assert!(store.get_batch(&txn, 2).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_delete_task() {
+        let tmp = tmp_env();
+        let store = Store::new(tmp.env()).unwrap();
+
+        let gen_task = |id: TaskId, index_uid: &str| Task {
+            id,
+            content: TaskContent::IndexDeletion {
+                index_uid: IndexUid::new_unchecked(index_uid),
+                priority: Default::default(),
+            },
+            events: vec![],
+        };
+
+        let mut txn = store.wtxn().unwrap();
+        store.put(&mut txn, &gen_task(0, "test")).unwrap();
+        store.put(&mut txn, &gen_task(1, "test1")).unwrap();
+        store.put(&mut txn, &gen_task(2, "test")).unwrap();
+
+        store.delete(&mut txn, &gen_task(2, "test")).unwrap();
+        store.delete(&mut txn, &gen_task(1, "test1")).unwrap();
+
+        assert!(store.get(&txn, 2).unwrap().is_none());
+        assert!(store
+            .index_uid_task_ids
+            .get(&txn, "test1")
+            .unwrap()
+            .is_none());
+
+        let mut filter = TaskFilter::default();
+        filter.filter_index("test".into());
+        let tasks = store.list_tasks(&txn, None, Some(filter), None).unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id).collect::<Vec<_>>(), &[0]);
+
+        // The ids of the deleted tasks must not be reused.
+        assert_eq!(store.next_task_id(&mut txn).unwrap(), 3);
+    }
 }
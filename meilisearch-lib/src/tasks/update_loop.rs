@@ -2,22 +2,25 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use time::OffsetDateTime;
-use tokio::sync::{watch, RwLock};
+use tokio::sync::{broadcast, watch, RwLock};
 use tokio::time::interval_at;
 
-use super::batch::Batch;
+use super::batch::{Batch, BatchContent};
 use super::error::Result;
 use super::{BatchHandler, Scheduler};
-use crate::tasks::task::TaskEvent;
+use crate::tasks::task::{Task, TaskEvent};
 
-/// The update loop sequentially performs batches of updates by asking the scheduler for a batch,
-/// and handing it to the `TaskPerformer`.
+/// The update loop performs batches of updates by asking the scheduler for a batch, and handing it
+/// to the `TaskPerformer`. The scheduler decides how many batches can be processed at the same
+/// time.
 pub struct UpdateLoop {
     scheduler: Arc<RwLock<Scheduler>>,
     performers: Vec<Arc<dyn BatchHandler + Send + Sync + 'static>>,
 
     notifier: Option<watch::Receiver<()>>,
     debounce_duration: Option<Duration>,
+    /// Broadcasts the tasks every time their new state is saved to the store.
+    events: broadcast::Sender<Task>,
 }
 
 impl UpdateLoop {
@@ -26,36 +29,57 @@ impl UpdateLoop {
         performers: Vec<Arc<dyn BatchHandler + Send + Sync + 'static>>,
         debuf_duration: Option<Duration>,
         notifier: watch::Receiver<()>,
+        events: broadcast::Sender<Task>,
     ) -> Self {
         Self {
             scheduler,
             performers,
             debounce_duration: debuf_duration,
             notifier: Some(notifier),
+            events,
         }
     }
 
     pub async fn run(mut self) {
         let mut notifier = self.notifier.take().unwrap();
+        let this = Arc::new(self);
 
         loop {
             if notifier.changed().await.is_err() {
                 break;
             }
 
-            if let Some(t) = self.debounce_duration {
+            if let Some(t) = this.debounce_duration {
                 let mut interval = interval_at(tokio::time::Instant::now() + t, t);
                 interval.tick().await;
             };
 
-            if let Err(e) = self.process_next_batch().await {
-                log::error!("an error occured while processing an update batch: {}", e);
+            // Start as many batches as the scheduler allows. It notifies us again when a batch is
+            // finished.
+            loop {
+                let batch = match this.scheduler.write().await.prepare().await {
+                    Ok(batch) if batch.is_empty() => break,
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        log::error!("an error occured while preparing an update batch: {}", e);
+                        break;
+                    }
+                };
+
+                let this = this.clone();
+                tokio::task::spawn_local(async move {
+                    let batch_id = batch.id;
+                    if let Err(e) = this.process_batch(batch).await {
+                        log::error!("an error occured while processing an update batch: {}", e);
+                        // The batch can't be finished normally, but its index must be released.
+                        this.scheduler.write().await.finish(batch_id);
+                    }
+                });
             }
         }
     }
 
-    async fn process_next_batch(&self) -> Result<()> {
-        let mut batch = { self.scheduler.write().await.prepare().await? };
+    async fn process_batch(&self, mut batch: Batch) -> Result<()> {
         let performer = self
             .performers
             .iter()
@@ -74,6 +98,7 @@ impl UpdateLoop {
                 .update_tasks(batch.content)
                 .await?
         };
+        self.publish(&batch.content);
 
         let batch = performer.process_batch(batch).await;
 
@@ -94,10 +119,18 @@ impl UpdateLoop {
     ) -> Result<()> {
         let mut scheduler = self.scheduler.write().await;
         let content = scheduler.update_tasks(batch.content).await?;
-        scheduler.finish();
+        scheduler.finish(batch.id);
         drop(scheduler);
         batch.content = content;
+        self.publish(&batch.content);
         performer.finish(&batch).await;
         Ok(())
     }
+
+    fn publish(&self, content: &BatchContent) {
+        for task in content.tasks() {
+            // An error only means that nobody is listening.
+            let _ = self.events.send(task.clone());
+        }
+    }
 }
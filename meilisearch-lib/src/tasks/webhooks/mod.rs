@@ -0,0 +1,450 @@
+mod store;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::join_all;
+use log::{error, warn};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{Notify, RwLock};
+use tokio::task::spawn_blocking;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use super::error::{Result, TaskError};
+use super::task::{Task, TaskEvent, TaskId};
+use super::{Scheduler, TaskFilter};
+
+use store::DeliveryId;
+pub use store::WebhookStore;
+
+/// Builds the body posted to the webhooks from a finished task.
+pub type WebhookPayload = fn(&Task) -> serde_json::Value;
+
+/// The time a webhook has to answer before the delivery is considered failed.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The delay before the first retry of a failed delivery. It is doubled after every failed attempt,
+/// up to `MAX_RETRY_DELAY`.
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(1);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// A delivery is dropped once it failed that many times.
+const MAX_DELIVERY_ATTEMPTS: u32 = 10;
+
+/// Interval between two checks of the deliveries that are due for a retry.
+const DELIVERY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A url that is called with every task reaching the `Succeeded` or `Failed` state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub uid: Uuid,
+    pub url: String,
+    /// The webhook is only called with the tasks of this index, or with every task if `None`.
+    pub index_uid: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl Webhook {
+    pub fn new(url: String, index_uid: Option<String>) -> Result<Self> {
+        match reqwest::Url::parse(&url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => (),
+            _ => return Err(TaskError::InvalidWebhookUrl(url)),
+        }
+
+        Ok(Self {
+            uid: Uuid::new_v4(),
+            url,
+            index_uid,
+            created_at: OffsetDateTime::now_utc(),
+        })
+    }
+
+    /// Returns whether the webhook must be called with the task. The tasks that finished before
+    /// the webhook was created are skipped, since they can be recovered after a restart.
+    fn matches(&self, task: &Task) -> bool {
+        let index = self
+            .index_uid
+            .as_deref()
+            .map_or(true, |index_uid| task.index_uid() == Some(index_uid));
+        let created = task
+            .finished_at()
+            .map_or(true, |finished_at| finished_at >= self.created_at);
+
+        index && created
+    }
+}
+
+/// The finish date of the last tasks the deliveries were recorded for, along with their ids. It is
+/// persisted with the deliveries, so that the tasks that finished while the webhooks weren't
+/// running are recorded on start.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordCursor {
+    #[serde(with = "time::serde::rfc3339")]
+    finished_at: OffsetDateTime,
+    task_ids: HashSet<TaskId>,
+}
+
+impl RecordCursor {
+    fn new(finished_at: OffsetDateTime) -> Self {
+        Self {
+            finished_at,
+            task_ids: HashSet::new(),
+        }
+    }
+
+    /// Moves the cursor past the task, if it finished after the cursor.
+    fn advance(&mut self, task: &Task) {
+        match task.finished_at() {
+            Some(date) if date > self.finished_at => {
+                self.finished_at = date;
+                self.task_ids.clear();
+                self.task_ids.insert(task.id);
+            }
+            Some(date) if date == self.finished_at => {
+                self.task_ids.insert(task.id);
+            }
+            _ => (),
+        }
+    }
+
+    /// Returns whether the task was already recorded.
+    fn contains(&self, task: &Task) -> bool {
+        task.finished_at()
+            .map_or(false, |date| date < self.finished_at)
+            || self.task_ids.contains(&task.id)
+    }
+}
+
+/// A call to a webhook that didn't succeed yet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Delivery {
+    webhook_uid: Uuid,
+    url: String,
+    payload: serde_json::Value,
+    attempts: u32,
+    #[serde(with = "time::serde::rfc3339")]
+    next_attempt_at: OffsetDateTime,
+}
+
+impl Delivery {
+    fn new(webhook: &Webhook, payload: serde_json::Value) -> Self {
+        Self {
+            webhook_uid: webhook.uid,
+            url: webhook.url.clone(),
+            payload,
+            attempts: 0,
+            next_attempt_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    /// Records a failed attempt and schedules the next one. Returns `false` when the delivery ran
+    /// out of attempts.
+    fn retry_later(&mut self) -> bool {
+        self.attempts += 1;
+        if self.attempts >= MAX_DELIVERY_ATTEMPTS {
+            return false;
+        }
+
+        let delay = FIRST_RETRY_DELAY
+            .saturating_mul(2u32.saturating_pow(self.attempts - 1))
+            .min(MAX_RETRY_DELAY);
+        self.next_attempt_at = OffsetDateTime::now_utc() + delay;
+
+        true
+    }
+}
+
+/// Records a delivery for every webhook matching a task that reached the `Succeeded` or `Failed`
+/// state, and delivers them.
+///
+/// The deliveries are persisted before being sent, so that they are retried after a restart.
+pub struct WebhookService {
+    pub(crate) store: WebhookStore,
+    pub(crate) scheduler: Arc<RwLock<Scheduler>>,
+    pub(crate) payload: WebhookPayload,
+}
+
+impl WebhookService {
+    pub async fn run(self) {
+        let notifier = Notify::new();
+        tokio::join!(self.record(&notifier), self.deliver(&notifier));
+    }
+
+    /// Runs an operation on the webhook store, out of the async runtime.
+    async fn with_store<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(&WebhookStore) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let store = self.store.clone();
+        spawn_blocking(move || f(&store)).await?
+    }
+
+    async fn record(&self, notifier: &Notify) {
+        let mut events = self.scheduler.read().await.subscribe();
+
+        // The recording resumes from the persisted cursor, or starts now on the first run.
+        let mut cursor = match self.with_store(|store| store.record_cursor()).await {
+            Ok(cursor) => cursor.unwrap_or_else(|| RecordCursor::new(OffsetDateTime::now_utc())),
+            Err(e) => {
+                error!("error while fetching the webhook cursor: {}", e);
+                RecordCursor::new(OffsetDateTime::now_utc())
+            }
+        };
+        // The tasks that finished since the cursor are fetched from the task store on start, and
+        // when the subscription lags behind.
+        let mut missed = true;
+        // The tasks fetched from the task store, that may still be received from the subscription.
+        let mut recovered = HashSet::new();
+
+        loop {
+            let tasks = if missed {
+                missed = false;
+                match self.finished_tasks_since(cursor.finished_at).await {
+                    Ok(tasks) => {
+                        let tasks: Vec<_> = tasks
+                            .into_iter()
+                            .filter(|task| !cursor.contains(task))
+                            .collect();
+                        recovered = tasks.iter().map(|task| task.id).collect();
+                        tasks
+                    }
+                    Err(e) => {
+                        error!("error while fetching the missed tasks: {}", e);
+                        continue;
+                    }
+                }
+            } else {
+                match events.recv().await {
+                    Ok(task) if recovered.remove(&task.id) => continue,
+                    Ok(task) => vec![task],
+                    Err(RecvError::Lagged(missed_changes)) => {
+                        warn!(
+                            "{} task state changes were missed by the webhooks, fetching them from the task store.",
+                            missed_changes
+                        );
+                        missed = true;
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            };
+
+            let tasks: Vec<_> = tasks
+                .into_iter()
+                .filter(|task| {
+                    matches!(
+                        task.events.last(),
+                        Some(TaskEvent::Succeeded { .. } | TaskEvent::Failed { .. })
+                    )
+                })
+                .collect();
+            if tasks.is_empty() {
+                continue;
+            }
+
+            for task in &tasks {
+                cursor.advance(task);
+            }
+
+            let payload = self.payload;
+            let record_cursor = cursor.clone();
+            let result = self
+                .with_store(move |store| {
+                    let webhooks = store.webhooks()?;
+                    let mut deliveries = Vec::new();
+                    for task in &tasks {
+                        let matching = webhooks.iter().filter(|webhook| webhook.matches(task));
+                        deliveries
+                            .extend(matching.map(|webhook| Delivery::new(webhook, payload(task))));
+                    }
+                    store.enqueue_deliveries(&deliveries, &record_cursor)
+                })
+                .await;
+
+            match result {
+                Ok(()) => notifier.notify_one(),
+                Err(e) => error!("error while recording the webhook deliveries: {}", e),
+            }
+        }
+    }
+
+    /// Returns the tasks that finished at or after the given date, in the order they were
+    /// enqueued.
+    async fn finished_tasks_since(&self, date: OffsetDateTime) -> Result<Vec<Task>> {
+        let mut filter = TaskFilter::default();
+        filter.filter_finished_after(date - Duration::from_nanos(1));
+        let mut tasks = self
+            .scheduler
+            .read()
+            .await
+            .list_tasks(None, Some(filter), None)
+            .await?;
+        tasks.reverse();
+
+        Ok(tasks)
+    }
+
+    async fn deliver(&self, notifier: &Notify) {
+        let client = reqwest::Client::new();
+
+        loop {
+            let now = OffsetDateTime::now_utc();
+            let deliveries = match self
+                .with_store(move |store| store.due_deliveries(now))
+                .await
+            {
+                Ok(deliveries) => deliveries,
+                Err(e) => {
+                    error!("error while fetching the webhook deliveries: {}", e);
+                    Vec::new()
+                }
+            };
+
+            // The deliveries to an url are made in order, but the urls are called concurrently so
+            // that a slow endpoint doesn't hold back the others.
+            let mut deliveries_by_url: HashMap<_, Vec<_>> = HashMap::new();
+            for (id, delivery) in deliveries {
+                deliveries_by_url
+                    .entry(delivery.url.clone())
+                    .or_default()
+                    .push((id, delivery));
+            }
+
+            join_all(
+                deliveries_by_url
+                    .into_values()
+                    .map(|deliveries| self.deliver_in_order(&client, deliveries)),
+            )
+            .await;
+
+            tokio::select! {
+                _ = notifier.notified() => (),
+                _ = sleep(DELIVERY_POLL_INTERVAL) => (),
+            }
+        }
+    }
+
+    /// Attempts the deliveries one after the other, and records their outcome.
+    async fn deliver_in_order(
+        &self,
+        client: &reqwest::Client,
+        deliveries: Vec<(DeliveryId, Delivery)>,
+    ) {
+        for (id, mut delivery) in deliveries {
+            let response = client
+                .post(&delivery.url)
+                .timeout(DELIVERY_TIMEOUT)
+                .json(&delivery.payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            let done = match response {
+                Ok(_) => true,
+                Err(e) if delivery.retry_later() => {
+                    warn!(
+                        "webhook delivery to {} failed, retrying: {}",
+                        delivery.url, e
+                    );
+                    false
+                }
+                Err(e) => {
+                    error!(
+                        "webhook delivery to {} failed, giving up: {}",
+                        delivery.url, e
+                    );
+                    true
+                }
+            };
+
+            let result = self
+                .with_store(move |store| {
+                    if done {
+                        store.delete_delivery(id)
+                    } else {
+                        store.put_delivery(id, &delivery)
+                    }
+                })
+                .await;
+
+            if let Err(e) = result {
+                error!("error while updating a webhook delivery: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tasks::task::{TaskContent, TaskResult};
+
+    use super::*;
+
+    #[test]
+    fn webhook_url_validation() {
+        assert!(Webhook::new("http://localhost:7700/hook".to_string(), None).is_ok());
+        assert!(Webhook::new("https://example.com".to_string(), None).is_ok());
+        assert!(Webhook::new("ftp://example.com".to_string(), None).is_err());
+        assert!(Webhook::new("example.com/hook".to_string(), None).is_err());
+    }
+
+    #[test]
+    fn delivery_backoff() {
+        let webhook = Webhook::new("http://localhost".to_string(), None).unwrap();
+        let mut delivery = Delivery::new(&webhook, serde_json::Value::Null);
+
+        let mut previous = delivery.next_attempt_at;
+        for _ in 1..MAX_DELIVERY_ATTEMPTS {
+            assert!(delivery.retry_later());
+            assert!(delivery.next_attempt_at > previous);
+            previous = delivery.next_attempt_at;
+        }
+
+        assert!(!delivery.retry_later());
+    }
+
+    #[test]
+    fn record_cursor() {
+        let now = OffsetDateTime::now_utc();
+        let finished_task = |id: TaskId, finished_at: OffsetDateTime| Task {
+            id,
+            content: TaskContent::Dump {
+                uid: id.to_string(),
+            },
+            events: vec![
+                TaskEvent::Created(now),
+                TaskEvent::Succeeded {
+                    result: TaskResult::Other,
+                    timestamp: finished_at,
+                },
+            ],
+        };
+
+        let mut cursor = RecordCursor::new(now);
+        let first = finished_task(0, now + Duration::from_secs(1));
+        let second = finished_task(1, now + Duration::from_secs(1));
+        let third = finished_task(2, now + Duration::from_secs(2));
+        assert!(!cursor.contains(&first));
+
+        cursor.advance(&first);
+        cursor.advance(&second);
+        assert!(cursor.contains(&first));
+        assert!(cursor.contains(&second));
+        assert!(!cursor.contains(&third));
+
+        cursor.advance(&third);
+        assert!(cursor.contains(&first));
+        assert!(cursor.contains(&third));
+
+        // a task that finished earlier doesn't move the cursor back.
+        cursor.advance(&finished_task(3, now));
+        assert_eq!(cursor.finished_at, now + Duration::from_secs(2));
+    }
+}
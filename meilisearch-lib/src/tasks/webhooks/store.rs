@@ -0,0 +1,247 @@
+use std::sync::Arc;
+
+use milli::heed::types::{ByteSlice, OwnedType, SerdeJson, Str};
+use milli::heed::{Database, Env};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::super::Result;
+use super::{Delivery, RecordCursor, Webhook};
+
+#[allow(clippy::upper_case_acronyms)]
+type BEU64 = milli::heed::zerocopy::U64<milli::heed::byteorder::BE>;
+
+/// The id of a pending delivery.
+pub type DeliveryId = BEU64;
+
+const WEBHOOKS: &str = "webhooks";
+const WEBHOOK_DELIVERIES: &str = "webhook-deliveries";
+const WEBHOOK_CURSOR: &str = "webhook-cursor";
+
+const RECORD_CURSOR_KEY: &str = "record-cursor";
+
+/// Persists the registered webhooks and the deliveries that are not done yet.
+#[derive(Clone)]
+pub struct WebhookStore {
+    env: Arc<Env>,
+    /// Maps a webhook uid to the webhook.
+    webhooks: Database<ByteSlice, SerdeJson<Webhook>>,
+    /// Maps an incrementing id to a pending delivery, so that deliveries are made in the order
+    /// they were recorded.
+    deliveries: Database<OwnedType<BEU64>, SerdeJson<Delivery>>,
+    /// Holds the cursor of the last tasks the deliveries were recorded for.
+    cursor: Database<Str, SerdeJson<RecordCursor>>,
+}
+
+impl WebhookStore {
+    pub fn new(env: Arc<Env>) -> Result<Self> {
+        let webhooks = env.create_database(Some(WEBHOOKS))?;
+        let deliveries = env.create_database(Some(WEBHOOK_DELIVERIES))?;
+        let cursor = env.create_database(Some(WEBHOOK_CURSOR))?;
+
+        Ok(Self {
+            env,
+            webhooks,
+            deliveries,
+            cursor,
+        })
+    }
+
+    pub fn put_webhook(&self, webhook: &Webhook) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.webhooks
+            .put(&mut txn, webhook.uid.as_bytes(), webhook)?;
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    pub fn webhook(&self, uid: Uuid) -> Result<Option<Webhook>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.webhooks.get(&txn, uid.as_bytes())?)
+    }
+
+    /// Returns all the webhooks, by order of creation.
+    pub fn webhooks(&self) -> Result<Vec<Webhook>> {
+        let txn = self.env.read_txn()?;
+        let mut webhooks = self
+            .webhooks
+            .iter(&txn)?
+            .map(|result| result.map(|(_, webhook)| webhook))
+            .collect::<milli::heed::Result<Vec<_>>>()?;
+        webhooks.sort_by_key(|webhook| webhook.created_at);
+
+        Ok(webhooks)
+    }
+
+    /// Deletes a webhook along with its pending deliveries. Returns `false` if the webhook didn't
+    /// exist.
+    pub fn delete_webhook(&self, uid: Uuid) -> Result<bool> {
+        let mut txn = self.env.write_txn()?;
+        if !self.webhooks.delete(&mut txn, uid.as_bytes())? {
+            return Ok(false);
+        }
+
+        let mut ids = Vec::new();
+        for result in self.deliveries.iter(&txn)? {
+            let (id, delivery) = result?;
+            if delivery.webhook_uid == uid {
+                ids.push(id);
+            }
+        }
+        for id in ids {
+            self.deliveries.delete(&mut txn, &id)?;
+        }
+        txn.commit()?;
+
+        Ok(true)
+    }
+
+    /// Returns the cursor of the last tasks the deliveries were recorded for, if any were.
+    pub fn record_cursor(&self) -> Result<Option<RecordCursor>> {
+        let txn = self.env.read_txn()?;
+        Ok(self.cursor.get(&txn, RECORD_CURSOR_KEY)?)
+    }
+
+    /// Enqueues the deliveries of some finished tasks, and moves the record cursor past these
+    /// tasks in the same transaction.
+    pub fn enqueue_deliveries(&self, deliveries: &[Delivery], cursor: &RecordCursor) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.cursor.put(&mut txn, RECORD_CURSOR_KEY, cursor)?;
+        let mut next_id = self
+            .deliveries
+            .last(&txn)?
+            .map_or(0, |(id, _)| id.get() + 1);
+        for delivery in deliveries {
+            self.deliveries
+                .put(&mut txn, &BEU64::new(next_id), delivery)?;
+            next_id += 1;
+        }
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Returns the deliveries that should be attempted at `now`, along with their ids.
+    pub fn due_deliveries(&self, now: OffsetDateTime) -> Result<Vec<(DeliveryId, Delivery)>> {
+        let txn = self.env.read_txn()?;
+        let mut deliveries = Vec::new();
+        for result in self.deliveries.iter(&txn)? {
+            let (id, delivery) = result?;
+            if delivery.next_attempt_at <= now {
+                deliveries.push((id, delivery));
+            }
+        }
+
+        Ok(deliveries)
+    }
+
+    pub fn put_delivery(&self, id: DeliveryId, delivery: &Delivery) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.deliveries.put(&mut txn, &id, delivery)?;
+        txn.commit()?;
+
+        Ok(())
+    }
+
+    pub fn delete_delivery(&self, id: DeliveryId) -> Result<()> {
+        let mut txn = self.env.write_txn()?;
+        self.deliveries.delete(&mut txn, &id)?;
+        txn.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use milli::heed::EnvOpenOptions;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn store() -> (TempDir, WebhookStore) {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 100000);
+        options.max_dbs(10);
+        let env = Arc::new(options.open(tmp.path()).unwrap());
+
+        (tmp, WebhookStore::new(env).unwrap())
+    }
+
+    #[test]
+    fn test_put_and_delete_webhooks() {
+        let (_tmp, store) = store();
+
+        let first = Webhook::new("http://localhost/first".to_string(), None).unwrap();
+        let second = Webhook::new(
+            "http://localhost/second".to_string(),
+            Some("test".to_string()),
+        )
+        .unwrap();
+        store.put_webhook(&first).unwrap();
+        store.put_webhook(&second).unwrap();
+
+        assert_eq!(store.webhook(second.uid).unwrap(), Some(second.clone()));
+        assert_eq!(
+            store.webhooks().unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        assert!(store.delete_webhook(first.uid).unwrap());
+        assert!(!store.delete_webhook(first.uid).unwrap());
+        assert_eq!(store.webhook(first.uid).unwrap(), None);
+        assert_eq!(store.webhooks().unwrap(), vec![second]);
+    }
+
+    #[test]
+    fn test_deliveries() {
+        let (_tmp, store) = store();
+
+        let first = Webhook::new("http://localhost/first".to_string(), None).unwrap();
+        let second = Webhook::new("http://localhost/second".to_string(), None).unwrap();
+        store.put_webhook(&first).unwrap();
+        store.put_webhook(&second).unwrap();
+
+        assert_eq!(store.record_cursor().unwrap(), None);
+        let cursor = RecordCursor::new(OffsetDateTime::now_utc());
+        store
+            .enqueue_deliveries(
+                &[
+                    Delivery::new(&first, json!(1)),
+                    Delivery::new(&second, json!(2)),
+                ],
+                &cursor,
+            )
+            .unwrap();
+        assert_eq!(store.record_cursor().unwrap(), Some(cursor));
+
+        let due = store.due_deliveries(OffsetDateTime::now_utc()).unwrap();
+        assert_eq!(due.len(), 2);
+        assert_eq!(due[0].1.payload, json!(1));
+        assert_eq!(due[1].1.payload, json!(2));
+
+        // a delivery that is retried later is not due anymore.
+        let (id, mut delivery) = due[0].clone();
+        assert!(delivery.retry_later());
+        store.put_delivery(id, &delivery).unwrap();
+        let due = store.due_deliveries(OffsetDateTime::now_utc()).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.payload, json!(2));
+
+        // deleting a webhook drops its deliveries.
+        store.delete_webhook(second.uid).unwrap();
+        assert!(store
+            .due_deliveries(OffsetDateTime::now_utc())
+            .unwrap()
+            .is_empty());
+
+        store.delete_delivery(id).unwrap();
+        assert!(store
+            .due_deliveries(delivery.next_attempt_at)
+            .unwrap()
+            .is_empty());
+    }
+}
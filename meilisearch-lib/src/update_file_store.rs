@@ -2,6 +2,7 @@ use std::fs::{create_dir_all, File};
 use std::io::{self, BufReader, BufWriter, Write};
 use std::ops::{Deref, DerefMut};
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use milli::documents::DocumentBatchReader;
 use serde_json::Map;
@@ -176,6 +177,25 @@ mod store {
             Ok(self.get_update(uuid)?.metadata()?.len())
         }
 
+        /// Returns the uuids of the update files that were last modified before the given time.
+        pub fn list_updates(&self, modified_before: SystemTime) -> Result<Vec<Uuid>> {
+            let mut uuids = Vec::new();
+            for entry in std::fs::read_dir(&self.path)? {
+                let entry = entry?;
+                // Skip the temporary files of the updates that are not persisted yet.
+                let uuid = match entry.file_name().to_str().map(Uuid::parse_str) {
+                    Some(Ok(uuid)) => uuid,
+                    _ => continue,
+                };
+
+                if entry.metadata()?.modified()? < modified_before {
+                    uuids.push(uuid);
+                }
+            }
+
+            Ok(uuids)
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             let path = self.path.join(uuid.to_string());
             tokio::fs::remove_file(path).await?;
@@ -246,6 +266,13 @@ mod test {
             }
         }
 
+        pub fn list_updates(&self, modified_before: SystemTime) -> Result<Vec<Uuid>> {
+            match self {
+                MockUpdateFileStore::Real(s) => s.list_updates(modified_before),
+                MockUpdateFileStore::Mock(_) => todo!(),
+            }
+        }
+
         pub async fn delete(&self, uuid: Uuid) -> Result<()> {
             match self {
                 MockUpdateFileStore::Real(s) => s.delete(uuid).await,
@@ -124,6 +124,60 @@ pub fn map_leaf_values_in_array(
     }
 }
 
+/// Applies a [JSON merge patch](https://www.rfc-editor.org/rfc/rfc7396) to an object.
+/// The objects of the patch are merged in the objects of the document, a `null` removes the
+/// field, and any other value replaces it.
+/// ```
+/// use serde_json::*;
+/// use permissive_json_pointer::merge_patch;
+///
+/// let mut value: Value = json!({
+///     "name": "peanut",
+///     "price": { "eur": 10, "usd": 11 },
+///     "tags": ["dog", "good boy"],
+/// });
+/// let patch: Value = json!({
+///     "price": { "eur": 12, "usd": null },
+///     "tags": ["dog"],
+///     "age": 8,
+/// });
+///
+/// merge_patch(value.as_object_mut().unwrap(), patch.as_object().unwrap());
+/// assert_eq!(
+///     value,
+///     json!({
+///         "name": "peanut",
+///         "price": { "eur": 12 },
+///         "tags": ["dog"],
+///         "age": 8,
+///     })
+/// );
+/// ```
+pub fn merge_patch(document: &mut Map<String, Value>, patch: &Map<String, Value>) {
+    for (key, value) in patch {
+        match value {
+            Value::Null => {
+                document.remove(key);
+            }
+            Value::Object(patch) => {
+                let value = document
+                    .entry(key.clone())
+                    .or_insert_with(|| Value::Object(Map::new()));
+                // Only objects are merged, any other value is replaced by the patched object.
+                if !value.is_object() {
+                    *value = Value::Object(Map::new());
+                }
+                if let Value::Object(object) = value {
+                    merge_patch(object, patch);
+                }
+            }
+            value => {
+                document.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 /// Permissively selects values in a json with a list of selectors.
 /// Returns a new json containing all the selected fields.
 /// ```
@@ -240,6 +294,60 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_merge_patch() {
+        // the examples of the RFC 7396
+        let cases = [
+            (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+            (
+                json!({"a": "b"}),
+                json!({"b": "c"}),
+                json!({"a": "b", "b": "c"}),
+            ),
+            (json!({"a": "b"}), json!({"a": null}), json!({})),
+            (
+                json!({"a": "b", "b": "c"}),
+                json!({"a": null}),
+                json!({"b": "c"}),
+            ),
+            (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+            (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+            (
+                json!({"a": {"b": "c"}}),
+                json!({"a": {"b": "d", "c": null}}),
+                json!({"a": {"b": "d"}}),
+            ),
+            (
+                json!({"a": [{"b": "c"}]}),
+                json!({"a": [1]}),
+                json!({"a": [1]}),
+            ),
+            (
+                json!({"e": null}),
+                json!({"a": 1}),
+                json!({"e": null, "a": 1}),
+            ),
+            (
+                json!({"a": "foo"}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+            (
+                json!({}),
+                json!({"a": {"bb": {"ccc": null}}}),
+                json!({"a": {"bb": {}}}),
+            ),
+        ];
+
+        for (mut document, patch, expected) in cases {
+            merge_patch(
+                document.as_object_mut().unwrap(),
+                patch.as_object().unwrap(),
+            );
+            assert_eq!(document, expected, "patch: {}", patch);
+        }
+    }
+
     #[test]
     fn test_contained_in() {
         assert!(contained_in("animaux", "animaux"));